@@ -0,0 +1,491 @@
+use crate::interval::*;
+
+// Outward-rounded interval arithmetic.
+//
+// Every operation below returns an interval guaranteed to enclose the exact real
+// result of the operation applied to any pair of reals taken from the operands.
+// Rust offers no control over the FPU rounding mode, so each bound is computed
+// with the default round-to-nearest, its exact rounding error is recovered with an
+// error-free transformation (TwoSum or a fused multiply-add), and the bound is
+// moved one ulp outward only when the rounding went the wrong way. Exact results
+// are therefore kept tight.
+//
+// Any operation involving an empty interval returns the empty interval.
+
+// EXACT_ERROR_MIN is 2^-968. The rounding error of a product, quotient or square
+// root is a multiple of the smallest subnormal, hence exactly computed by a fused
+// multiply-add, as long as the magnitude of the product, of the dividend or of
+// the radicand is at least this. Below it the error may round to zero, so its
+// sign is unknown.
+const EXACT_ERROR_MIN: f64 = f64::MIN_POSITIVE * (1u64 << 54) as f64;
+
+// round_down returns the largest float not greater than the exact value v + err,
+// where v is the rounded result and err the (sign of the) rounding error. A NaN
+// error means the error could not be computed, e.g. on overflow or underflow.
+fn round_down(v: f64, err: f64) -> f64 {
+    if err.is_nan() || err < 0.0 {
+        return v.next_down();
+    }
+
+    return v;
+}
+
+// round_up returns the smallest float not less than the exact value v + err.
+fn round_up(v: f64, err: f64) -> f64 {
+    if err.is_nan() || err > 0.0 {
+        return v.next_up();
+    }
+
+    return v;
+}
+
+// two_sum returns a + b rounded to nearest together with its exact rounding error.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    if !s.is_finite() {
+        if a.is_finite() && b.is_finite() {
+            return (s, f64::NAN);
+        }
+        return (s, 0.0);
+    }
+
+    let bb = s - a;
+    let err = (a - (s - bb)) + (b - bb);
+
+    return (s, err);
+}
+
+// two_product returns a * b rounded to nearest together with its exact rounding
+// error.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    if p.is_nan() {
+        // 0 * ∞: the enclosure of {0} is all we can meaningfully return.
+        return (0.0, 0.0);
+    }
+
+    if !p.is_finite() {
+        if a.is_finite() && b.is_finite() {
+            return (p, f64::NAN);
+        }
+        return (p, 0.0);
+    }
+
+    if p.abs() < EXACT_ERROR_MIN && a != 0.0 && b != 0.0 {
+        return (p, f64::NAN);
+    }
+
+    return (p, a.mul_add(b, -p));
+}
+
+fn add_down(a: f64, b: f64) -> f64 {
+    let (s, err) = two_sum(a, b);
+    return round_down(s, err);
+}
+
+fn add_up(a: f64, b: f64) -> f64 {
+    let (s, err) = two_sum(a, b);
+    return round_up(s, err);
+}
+
+fn mul_down(a: f64, b: f64) -> f64 {
+    let (p, err) = two_product(a, b);
+    return round_down(p, err);
+}
+
+fn mul_up(a: f64, b: f64) -> f64 {
+    let (p, err) = two_product(a, b);
+    return round_up(p, err);
+}
+
+// quotient returns a / b rounded to nearest along with a value having the sign of
+// its rounding error. b must not be zero.
+fn quotient(a: f64, b: f64) -> (f64, f64) {
+    let q = a / b;
+    if !q.is_finite() {
+        if a.is_finite() {
+            return (q, f64::NAN);
+        }
+        return (q, 0.0);
+    }
+
+    if !a.is_finite() || !b.is_finite() {
+        return (q, 0.0);
+    }
+
+    // The remainder of a subnormal quotient may round to zero as well.
+    if (a.abs() < EXACT_ERROR_MIN || q.abs() < f64::MIN_POSITIVE) && a != 0.0 {
+        return (q, f64::NAN);
+    }
+
+    // The exact quotient is q + r / b, with r = a - q * b computed exactly.
+    let r = (-q).mul_add(b, a);
+    if b < 0.0 {
+        return (q, -r);
+    }
+
+    return (q, r);
+}
+
+fn div_down(a: f64, b: f64) -> f64 {
+    let (q, err) = quotient(a, b);
+    return round_down(q, err);
+}
+
+fn div_up(a: f64, b: f64) -> f64 {
+    let (q, err) = quotient(a, b);
+    return round_up(q, err);
+}
+
+// sqrt_bounds returns the square root of a non-negative a rounded down and up.
+fn sqrt_bounds(a: f64) -> (f64, f64) {
+    let s = a.sqrt();
+    if !s.is_finite() || s == 0.0 {
+        return (s, s);
+    }
+
+    if a < EXACT_ERROR_MIN {
+        return (s.next_down(), s.next_up());
+    }
+
+    // The exact square root is larger than s iff a - s * s > 0.
+    let r = (-s).mul_add(s, a);
+
+    return (round_down(s, r), round_up(s, r));
+}
+
+impl Interval {
+    // add returns an enclosure of { x + y : x ∈ self, y ∈ oi }.
+    pub fn add(self, oi: Interval) -> Interval {
+        if self.is_empty() || oi.is_empty() {
+            return empty_interval();
+        }
+
+        return Interval {
+            lo: add_down(self.lo, oi.lo),
+            hi: add_up(self.hi, oi.hi),
+        };
+    }
+
+    // sub returns an enclosure of { x - y : x ∈ self, y ∈ oi }.
+    pub fn sub(self, oi: Interval) -> Interval {
+        return self.add(oi.neg());
+    }
+
+    // neg returns { -x : x ∈ self }. Negation is always exact.
+    pub fn neg(self) -> Interval {
+        if self.is_empty() {
+            return empty_interval();
+        }
+
+        return Interval {
+            lo: -self.hi,
+            hi: -self.lo,
+        };
+    }
+
+    // mul returns an enclosure of { x * y : x ∈ self, y ∈ oi }.
+    pub fn mul(self, oi: Interval) -> Interval {
+        if self.is_empty() || oi.is_empty() {
+            return empty_interval();
+        }
+
+        let corners = [
+            (self.lo, oi.lo),
+            (self.lo, oi.hi),
+            (self.hi, oi.lo),
+            (self.hi, oi.hi),
+        ];
+
+        let mut res = Interval {
+            lo: f64::INFINITY,
+            hi: f64::NEG_INFINITY,
+        };
+        for (a, b) in corners {
+            res.lo = res.lo.min(mul_down(a, b));
+            res.hi = res.hi.max(mul_up(a, b));
+        }
+
+        return res;
+    }
+
+    // div returns an enclosure of { x / y : x ∈ self, y ∈ oi }. When oi contains
+    // zero the quotient is unbounded and the whole real line is returned.
+    pub fn div(self, oi: Interval) -> Interval {
+        if self.is_empty() || oi.is_empty() {
+            return empty_interval();
+        }
+
+        if oi.contains(0.0) {
+            return Interval {
+                lo: f64::NEG_INFINITY,
+                hi: f64::INFINITY,
+            };
+        }
+
+        let corners = [
+            (self.lo, oi.lo),
+            (self.lo, oi.hi),
+            (self.hi, oi.lo),
+            (self.hi, oi.hi),
+        ];
+
+        let mut res = Interval {
+            lo: f64::INFINITY,
+            hi: f64::NEG_INFINITY,
+        };
+        for (a, b) in corners {
+            res.lo = res.lo.min(div_down(a, b));
+            res.hi = res.hi.max(div_up(a, b));
+        }
+
+        return res;
+    }
+
+    // square returns an enclosure of { x * x : x ∈ self }. Unlike self.mul(self),
+    // the result never contains negative values.
+    pub fn square(self) -> Interval {
+        if self.is_empty() {
+            return empty_interval();
+        }
+
+        let lo = self.lo.abs();
+        let hi = self.hi.abs();
+
+        if self.contains(0.0) {
+            return Interval {
+                lo: 0.0,
+                hi: mul_up(lo.max(hi), lo.max(hi)),
+            };
+        }
+
+        let (small, large) = (lo.min(hi), lo.max(hi));
+
+        return Interval {
+            lo: mul_down(small, small),
+            hi: mul_up(large, large),
+        };
+    }
+
+    // sqrt returns an enclosure of { √x : x ∈ self, x ≥ 0 }. The negative part of
+    // the interval is ignored, and an interval lying entirely below zero yields the
+    // empty interval.
+    pub fn sqrt(self) -> Interval {
+        if self.is_empty() || self.hi < 0.0 {
+            return empty_interval();
+        }
+
+        let (lo, _) = sqrt_bounds(self.lo.max(0.0));
+        let (_, hi) = sqrt_bounds(self.hi);
+
+        return Interval { lo, hi };
+    }
+
+    // add_scalar returns an enclosure of { x + m : x ∈ self }.
+    pub fn add_scalar(self, m: f64) -> Interval {
+        return self.add(interval_from_point(m));
+    }
+
+    // sub_scalar returns an enclosure of { x - m : x ∈ self }.
+    pub fn sub_scalar(self, m: f64) -> Interval {
+        return self.sub(interval_from_point(m));
+    }
+
+    // mul_scalar returns an enclosure of { x * m : x ∈ self }.
+    pub fn mul_scalar(self, m: f64) -> Interval {
+        return self.mul(interval_from_point(m));
+    }
+
+    // div_scalar returns an enclosure of { x / m : x ∈ self }.
+    pub fn div_scalar(self, m: f64) -> Interval {
+        return self.div(interval_from_point(m));
+    }
+
+    // is_certainly_positive reports whether every value of the interval is > 0.
+    pub fn is_certainly_positive(self) -> bool {
+        return !self.is_empty() && self.lo > 0.0;
+    }
+
+    // is_certainly_negative reports whether every value of the interval is < 0.
+    pub fn is_certainly_negative(self) -> bool {
+        return !self.is_empty() && self.hi < 0.0;
+    }
+
+    // certain_sign returns the sign shared by every value of the interval:
+    //
+    //	-1 if the interval lies below zero
+    //	 0 if the interval is exactly [0, 0]
+    //	+1 if the interval lies above zero
+    //
+    // and None if the sign cannot be decided, i.e. when the interval contains zero
+    // along with other values, or is empty.
+    pub fn certain_sign(self) -> Option<i64> {
+        if self.is_certainly_positive() {
+            return Some(1);
+        }
+
+        if self.is_certainly_negative() {
+            return Some(-1);
+        }
+
+        if self.lo == 0.0 && self.hi == 0.0 {
+            return Some(0);
+        }
+
+        return None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(p: f64) -> Interval {
+        interval_from_point(p)
+    }
+
+    #[test]
+    fn exact_operations_stay_tight() {
+        let a = Interval { lo: 1.0, hi: 2.0 };
+        let b = Interval { lo: 3.0, hi: 4.0 };
+
+        assert_eq!(Interval { lo: 4.0, hi: 6.0 }, a.add(b));
+        assert_eq!(Interval { lo: -3.0, hi: -1.0 }, a.sub(b));
+        assert_eq!(Interval { lo: 3.0, hi: 8.0 }, a.mul(b));
+        assert_eq!(Interval { lo: 1.0, hi: 4.0 }, a.square());
+        assert_eq!(Interval { lo: 2.0, hi: 4.0 }, a.mul_scalar(2.0));
+        assert_eq!(Interval { lo: 0.5, hi: 1.0 }, a.div_scalar(2.0));
+        assert_eq!(Interval { lo: -1.0, hi: 0.0 }, a.sub_scalar(2.0));
+
+        let quotient = a.div(b);
+        assert_eq!(0.25, quotient.lo);
+        assert_eq!((2.0f64 / 3.0).next_up(), quotient.hi);
+        assert_eq!(
+            Interval { lo: 1.0, hi: 2.0 },
+            Interval { lo: 1.0, hi: 4.0 }.sqrt()
+        );
+    }
+
+    #[test]
+    fn inexact_operations_enclose() {
+        let sum = point(0.1).add(point(0.2));
+        assert!(sum.lo < sum.hi);
+        assert!(sum.contains(0.1 + 0.2));
+        assert!(sum.hi.next_down() <= sum.lo.next_up());
+
+        let third = point(1.0).div(point(3.0));
+        assert!(third.lo < third.hi);
+        assert!(third.lo.mul_add(3.0, -1.0) < 0.0);
+        assert!(third.hi.mul_add(3.0, -1.0) > 0.0);
+
+        let root = point(2.0).sqrt();
+        assert!(root.lo < root.hi);
+        assert!(root.lo.mul_add(root.lo, -2.0) < 0.0);
+        assert!(root.hi.mul_add(root.hi, -2.0) > 0.0);
+
+        let product = point(0.1).mul(point(0.1));
+        assert!(product.lo < product.hi);
+        assert!(product.contains(0.1 * 0.1));
+    }
+
+    #[test]
+    fn special_cases() {
+        let (empty, unit) = (empty_interval(), Interval { lo: 0.0, hi: 1.0 });
+
+        assert!(empty.add(unit).is_empty());
+        assert!(unit.mul(empty).is_empty());
+        assert!(Interval { lo: -2.0, hi: -1.0 }.sqrt().is_empty());
+
+        let unbounded = unit.div(Interval { lo: -1.0, hi: 1.0 });
+        assert_eq!(f64::NEG_INFINITY, unbounded.lo);
+        assert_eq!(f64::INFINITY, unbounded.hi);
+
+        assert_eq!(
+            Interval { lo: 0.0, hi: 9.0 },
+            Interval { lo: -3.0, hi: 2.0 }.square()
+        );
+        assert_eq!(
+            Interval { lo: -6.0, hi: 4.0 },
+            Interval { lo: -3.0, hi: 2.0 }.mul(Interval { lo: -1.0, hi: 2.0 })
+        );
+
+        let overflow = point(f64::MAX).add(point(f64::MAX));
+        assert_eq!(f64::MAX, overflow.lo);
+        assert_eq!(f64::INFINITY, overflow.hi);
+    }
+
+    #[test]
+    fn underflow() {
+        // Each of these products and quotients has an error which the fused
+        // multiply-add cannot represent, even when the result itself is normal.
+        // The result must still enclose the exact value, of the given sign.
+        let tiny = point(1e-200);
+        let tests_array: [(Interval, i64); 7] = [
+            (tiny.mul(tiny), 1),
+            (tiny.square(), 1),
+            (tiny.mul_scalar(-1e-200), -1),
+            (point(3e-300).mul(point(1e-10)), 1),
+            (tiny.div(point(-1e200)), -1),
+            (point(1.5e-154).square(), 1),
+            (point(3e-300).div(point(7e-300)), 1),
+        ];
+        for (interval, sign) in tests_array {
+            assert!(interval.lo < interval.hi);
+            assert!(if sign > 0 {
+                interval.hi > 0.0
+            } else {
+                interval.lo < 0.0
+            });
+            assert_ne!(Some(0), interval.certain_sign());
+            assert_ne!(Some(-sign), interval.certain_sign());
+        }
+        assert_eq!(None, tiny.mul(tiny).certain_sign());
+
+        assert_eq!(point(0.0), point(0.0).mul(tiny));
+    }
+
+    #[test]
+    fn subnormal_sqrt() {
+        // The residual a - s * s of these square roots is below the subnormal
+        // range, so the bounds cannot be checked by a fused multiply-add.
+        let tests_array: [f64; 5] = [
+            f64::from_bits(1),
+            f64::from_bits(3),
+            f64::from_bits(0x000f_ffff_ffff_ffff),
+            f64::MIN_POSITIVE * 3.0,
+            1e-300,
+        ];
+        for a in tests_array {
+            let Interval { lo, hi } = point(a).sqrt();
+            assert!(lo < hi);
+            assert!(lo >= 0.0);
+            // lo * lo <= a <= hi * hi, checked after scaling everything by a power
+            // of two so that the residuals are exact again.
+            let scale = 2f64.powi(600);
+            let (lo, hi, a) = (lo * scale, hi * scale, a * scale * scale);
+            assert!(lo.mul_add(lo, -a) <= 0.0);
+            assert!(hi.mul_add(hi, -a) >= 0.0);
+        }
+    }
+
+    #[test]
+    fn sign() {
+        let tests_array: [(Interval, Option<i64>); 5] = [
+            (Interval { lo: 1.0, hi: 2.0 }, Some(1)),
+            (Interval { lo: -2.0, hi: -1.0 }, Some(-1)),
+            (Interval { lo: 0.0, hi: 0.0 }, Some(0)),
+            (Interval { lo: -1.0, hi: 1.0 }, None),
+            (empty_interval(), None),
+        ];
+
+        for (interval, want) in tests_array {
+            assert_eq!(want, interval.certain_sign());
+            assert_eq!(want == Some(1), interval.is_certainly_positive());
+            assert_eq!(want == Some(-1), interval.is_certainly_negative());
+        }
+
+        // A determinant whose floating-point value is unreliable.
+        let det = point(0.1).mul(point(0.3)).sub(point(0.03));
+        assert!(det.contains(0.1 * 0.3 - 0.03) && det.lo < det.hi);
+    }
+}
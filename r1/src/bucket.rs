@@ -0,0 +1,224 @@
+use crate::interval::*;
+
+// Bucketing splits an interval, the domain, into count consecutive buckets.
+//
+// Buckets are closed, like every Interval, so two neighbouring buckets share their
+// common boundary. A value falling exactly on such a boundary is assigned to the
+// upper bucket, except for the upper bound of the domain which belongs to the last
+// bucket. Values outside the domain are first moved into it with clamp_point, so
+// they are assigned to the first or the last bucket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bucketing {
+    pub domain: Interval,
+    pub count: usize,
+    pub scale: BucketScale,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BucketScale {
+    // Linear buckets all have the same length.
+    Linear,
+    // Logarithmic buckets all have the same ratio between their bounds. The domain
+    // must then be positive.
+    Logarithmic,
+}
+
+impl Interval {
+    // linear_buckets splits the interval into count buckets of equal length.
+    // It panics if count is zero.
+    pub fn linear_buckets(self, count: usize) -> Bucketing {
+        assert!(count > 0, "bucket count must be positive");
+
+        Bucketing {
+            domain: self,
+            count,
+            scale: BucketScale::Linear,
+        }
+    }
+
+    // log_buckets splits the interval into count buckets whose bounds form a
+    // geometric progression, e.g. [1, 10], [10, 100] and [100, 1000] for [1, 1000]
+    // and three buckets. It panics if count is zero or if the interval is not empty
+    // and has a lower bound that is not positive.
+    pub fn log_buckets(self, count: usize) -> Bucketing {
+        assert!(count > 0, "bucket count must be positive");
+        assert!(
+            self.is_empty() || self.lo > 0.0,
+            "logarithmic buckets need a positive domain"
+        );
+
+        Bucketing {
+            domain: self,
+            count,
+            scale: BucketScale::Logarithmic,
+        }
+    }
+}
+
+impl Bucketing {
+    // boundary returns the lower bound of bucket k, or the upper bound of the
+    // domain when k is count. The bounds of the domain are returned exactly.
+    fn boundary(self, k: usize) -> f64 {
+        if k == 0 {
+            return self.domain.lo;
+        }
+
+        if k >= self.count {
+            return self.domain.hi;
+        }
+
+        let t = k as f64 / self.count as f64;
+        match self.scale {
+            BucketScale::Linear => self.domain.lo + t * self.domain.length(),
+            BucketScale::Logarithmic => self.domain.lo * (self.domain.hi / self.domain.lo).powf(t),
+        }
+    }
+
+    // bucket returns the index of the bucket the value belongs to, or None if the
+    // domain is empty or the value is NaN.
+    pub fn bucket(self, value: f64) -> Option<usize> {
+        if self.domain.is_empty() || value.is_nan() {
+            return None;
+        }
+
+        let v = self.domain.clamp_point(value);
+        let t = match self.scale {
+            BucketScale::Linear => (v - self.domain.lo) / self.domain.length(),
+            BucketScale::Logarithmic => {
+                (v / self.domain.lo).ln() / (self.domain.hi / self.domain.lo).ln()
+            }
+        };
+
+        // The estimate is off by one at most when rounding errors push a value
+        // lying close to a boundary across it, so it is fixed up against the
+        // boundaries returned by interval.
+        let last = self.count - 1;
+        let mut k = ((t * self.count as f64).floor().max(0.0) as usize).min(last);
+        while k > 0 && v < self.boundary(k) {
+            k -= 1;
+        }
+        while k < last && v >= self.boundary(k + 1) {
+            k += 1;
+        }
+
+        return Some(k);
+    }
+
+    // interval returns the bucket at the given index, which must be less than
+    // count.
+    pub fn interval(self, index: usize) -> Interval {
+        assert!(index < self.count, "bucket index out of range");
+
+        if self.domain.is_empty() {
+            return empty_interval();
+        }
+
+        Interval {
+            lo: self.boundary(index),
+            hi: self.boundary(index + 1),
+        }
+    }
+
+    // overlapping returns the index and bounds of every bucket intersecting the
+    // given interval, in increasing order. As buckets are closed, a query
+    // touching a boundary returns the buckets on both of its sides.
+    pub fn overlapping(self, query: Interval) -> impl Iterator<Item = (usize, Interval)> {
+        let range = if self.domain.intersects(query) {
+            let mut first = self.bucket(query.lo).unwrap_or(0);
+            if first > 0 && self.boundary(first) == self.domain.clamp_point(query.lo) {
+                first -= 1;
+            }
+            let last = self.bucket(query.hi).unwrap_or(0);
+            first..last + 1
+        } else {
+            0..0
+        };
+
+        return range.map(move |k| (k, self.interval(k)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tolerance::Tolerance;
+
+    fn interval(lo: f64, hi: f64) -> Interval {
+        Interval { lo, hi }
+    }
+
+    #[test]
+    fn linear() {
+        let buckets = interval(0.0, 10.0).linear_buckets(5);
+
+        let tests_array: [(f64, Option<usize>); 8] = [
+            (-3.0, Some(0)),
+            (0.0, Some(0)),
+            (1.9, Some(0)),
+            (2.0, Some(1)),
+            (9.0, Some(4)),
+            (10.0, Some(4)),
+            (42.0, Some(4)),
+            (f64::NAN, None),
+        ];
+
+        for (value, want) in tests_array {
+            assert_eq!(want, buckets.bucket(value));
+        }
+
+        assert_eq!(interval(4.0, 6.0), buckets.interval(2));
+        assert_eq!(interval(8.0, 10.0), buckets.interval(4));
+        assert_eq!(None, empty_interval().linear_buckets(3).bucket(1.0));
+    }
+
+    #[test]
+    fn logarithmic() {
+        let buckets = interval(1.0, 1000.0).log_buckets(3);
+
+        let tests_array: [(f64, usize); 6] = [
+            (0.5, 0),
+            (9.99, 0),
+            (10.0, 1),
+            (99.0, 1),
+            (100.0, 2),
+            (1000.0, 2),
+        ];
+
+        for (value, want) in tests_array {
+            assert_eq!(Some(want), buckets.bucket(value));
+        }
+
+        let b = buckets.interval(1);
+        assert!(b.approx_equal_with(interval(10.0, 100.0), Tolerance::Relative(1e-12)));
+        assert_eq!(1000.0, buckets.interval(2).hi);
+    }
+
+    #[test]
+    fn consistent_with_intervals() {
+        let buckets = interval(0.1, 0.7).linear_buckets(7);
+        for k in 0..7 {
+            let b = buckets.interval(k);
+            assert_eq!(Some(k), buckets.bucket(b.lo));
+            assert_eq!(Some(k), buckets.bucket(b.center()));
+        }
+    }
+
+    #[test]
+    fn overlapping() {
+        let buckets = interval(0.0, 10.0).linear_buckets(5);
+        let indices = |query: Interval| -> Vec<usize> {
+            buckets.overlapping(query).map(|(k, _)| k).collect()
+        };
+
+        assert_eq!(vec![1, 2], indices(interval(3.0, 5.0)));
+        assert_eq!(vec![0, 1, 2], indices(interval(-1.0, 4.0)));
+        assert_eq!(vec![1, 2], indices(interval(4.0, 4.0)));
+        assert_eq!(vec![4], indices(interval(10.0, 20.0)));
+        assert_eq!(Vec::<usize>::new(), indices(interval(11.0, 20.0)));
+        assert_eq!(Vec::<usize>::new(), indices(empty_interval()));
+
+        for (k, b) in buckets.overlapping(interval(-5.0, 15.0)) {
+            assert_eq!(buckets.interval(k), b);
+        }
+    }
+}
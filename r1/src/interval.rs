@@ -1,9 +1,14 @@
-use std::f64::INFINITY;
+use crate::parse::*;
+use crate::scalar::*;
+use crate::tolerance::*;
+use std::fmt;
+use std::str::FromStr;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
-pub struct Interval {
-    pub lo: f64,
-    pub hi: f64,
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub struct Interval<T = f64> {
+    pub lo: T,
+    pub hi: T,
 }
 
 pub fn empty_interval() -> Interval {
@@ -14,35 +19,52 @@ pub fn interval_from_point(p: f64) -> Interval {
     Interval { lo: p, hi: p }
 }
 
-// epsilon is a small number that represents a reasonable level of noise between two
-// values that can be considered to be equal.
-const EPSILON: f64 = 1e-16;
 // dblEpsilon is a smaller number for values that require more precision.
 // This is the C++ DBL_EPSILON equivalent.
+#[allow(dead_code)]
 const DBL_EPSILON: f64 = 2.220446049250313e-16;
 
-impl Interval {
+impl<T: Scalar> Interval<T> {
+    // empty returns the canonical empty interval of the given scalar type, the
+    // generic counterpart of empty_interval.
+    pub fn empty() -> Interval<T> {
+        Interval {
+            lo: T::one(),
+            hi: T::zero(),
+        }
+    }
+
+    // from_point returns the interval reduced to the single point p, the generic
+    // counterpart of interval_from_point.
+    pub fn from_point(p: T) -> Interval<T> {
+        Interval { lo: p, hi: p }
+    }
+
     pub fn is_empty(self) -> bool {
         self.lo > self.hi
     }
 
-    pub fn equal(self, oi: Interval) -> bool {
+    pub fn equal(self, oi: Interval<T>) -> bool {
         return self == oi || (self.is_empty() && oi.is_empty());
     }
 
-    pub fn center(self) -> f64 {
-        0.5 * (self.hi + self.lo)
+    // center returns the midpoint of the interval. For integer scalars it is
+    // rounded towards lo.
+    pub fn center(self) -> T {
+        T::midpoint(self.lo, self.hi)
     }
 
-    pub fn length(self) -> f64 {
-        self.hi - self.lo
+    // length returns hi - lo. It is negative for empty floating-point intervals
+    // but zero for empty integer intervals, see Scalar::span.
+    pub fn length(self) -> T {
+        T::span(self.lo, self.hi)
     }
 
-    pub fn contains(self, p: f64) -> bool {
+    pub fn contains(self, p: T) -> bool {
         self.lo <= p && p <= self.hi
     }
 
-    pub fn contains_interval(self, oi: Interval) -> bool {
+    pub fn contains_interval(self, oi: Interval<T>) -> bool {
         if oi.is_empty() {
             return true;
         }
@@ -50,13 +72,11 @@ impl Interval {
         self.lo <= oi.lo && oi.hi <= self.hi
     }
 
-    pub fn interior_contains(self, p: f64) -> bool {
+    pub fn interior_contains(self, p: T) -> bool {
         self.lo < p && p < self.hi
     }
 
-    pub fn interior_contains_interval(self, oi: Interval) -> bool {
-        println!("oi.is_empty() = {}", oi.is_empty());
-
+    pub fn interior_contains_interval(self, oi: Interval<T>) -> bool {
         if oi.is_empty() {
             return true;
         }
@@ -64,7 +84,7 @@ impl Interval {
         self.lo < oi.lo && oi.hi < self.hi
     }
 
-    pub fn intersects(self, oi: Interval) -> bool {
+    pub fn intersects(self, oi: Interval<T>) -> bool {
         if self.lo <= oi.lo {
             return oi.lo <= self.hi && oi.lo <= oi.hi; // oi.Lo ∈ i and oi is not empty
         }
@@ -72,18 +92,18 @@ impl Interval {
         self.lo <= oi.hi && self.lo <= self.hi // i.Lo ∈ oi and i is not empty
     }
 
-    pub fn interior_intersects(self, oi: Interval) -> bool {
+    pub fn interior_intersects(self, oi: Interval<T>) -> bool {
         return oi.lo < self.hi && self.lo < oi.hi && self.lo < self.hi && oi.lo <= oi.hi;
     }
 
-    pub fn intersection(self, oi: Interval) -> Interval {
+    pub fn intersection(self, oi: Interval<T>) -> Interval<T> {
         Interval {
             lo: self.lo.max(oi.lo),
             hi: self.hi.min(oi.hi),
         }
     }
 
-    pub fn add_point(self, p: f64) -> Interval {
+    pub fn add_point(self, p: T) -> Interval<T> {
         if self.is_empty() {
             return Interval { lo: p, hi: p };
         }
@@ -99,22 +119,24 @@ impl Interval {
         return self;
     }
 
-    pub fn clamp_point(self, p: f64) -> f64 {
+    pub fn clamp_point(self, p: T) -> T {
         return self.lo.max(self.hi.min(p));
     }
 
-    pub fn expanded(self, margin: f64) -> Interval {
+    // expanded returns the interval grown by margin on both sides, or shrunk if
+    // margin is negative. Integer bounds saturate at the limits of their type.
+    pub fn expanded(self, margin: T) -> Interval<T> {
         if self.is_empty() {
             return self;
         }
 
         Interval {
-            lo: self.lo - margin,
-            hi: self.hi + margin,
+            lo: self.lo.saturating_sub(margin),
+            hi: self.hi.saturating_add(margin),
         }
     }
 
-    pub fn union(self, other: Interval) -> Interval {
+    pub fn union(self, other: Interval<T>) -> Interval<T> {
         if self.is_empty() {
             return other;
         }
@@ -129,20 +151,44 @@ impl Interval {
         };
     }
 
+    // cast converts the interval to another scalar type, see Scalar::from_f64.
+    // Empty intervals stay empty.
+    pub fn cast<U: Scalar>(self) -> Interval<U> {
+        if self.is_empty() {
+            return Interval::empty();
+        }
+
+        return Interval {
+            lo: U::from_f64(self.lo.to_f64()),
+            hi: U::from_f64(self.hi.to_f64()),
+        };
+    }
+}
+
+impl Interval {
     pub fn string(self) -> String {
         format!("[{:.7}, {:.7}]", self.lo, self.hi)
     }
 
+    // approx_equal reports whether the two intervals are equal up to the default
+    // tolerance, see approx_equal_with.
     pub fn approx_equal(self, other: Interval) -> bool {
+        return self.approx_equal_with(other, Tolerance::default());
+    }
+
+    // approx_equal_with reports whether each bound of the two intervals is equal up
+    // to the given tolerance. An empty interval is approximately equal to any
+    // interval that the tolerance can collapse into an empty one.
+    pub fn approx_equal_with(self, other: Interval, tolerance: Tolerance) -> bool {
         if self.is_empty() {
-            return other.length() <= 2.0 * EPSILON;
+            return tolerance.collapses(other.lo, other.hi);
         }
 
         if other.is_empty() {
-            return self.length() <= 2.0 * EPSILON;
+            return tolerance.collapses(self.lo, self.hi);
         }
 
-        return (other.lo - self.lo).abs() <= EPSILON && (other.hi - self.hi).abs() <= EPSILON;
+        return tolerance.approx_eq(self.lo, other.lo) && tolerance.approx_eq(self.hi, other.hi);
     }
 
     pub fn directed_hausdorff_distance(self, other: Interval) -> f64 {
@@ -151,15 +197,158 @@ impl Interval {
         }
 
         if other.is_empty() {
-            return INFINITY;
+            return f64::INFINITY;
         }
 
         let zero: f64 = 0.0;
         return zero.max((self.hi - other.hi).max(other.lo - self.lo));
     }
+
+    // hausdorff_distance returns the Hausdorff distance between the two intervals,
+    // the largest of the two directed Hausdorff distances.
+    pub fn hausdorff_distance(self, other: Interval) -> f64 {
+        return self
+            .directed_hausdorff_distance(other)
+            .max(other.directed_hausdorff_distance(self));
+    }
+
+    // distance returns the gap between the two intervals, i.e. the smallest distance
+    // between a point of one and a point of the other. It is 0 when the intervals
+    // intersect, and infinite when either of them is empty.
+    pub fn distance(self, other: Interval) -> f64 {
+        if self.is_empty() || other.is_empty() {
+            return f64::INFINITY;
+        }
+
+        let zero: f64 = 0.0;
+        return zero.max((other.lo - self.hi).max(self.lo - other.hi));
+    }
+
+    // distance_to_point returns the distance from p to the closest point of the
+    // interval, or infinity if the interval is empty.
+    pub fn distance_to_point(self, p: f64) -> f64 {
+        return self.distance(interval_from_point(p));
+    }
+
+    // overlap_ratio returns the fraction of this interval covered by the other one.
+    // A degenerate interval is either fully covered (1) or not at all (0), and an
+    // empty interval is never covered.
+    pub fn overlap_ratio(self, other: Interval) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        let length = self.length();
+        if length == 0.0 {
+            return if other.contains(self.lo) { 1.0 } else { 0.0 };
+        }
+
+        return self.intersection(other).length().max(0.0) / length;
+    }
+
+    // intersection_over_union returns the length of the intersection of the two
+    // intervals divided by the length of their union, from 0 for disjoint intervals
+    // to 1 for equal ones. When the union has no length, the result is 1 if the
+    // intervals are the same point and 0 otherwise.
+    pub fn intersection_over_union(self, other: Interval) -> f64 {
+        if self.is_empty() || other.is_empty() {
+            return 0.0;
+        }
+
+        let inter = self.intersection(other).length().max(0.0);
+        let union = self.length() + other.length() - inter;
+        if union == 0.0 {
+            return if self == other { 1.0 } else { 0.0 };
+        }
+
+        return inter / union;
+    }
 }
 
+// Display writes the interval in the "[lo, hi]" form used by string(). Formatting
+// options apply to both bounds, so format!("{:.7}", i) is i.string(), while the
+// default shortest representation parses back to the very same interval.
+impl<T: fmt::Display> fmt::Display for Interval<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        fmt::Display::fmt(&self.lo, f)?;
+        f.write_str(", ")?;
+        fmt::Display::fmt(&self.hi, f)?;
+        f.write_str("]")
+    }
+}
+
+// FromStr parses the "[lo, hi]" form written by string() and Display. Empty
+// intervals are parsed as written, so they remain empty.
+impl<T: FromStr> FromStr for Interval<T> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut scanner = Scanner::new(s);
+
+        scanner.expect("[")?;
+        let lo = scanner.number()?;
+        scanner.expect(",")?;
+        let hi = scanner.number()?;
+        scanner.expect("]")?;
+        scanner.finish()?;
+
+        return Ok(Interval { lo, hi });
+    }
+}
+
+// Serialize writes the interval as a { lo, hi } struct. Empty intervals have
+// many representations, all of them are written as the canonical empty interval
+// so that they deserialize to values that are equal according to equal().
+#[cfg(feature = "serde")]
+impl<T: Scalar + serde::Serialize> serde::Serialize for Interval<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let i = if self.is_empty() {
+            Interval::empty()
+        } else {
+            *self
+        };
+
+        let mut state = serializer.serialize_struct("Interval", 2)?;
+        state.serialize_field("lo", &i.lo)?;
+        state.serialize_field("hi", &i.hi)?;
+        return state.end();
+    }
+}
+
+// Lossless conversions between interval precisions. Use Interval::cast for the
+// other ones.
+macro_rules! impl_interval_from {
+    ($($from:ty => $to:ty),*) => {$(
+        impl From<Interval<$from>> for Interval<$to> {
+            fn from(i: Interval<$from>) -> Self {
+                Interval {
+                    lo: i.lo.into(),
+                    hi: i.hi.into(),
+                }
+            }
+        }
+    )*};
+}
+
+impl_interval_from!(
+    f32 => f64,
+    i32 => f64,
+    u32 => f64,
+    i16 => f32,
+    u16 => f32,
+    i32 => i64,
+    u32 => u64
+);
+
 #[cfg(test)]
+#[allow(
+    clippy::module_inception,
+    clippy::bool_assert_comparison,
+    clippy::needless_late_init
+)]
 mod interval {
     use super::*;
 
@@ -760,4 +949,229 @@ mod interval {
             assert_eq!(test.want, res)
         }
     }
+
+    #[test]
+    fn generic_scalars() {
+        let pixels: Interval<u32> = Interval { lo: 3, hi: 8 };
+
+        assert_eq!(5, pixels.center());
+        assert_eq!(5, pixels.length());
+        assert!(pixels.contains(8));
+        assert_eq!(0, Interval::<u32>::empty().length());
+        assert!(pixels.intersection(Interval { lo: 9, hi: 12 }).is_empty());
+
+        // Expanding integer intervals saturates at the bounds of the type.
+        assert_eq!(
+            Interval { lo: 0, hi: 6 },
+            Interval::<u32> { lo: 0, hi: 5 }.expanded(1)
+        );
+        assert_eq!(Interval { lo: 2, hi: 9 }, pixels.expanded(1));
+        assert!(pixels.expanded(3).equal(Interval { lo: 0, hi: 11 }));
+        assert_eq!(
+            Interval { lo: -128, hi: 127 },
+            Interval::<i8> { lo: -120, hi: 120 }.expanded(10)
+        );
+        assert!(Interval::<i8> { lo: -2, hi: 2 }.expanded(-3).is_empty());
+
+        // The length and center of signed intervals do not overflow either.
+        assert_eq!(127, Interval::<i8> { lo: -100, hi: 100 }.length());
+        assert_eq!(
+            -1,
+            Interval::<i32> {
+                lo: i32::MIN,
+                hi: i32::MAX
+            }
+            .center()
+        );
+
+        let gpu: Interval<f32> = Interval { lo: 0.25, hi: 0.75 };
+        assert_eq!(0.5, gpu.center());
+        assert!(gpu.cast::<f64>().equal(Interval { lo: 0.25, hi: 0.75 }));
+        assert!(Interval::from(gpu).equal(Interval { lo: 0.25, hi: 0.75 }));
+        assert!(empty_interval().cast::<u8>().is_empty());
+        assert_eq!(Interval { lo: 0u8, hi: 2u8 }, unit_cast());
+    }
+
+    fn unit_cast() -> Interval<u8> {
+        Interval { lo: -0.4, hi: 1.6 }.cast()
+    }
+
+    #[test]
+    fn parse() {
+        let i = Interval { lo: 0.1, hi: 4.5 };
+
+        assert_eq!(i, i.to_string().parse().unwrap());
+        assert_eq!(i.string(), format!("{:.7}", i));
+        assert!(i.string().parse::<Interval>().unwrap().approx_equal(i));
+        assert!(empty_interval()
+            .string()
+            .parse::<Interval>()
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            Interval { lo: -3, hi: 7 },
+            " [ -3 ,7 ] ".parse::<Interval<i32>>().unwrap()
+        );
+
+        let tests_array: [(&str, ParseError); 4] = [
+            (
+                "2.0, 4.5]",
+                ParseError {
+                    position: 0,
+                    kind: ParseErrorKind::Expected("["),
+                },
+            ),
+            (
+                "[2.0; 4.5]",
+                ParseError {
+                    position: 4,
+                    kind: ParseErrorKind::Expected(","),
+                },
+            ),
+            (
+                "[2.0, 4.x5]",
+                ParseError {
+                    position: 6,
+                    kind: ParseErrorKind::InvalidNumber,
+                },
+            ),
+            (
+                "[2.0, 4.5] x",
+                ParseError {
+                    position: 11,
+                    kind: ParseErrorKind::TrailingCharacters,
+                },
+            ),
+        ];
+
+        for (input, want) in tests_array {
+            assert_eq!(Err(want), input.parse::<Interval>());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let unit = Interval { lo: 0.0, hi: 1.0 };
+        let json = serde_json::to_string(&unit).unwrap();
+        assert_eq!(r#"{"lo":0.0,"hi":1.0}"#, json);
+        assert_eq!(unit, serde_json::from_str(&json).unwrap());
+
+        for empty in [empty_interval(), Interval { lo: 5.0, hi: -3.0 }] {
+            let json = serde_json::to_string(&empty).unwrap();
+            assert_eq!(r#"{"lo":1.0,"hi":0.0}"#, json);
+
+            let back: Interval = serde_json::from_str(&json).unwrap();
+            assert!(back.equal(empty));
+        }
+    }
+
+    #[test]
+    fn approx_equal_with() {
+        let i = Interval { lo: 1.0, hi: 2.0 };
+        let shifted = Interval {
+            lo: 1.0f64.next_up(),
+            hi: 2.0f64.next_down(),
+        };
+
+        assert!(i.approx_equal_with(shifted, Tolerance::Ulps(1)));
+        assert!(!i.approx_equal_with(shifted, Tolerance::Ulps(0)));
+        assert!(i.approx_equal_with(
+            Interval { lo: 1.1, hi: 1.9 },
+            Tolerance::Absolute(0.1 + 1e-12)
+        ));
+        assert!(Interval {
+            lo: 1000.0,
+            hi: 2000.0
+        }
+        .approx_equal_with(
+            Interval {
+                lo: 1000.5,
+                hi: 2001.0
+            },
+            Tolerance::Relative(1e-3)
+        ));
+        assert!(empty_interval().approx_equal_with(i, Tolerance::Absolute(0.5)));
+        assert!(i.approx_equal_with(empty_interval(), Tolerance::Absolute(0.5)));
+        assert!(!i.approx_equal_with(empty_interval(), Tolerance::Absolute(0.4)));
+    }
+
+    struct MetricsTest {
+        x: Interval,
+        y: Interval,
+        hausdorff: f64,
+        distance: f64,
+        overlap_ratio: f64,
+        iou: f64,
+    }
+
+    #[test]
+    fn metrics() {
+        let (empty, unit, negunit, half) = setup_interval();
+
+        let tests_array: [MetricsTest; 6] = [
+            MetricsTest {
+                x: unit,
+                y: unit,
+                hausdorff: 0.0,
+                distance: 0.0,
+                overlap_ratio: 1.0,
+                iou: 1.0,
+            },
+            MetricsTest {
+                x: unit,
+                y: negunit,
+                hausdorff: 1.0,
+                distance: 0.0,
+                overlap_ratio: 0.0,
+                iou: 0.0,
+            },
+            MetricsTest {
+                x: unit,
+                y: Interval { lo: 0.5, hi: 2.5 },
+                hausdorff: 1.5,
+                distance: 0.0,
+                overlap_ratio: 0.5,
+                iou: 0.2,
+            },
+            MetricsTest {
+                x: unit,
+                y: Interval { lo: 3.0, hi: 4.0 },
+                hausdorff: 3.0,
+                distance: 2.0,
+                overlap_ratio: 0.0,
+                iou: 0.0,
+            },
+            MetricsTest {
+                x: half,
+                y: unit,
+                hausdorff: 0.5,
+                distance: 0.0,
+                overlap_ratio: 1.0,
+                iou: 0.0,
+            },
+            MetricsTest {
+                x: empty,
+                y: unit,
+                hausdorff: f64::INFINITY,
+                distance: f64::INFINITY,
+                overlap_ratio: 0.0,
+                iou: 0.0,
+            },
+        ];
+
+        for test in tests_array {
+            assert_eq!(test.hausdorff, test.x.hausdorff_distance(test.y));
+            assert_eq!(test.hausdorff, test.y.hausdorff_distance(test.x));
+            assert_eq!(test.distance, test.x.distance(test.y));
+            assert_eq!(test.distance, test.y.distance(test.x));
+            assert_eq!(test.overlap_ratio, test.x.overlap_ratio(test.y));
+            assert_eq!(test.iou, test.x.intersection_over_union(test.y));
+            assert_eq!(test.iou, test.y.intersection_over_union(test.x));
+        }
+
+        assert_eq!(1.0, half.intersection_over_union(half));
+        assert_eq!(2.0, unit.distance_to_point(3.0));
+        assert_eq!(0.0, unit.distance_to_point(0.3));
+    }
 }
@@ -0,0 +1,301 @@
+use crate::interval::*;
+
+// IntervalSet represents a union of closed intervals. The intervals are kept
+// sorted by their lower bound, non-empty and pairwise disjoint: intervals that
+// overlap or touch are merged together.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+// interval_set_from_intervals builds a normalized set from an arbitrary list of
+// intervals. Empty intervals are dropped.
+pub fn interval_set_from_intervals(intervals: &[Interval]) -> IntervalSet {
+    let mut sorted: Vec<Interval> = intervals
+        .iter()
+        .copied()
+        .filter(|i| !i.is_empty())
+        .collect();
+    sorted.sort_by(|a, b| a.lo.total_cmp(&b.lo));
+
+    let mut merged: Vec<Interval> = Vec::with_capacity(sorted.len());
+    for i in sorted {
+        match merged.last_mut() {
+            Some(last) if last.intersects(i) => *last = last.union(i),
+            _ => merged.push(i),
+        }
+    }
+
+    return IntervalSet { intervals: merged };
+}
+
+impl IntervalSet {
+    pub fn new() -> IntervalSet {
+        IntervalSet::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    // len returns the number of disjoint intervals in the set.
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Interval> {
+        self.intervals.iter()
+    }
+
+    // bound returns the smallest interval containing every interval of the set,
+    // which is what Interval::union would have produced.
+    pub fn bound(&self) -> Interval {
+        match (self.intervals.first(), self.intervals.last()) {
+            (Some(first), Some(last)) => Interval {
+                lo: first.lo,
+                hi: last.hi,
+            },
+            _ => empty_interval(),
+        }
+    }
+
+    // length returns the total length covered by the set.
+    pub fn length(&self) -> f64 {
+        return self.intervals.iter().map(|i| i.length()).sum();
+    }
+
+    // contains reports whether the point belongs to one of the intervals.
+    pub fn contains(&self, p: f64) -> bool {
+        // Index of the first interval starting strictly after p.
+        let idx = self.intervals.partition_point(|i| i.lo <= p);
+        if idx == 0 {
+            return false;
+        }
+
+        return self.intervals[idx - 1].contains(p);
+    }
+
+    // contains_interval reports whether the given interval is entirely covered by
+    // the set. As for Interval, the empty interval is contained in any set.
+    pub fn contains_interval(&self, oi: Interval) -> bool {
+        if oi.is_empty() {
+            return true;
+        }
+
+        let idx = self.intervals.partition_point(|i| i.lo <= oi.lo);
+        if idx == 0 {
+            return false;
+        }
+
+        return self.intervals[idx - 1].contains_interval(oi);
+    }
+
+    // add_interval returns the set extended with the given interval.
+    pub fn add_interval(&self, oi: Interval) -> IntervalSet {
+        let mut intervals = self.intervals.clone();
+        intervals.push(oi);
+
+        return interval_set_from_intervals(&intervals);
+    }
+
+    // union returns the set of points belonging to either set.
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = self.intervals.clone();
+        intervals.extend_from_slice(&other.intervals);
+
+        return interval_set_from_intervals(&intervals);
+    }
+
+    // intersection returns the set of points belonging to both sets.
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals: Vec<Interval> = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let a = self.intervals[i];
+            let b = other.intervals[j];
+
+            let inter = a.intersection(b);
+            if !inter.is_empty() {
+                intervals.push(inter);
+            }
+
+            if a.hi < b.hi {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        return IntervalSet { intervals };
+    }
+
+    // difference returns the points of this set that do not belong to other.
+    // Since the set only holds closed intervals, the result is the closure of the
+    // exact set difference: removing [1, 2] from [0, 3] yields [0, 1] and [2, 3].
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals: Vec<Interval> = Vec::new();
+        let mut j = 0;
+
+        for a in &self.intervals {
+            let mut lo = a.lo;
+
+            // Skip the intervals of other lying entirely before a.
+            while j < other.intervals.len() && other.intervals[j].hi < a.lo {
+                j += 1;
+            }
+
+            let mut k = j;
+            while k < other.intervals.len() && other.intervals[k].lo <= a.hi {
+                let b = other.intervals[k];
+                if lo < b.lo {
+                    intervals.push(Interval { lo, hi: b.lo });
+                }
+                lo = lo.max(b.hi);
+                k += 1;
+            }
+
+            if lo < a.hi || (lo == a.lo && lo == a.hi && k == j) {
+                intervals.push(Interval { lo, hi: a.hi });
+            }
+        }
+
+        return interval_set_from_intervals(&intervals);
+    }
+
+    // complement returns the closure of the points of bound not covered by the set.
+    pub fn complement(&self, bound: Interval) -> IntervalSet {
+        return interval_set_from_intervals(&[bound]).difference(self);
+    }
+}
+
+impl<'a> IntoIterator for &'a IntervalSet {
+    type Item = &'a Interval;
+    type IntoIter = std::slice::Iter<'a, Interval>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.intervals.iter()
+    }
+}
+
+impl IntoIterator for IntervalSet {
+    type Item = Interval;
+    type IntoIter = std::vec::IntoIter<Interval>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.intervals.into_iter()
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let intervals: Vec<Interval> = iter.into_iter().collect();
+
+        return interval_set_from_intervals(&intervals);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(bounds: &[(f64, f64)]) -> IntervalSet {
+        bounds.iter().map(|&(lo, hi)| Interval { lo, hi }).collect()
+    }
+
+    #[test]
+    fn normalize() {
+        let s = set(&[(5.0, 6.0), (0.0, 1.0), (1.0, 2.0), (1.5, 3.0), (4.0, 3.0)]);
+
+        assert_eq!(
+            s.intervals(),
+            &[Interval { lo: 0.0, hi: 3.0 }, Interval { lo: 5.0, hi: 6.0 }]
+        );
+        assert_eq!(4.0, s.length());
+        assert!(s.bound().equal(Interval { lo: 0.0, hi: 6.0 }));
+        assert!(set(&[]).bound().is_empty());
+    }
+
+    #[test]
+    fn contains() {
+        let s = set(&[(0.0, 1.0), (2.0, 3.0)]);
+
+        assert!(s.contains(0.0));
+        assert!(s.contains(1.0));
+        assert!(!s.contains(1.5));
+        assert!(s.contains(2.5));
+        assert!(!s.contains(-1.0));
+        assert!(!s.contains(4.0));
+
+        assert!(s.contains_interval(Interval { lo: 2.0, hi: 2.5 }));
+        assert!(!s.contains_interval(Interval { lo: 0.5, hi: 2.5 }));
+        assert!(s.contains_interval(empty_interval()));
+    }
+
+    struct SetOperationTest {
+        x: IntervalSet,
+        y: IntervalSet,
+        union: IntervalSet,
+        intersection: IntervalSet,
+        difference: IntervalSet,
+    }
+
+    #[test]
+    fn set_operations() {
+        let tests_array: [SetOperationTest; 4] = [
+            SetOperationTest {
+                x: set(&[(0.0, 2.0), (4.0, 6.0)]),
+                y: set(&[(1.0, 5.0)]),
+                union: set(&[(0.0, 6.0)]),
+                intersection: set(&[(1.0, 2.0), (4.0, 5.0)]),
+                difference: set(&[(0.0, 1.0), (5.0, 6.0)]),
+            },
+            SetOperationTest {
+                x: set(&[(0.0, 1.0)]),
+                y: set(&[(2.0, 3.0)]),
+                union: set(&[(0.0, 1.0), (2.0, 3.0)]),
+                intersection: set(&[]),
+                difference: set(&[(0.0, 1.0)]),
+            },
+            SetOperationTest {
+                x: set(&[(0.0, 10.0)]),
+                y: set(&[(1.0, 2.0), (3.0, 4.0)]),
+                union: set(&[(0.0, 10.0)]),
+                intersection: set(&[(1.0, 2.0), (3.0, 4.0)]),
+                difference: set(&[(0.0, 1.0), (2.0, 3.0), (4.0, 10.0)]),
+            },
+            SetOperationTest {
+                x: set(&[(1.0, 1.0)]),
+                y: set(&[]),
+                union: set(&[(1.0, 1.0)]),
+                intersection: set(&[]),
+                difference: set(&[(1.0, 1.0)]),
+            },
+        ];
+
+        for test in tests_array {
+            assert_eq!(test.union, test.x.union(&test.y));
+            assert_eq!(test.intersection, test.x.intersection(&test.y));
+            assert_eq!(test.difference, test.x.difference(&test.y));
+        }
+    }
+
+    #[test]
+    fn complement() {
+        let s = set(&[(1.0, 2.0), (3.0, 4.0)]);
+
+        assert_eq!(
+            set(&[(0.0, 1.0), (2.0, 3.0), (4.0, 5.0)]),
+            s.complement(Interval { lo: 0.0, hi: 5.0 })
+        );
+        assert_eq!(
+            set(&[(2.0, 3.0)]),
+            s.complement(Interval { lo: 1.5, hi: 3.5 })
+        );
+        assert!(s.complement(empty_interval()).is_empty());
+    }
+}
@@ -0,0 +1,503 @@
+use crate::interval::*;
+use std::cmp::Ordering;
+
+// IntervalTree stores intervals along with a payload and answers stabbing and
+// overlap queries in O(min(n, (k + 1) log n)), k being the number of reported
+// entries. The interior variants of the queries skip the same subtrees but may
+// then reject the entries touching the query only at a bound, so they run in
+// O(n) in the worst case.
+//
+// It is a self-balancing (AVL) binary search tree ordered by the lower bound of
+// the intervals, where every node is augmented with the largest upper bound found
+// in its subtree so that whole subtrees can be skipped during queries.
+pub struct IntervalTree<V> {
+    root: Link<V>,
+    len: usize,
+}
+
+type Link<V> = Option<Box<Node<V>>>;
+
+struct Node<V> {
+    interval: Interval,
+    value: V,
+    max_hi: f64,
+    height: i64,
+    left: Link<V>,
+    right: Link<V>,
+}
+
+// interval_tree_from_entries builds a balanced tree from the given entries in
+// O(n log n), which is faster than inserting them one by one.
+pub fn interval_tree_from_entries<V>(entries: Vec<(Interval, V)>) -> IntervalTree<V> {
+    let mut entries = entries;
+    entries.sort_by(|a, b| key_cmp(a.0, b.0));
+
+    let len = entries.len();
+    let mut slots: Vec<Option<(Interval, V)>> = entries.into_iter().map(Some).collect();
+
+    return IntervalTree {
+        root: build(&mut slots),
+        len,
+    };
+}
+
+impl<V> Default for IntervalTree<V> {
+    fn default() -> Self {
+        IntervalTree { root: None, len: 0 }
+    }
+}
+
+impl<V> IntervalTree<V> {
+    pub fn new() -> IntervalTree<V> {
+        IntervalTree::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, interval: Interval, value: V) {
+        self.root = insert(self.root.take(), interval, value);
+        self.len += 1;
+    }
+
+    // remove deletes one entry whose interval is exactly the given one and returns
+    // its payload, or None if there is no such entry.
+    pub fn remove(&mut self, interval: Interval) -> Option<V> {
+        return self.remove_where(interval, &|_| true);
+    }
+
+    // remove_entry deletes one entry holding exactly the given interval and value,
+    // and returns its payload. Use it rather than remove when several entries may
+    // share the same interval. The values of those entries are compared one by
+    // one, in O(log n + m) for m entries sharing the interval.
+    pub fn remove_entry(&mut self, interval: Interval, value: &V) -> Option<V>
+    where
+        V: PartialEq,
+    {
+        return self.remove_where(interval, &|v| v == value);
+    }
+
+    fn remove_where(&mut self, interval: Interval, matches: &dyn Fn(&V) -> bool) -> Option<V> {
+        let (root, removed) = remove(self.root.take(), interval, matches);
+        self.root = root;
+
+        if removed.is_some() {
+            self.len -= 1;
+        }
+
+        return removed;
+    }
+
+    // containing returns the entries whose interval contains the point p, as
+    // reported by Interval::contains.
+    pub fn containing(&self, p: f64) -> Vec<(Interval, &V)> {
+        let mut res = Vec::new();
+        query(
+            &self.root,
+            interval_from_point(p),
+            &|i| i.contains(p),
+            &mut res,
+        );
+
+        return res;
+    }
+
+    // interior_containing returns the entries whose interval contains the point p
+    // in its interior, as reported by Interval::interior_contains.
+    pub fn interior_containing(&self, p: f64) -> Vec<(Interval, &V)> {
+        let mut res = Vec::new();
+        query(
+            &self.root,
+            interval_from_point(p),
+            &|i| i.interior_contains(p),
+            &mut res,
+        );
+
+        return res;
+    }
+
+    // intersecting returns the entries whose interval intersects oi, as reported by
+    // Interval::intersects.
+    pub fn intersecting(&self, oi: Interval) -> Vec<(Interval, &V)> {
+        let mut res = Vec::new();
+        if oi.is_empty() {
+            return res;
+        }
+
+        query(&self.root, oi, &|i| i.intersects(oi), &mut res);
+
+        return res;
+    }
+
+    // interior_intersecting returns the entries whose interval intersects the
+    // interior of oi, as reported by Interval::interior_intersects.
+    pub fn interior_intersecting(&self, oi: Interval) -> Vec<(Interval, &V)> {
+        let mut res = Vec::new();
+        if oi.is_empty() {
+            return res;
+        }
+
+        query(&self.root, oi, &|i| oi.interior_intersects(i), &mut res);
+
+        return res;
+    }
+
+    // entries returns all entries ordered by their lower bound.
+    pub fn entries(&self) -> Vec<(Interval, &V)> {
+        let mut res = Vec::with_capacity(self.len);
+        query(
+            &self.root,
+            Interval {
+                lo: f64::NEG_INFINITY,
+                hi: f64::INFINITY,
+            },
+            &|_| true,
+            &mut res,
+        );
+
+        return res;
+    }
+}
+
+fn key_cmp(a: Interval, b: Interval) -> Ordering {
+    return a.lo.total_cmp(&b.lo).then(a.hi.total_cmp(&b.hi));
+}
+
+fn height<V>(link: &Link<V>) -> i64 {
+    return link.as_ref().map_or(0, |n| n.height);
+}
+
+fn max_hi<V>(link: &Link<V>) -> f64 {
+    return link.as_ref().map_or(f64::NEG_INFINITY, |n| n.max_hi);
+}
+
+impl<V> Node<V> {
+    fn new(interval: Interval, value: V) -> Box<Node<V>> {
+        Box::new(Node {
+            interval,
+            value,
+            max_hi: interval.hi,
+            height: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    // update recomputes the height and the augmented upper bound from the children.
+    fn update(&mut self) {
+        self.height = 1 + height(&self.left).max(height(&self.right));
+        self.max_hi = self
+            .interval
+            .hi
+            .max(max_hi(&self.left))
+            .max(max_hi(&self.right));
+    }
+
+    fn balance_factor(&self) -> i64 {
+        return height(&self.left) - height(&self.right);
+    }
+}
+
+fn rotate_left<V>(mut node: Box<Node<V>>) -> Box<Node<V>> {
+    let mut pivot = node
+        .right
+        .take()
+        .expect("rotate_left requires a right child");
+    node.right = pivot.left.take();
+    node.update();
+    pivot.left = Some(node);
+    pivot.update();
+
+    return pivot;
+}
+
+fn rotate_right<V>(mut node: Box<Node<V>>) -> Box<Node<V>> {
+    let mut pivot = node
+        .left
+        .take()
+        .expect("rotate_right requires a left child");
+    node.left = pivot.right.take();
+    node.update();
+    pivot.right = Some(node);
+    pivot.update();
+
+    return pivot;
+}
+
+fn rebalance<V>(mut node: Box<Node<V>>) -> Box<Node<V>> {
+    node.update();
+
+    let balance = node.balance_factor();
+    if balance > 1 {
+        if node.left.as_ref().is_some_and(|l| l.balance_factor() < 0) {
+            node.left = node.left.take().map(rotate_left);
+        }
+        return rotate_right(node);
+    }
+
+    if balance < -1 {
+        if node.right.as_ref().is_some_and(|r| r.balance_factor() > 0) {
+            node.right = node.right.take().map(rotate_right);
+        }
+        return rotate_left(node);
+    }
+
+    return node;
+}
+
+fn insert<V>(link: Link<V>, interval: Interval, value: V) -> Link<V> {
+    let mut node = match link {
+        None => return Some(Node::new(interval, value)),
+        Some(node) => node,
+    };
+
+    if key_cmp(interval, node.interval) == Ordering::Less {
+        node.left = insert(node.left.take(), interval, value);
+    } else {
+        node.right = insert(node.right.take(), interval, value);
+    }
+
+    return Some(rebalance(node));
+}
+
+// remove_min detaches the leftmost node of the subtree.
+fn remove_min<V>(mut node: Box<Node<V>>) -> (Link<V>, Box<Node<V>>) {
+    match node.left.take() {
+        None => {
+            let right = node.right.take();
+            return (right, node);
+        }
+        Some(left) => {
+            let (new_left, min) = remove_min(left);
+            node.left = new_left;
+            return (Some(rebalance(node)), min);
+        }
+    }
+}
+
+// remove detaches one node holding the given interval and a value accepted by
+// matches. Rotations may have moved nodes with an equal interval on both sides of
+// each other, so both subtrees are searched when the current one is rejected.
+fn remove<V>(
+    link: Link<V>,
+    interval: Interval,
+    matches: &dyn Fn(&V) -> bool,
+) -> (Link<V>, Option<V>) {
+    let mut node = match link {
+        None => return (None, None),
+        Some(node) => node,
+    };
+
+    let removed;
+    match key_cmp(interval, node.interval) {
+        Ordering::Less => {
+            let (left, r) = remove(node.left.take(), interval, matches);
+            node.left = left;
+            removed = r;
+        }
+        Ordering::Greater => {
+            let (right, r) = remove(node.right.take(), interval, matches);
+            node.right = right;
+            removed = r;
+        }
+        Ordering::Equal if matches(&node.value) => {
+            let left = node.left.take();
+            let right = node.right.take();
+
+            let replacement = match right {
+                None => left,
+                Some(right) => {
+                    let (new_right, mut min) = remove_min(right);
+                    min.left = left;
+                    min.right = new_right;
+                    Some(rebalance(min))
+                }
+            };
+
+            return (replacement, Some(node.value));
+        }
+        Ordering::Equal => {
+            let (left, r) = remove(node.left.take(), interval, matches);
+            node.left = left;
+            removed = match r {
+                Some(v) => Some(v),
+                None => {
+                    let (right, r) = remove(node.right.take(), interval, matches);
+                    node.right = right;
+                    r
+                }
+            };
+        }
+    }
+
+    return (Some(rebalance(node)), removed);
+}
+
+fn build<V>(slots: &mut [Option<(Interval, V)>]) -> Link<V> {
+    if slots.is_empty() {
+        return None;
+    }
+
+    let mid = slots.len() / 2;
+    let (left, rest) = slots.split_at_mut(mid);
+    let (entry, right) = rest.split_first_mut().expect("slice is not empty");
+    let (interval, value) = entry.take().expect("each entry is built once");
+
+    let mut node = Node::new(interval, value);
+    node.left = build(left);
+    node.right = build(right);
+    node.update();
+
+    return Some(node);
+}
+
+// query collects, in order, the entries of the subtree matching pred. bound must
+// contain every interval accepted by pred as an intersecting interval, which lets
+// us skip the subtrees that end before it or start after it.
+fn query<'a, V>(
+    link: &'a Link<V>,
+    bound: Interval,
+    pred: &dyn Fn(Interval) -> bool,
+    res: &mut Vec<(Interval, &'a V)>,
+) {
+    let node = match link {
+        None => return,
+        Some(node) => node,
+    };
+
+    if node.max_hi < bound.lo {
+        return;
+    }
+
+    query(&node.left, bound, pred, res);
+
+    if node.interval.lo > bound.hi {
+        return;
+    }
+
+    if pred(node.interval) {
+        res.push((node.interval, &node.value));
+    }
+
+    query(&node.right, bound, pred, res);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    // intervals returns a deterministic pseudo-random list of intervals.
+    fn intervals(n: usize) -> Vec<Interval> {
+        let mut next = random_sequence(42);
+
+        return (0..n)
+            .map(|_| {
+                let lo = (next() * 100.0).floor();
+                Interval {
+                    lo,
+                    hi: lo + (next() * 10.0).floor(),
+                }
+            })
+            .collect();
+    }
+
+    fn check_height<V>(link: &Link<V>) -> i64 {
+        match link {
+            None => 0,
+            Some(node) => {
+                let l = check_height(&node.left);
+                let r = check_height(&node.right);
+                assert!((l - r).abs() <= 1);
+                1 + l.max(r)
+            }
+        }
+    }
+
+    fn ids(res: Vec<(Interval, &usize)>) -> Vec<usize> {
+        let mut ids: Vec<usize> = res.into_iter().map(|(_, &v)| v).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn queries_match_brute_force() {
+        let list = intervals(500);
+        let bulk = interval_tree_from_entries(list.iter().copied().zip(0..).collect());
+        let mut tree = IntervalTree::new();
+        for (id, i) in list.iter().enumerate() {
+            tree.insert(*i, id);
+        }
+
+        assert_eq!(500, tree.len());
+        check_height(&tree.root);
+        check_height(&bulk.root);
+
+        for t in [0.0, 3.0, 17.5, 50.0, 99.0, 120.0] {
+            let want: Vec<usize> = (0..list.len()).filter(|&k| list[k].contains(t)).collect();
+            assert_eq!(want, ids(tree.containing(t)));
+            assert_eq!(want, ids(bulk.containing(t)));
+
+            let want: Vec<usize> = (0..list.len())
+                .filter(|&k| list[k].interior_contains(t))
+                .collect();
+            assert_eq!(want, ids(tree.interior_containing(t)));
+        }
+
+        for q in [
+            Interval { lo: 10.0, hi: 12.0 },
+            Interval { lo: 55.5, hi: 55.5 },
+            Interval { lo: -5.0, hi: 0.0 },
+            empty_interval(),
+        ] {
+            let want: Vec<usize> = (0..list.len()).filter(|&k| list[k].intersects(q)).collect();
+            assert_eq!(want, ids(tree.intersecting(q)));
+            assert_eq!(want, ids(bulk.intersecting(q)));
+
+            let want: Vec<usize> = (0..list.len())
+                .filter(|&k| q.interior_intersects(list[k]))
+                .collect();
+            assert_eq!(want, ids(tree.interior_intersecting(q)));
+        }
+    }
+
+    #[test]
+    fn remove() {
+        let list = intervals(200);
+        let mut tree = interval_tree_from_entries(list.iter().copied().zip(0..).collect());
+
+        for i in list.iter().step_by(2) {
+            assert!(tree.remove(*i).is_some());
+        }
+        assert!(tree.remove(Interval { lo: -1.0, hi: 1.0 }).is_none());
+        assert!(tree.remove_entry(list[1], &0).is_none());
+
+        assert_eq!(100, tree.len());
+        check_height(&tree.root);
+
+        let remaining: Vec<Interval> = tree.entries().into_iter().map(|(i, _)| i).collect();
+        let mut want: Vec<Interval> = list.iter().skip(1).step_by(2).copied().collect();
+        want.sort_by(|a, b| key_cmp(*a, *b));
+        assert_eq!(want, remaining);
+    }
+
+    #[test]
+    fn remove_entry() {
+        let same = Interval { lo: 1.0, hi: 2.0 };
+        let mut tree = interval_tree_from_entries((0..64).map(|id| (same, id)).collect());
+
+        for id in (0..64).rev().step_by(3) {
+            assert_eq!(Some(id), tree.remove_entry(same, &id));
+            assert_eq!(None, tree.remove_entry(same, &id));
+        }
+
+        check_height(&tree.root);
+        let want: Vec<usize> = (0..64).filter(|id| (63 - id) % 3 != 0).collect();
+        assert_eq!(want, ids(tree.containing(1.5)));
+    }
+}
@@ -1,3 +1,30 @@
+#![allow(clippy::needless_return, clippy::should_implement_trait)]
+
+mod arithmetic;
+mod bucket;
 mod interval;
+mod interval_set;
+mod interval_tree;
+mod parse;
+mod scalar;
+mod sweep;
+#[cfg(test)]
+mod test_util;
+mod tolerance;
 
+pub use bucket::*;
 pub use interval::*;
+pub use interval_set::*;
+pub use interval_tree::*;
+pub use parse::{ParseError, ParseErrorKind};
+pub use scalar::*;
+pub use sweep::{interval_coverage, interval_join, interval_overlap_depth};
+pub use tolerance::*;
+
+// __private holds the items shared with r2 and r3 which are not part of the
+// public API of r1 and may change at any time.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::parse::Scanner;
+    pub use crate::sweep::ExpiryQueue;
+}
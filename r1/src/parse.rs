@@ -0,0 +1,121 @@
+use std::fmt;
+use std::str::FromStr;
+
+// ParseError is returned when parsing the textual form of a geometry value fails.
+// position is the byte offset in the input at which the problem was detected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub position: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    // The given token was expected but something else, or the end of the input,
+    // was found.
+    Expected(&'static str),
+    // The characters at position do not form a valid number.
+    InvalidNumber,
+    // The value was fully parsed but the input goes on.
+    TrailingCharacters,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ParseErrorKind::Expected(token) => {
+                write!(f, "expected {:?} at position {}", token, self.position)
+            }
+            ParseErrorKind::InvalidNumber => {
+                write!(f, "invalid number at position {}", self.position)
+            }
+            ParseErrorKind::TrailingCharacters => {
+                write!(f, "unexpected characters at position {}", self.position)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Scanner is a minimal cursor over the textual form of a geometry value. It is
+// shared by the FromStr implementations of r1, r2 and r3, which reach it through
+// r1::__private. Whitespace is skipped before every token.
+pub struct Scanner<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(input: &'a str) -> Scanner<'a> {
+        Scanner { input, pos: 0 }
+    }
+
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            position: self.pos,
+            kind,
+        }
+    }
+
+    // peek reports whether the next token is the given one, without consuming it.
+    pub fn peek(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        return self.rest().starts_with(token);
+    }
+
+    // expect consumes the given token.
+    pub fn expect(&mut self, token: &'static str) -> Result<(), ParseError> {
+        if !self.peek(token) {
+            return Err(self.error(ParseErrorKind::Expected(token)));
+        }
+
+        self.pos += token.len();
+        return Ok(());
+    }
+
+    // number consumes a number. Anything accepted by the FromStr implementation of
+    // T is allowed, including "inf" and "NaN" for floating-point types.
+    pub fn number<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        self.skip_whitespace();
+
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.'))
+            .unwrap_or(rest.len());
+
+        if len == 0 {
+            return Err(self.error(ParseErrorKind::Expected("number")));
+        }
+
+        let value = rest[..len]
+            .parse()
+            .map_err(|_| self.error(ParseErrorKind::InvalidNumber))?;
+        self.pos += len;
+
+        return Ok(value);
+    }
+
+    // finish checks that only whitespace is left in the input.
+    pub fn finish(&mut self) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if !self.rest().is_empty() {
+            return Err(self.error(ParseErrorKind::TrailingCharacters));
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,245 @@
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// Scalar is the numeric type the coordinates of Interval, r2::Point, r2::Rect and
+// r3::Vector are made of. It is implemented for the primitive floating-point and
+// integer types; f64 is used by default everywhere.
+pub trait Scalar:
+    Copy
+    + Debug
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    fn zero() -> Self;
+
+    fn one() -> Self;
+
+    fn min(self, other: Self) -> Self;
+
+    fn max(self, other: Self) -> Self;
+
+    // abs returns the absolute value. It is the identity for unsigned types.
+    fn abs(self) -> Self;
+
+    // midpoint returns the value halfway between lo and hi. Integer types round
+    // towards the smaller of the two values.
+    fn midpoint(lo: Self, hi: Self) -> Self;
+
+    // span returns the distance from lo to hi. For floating-point types this is
+    // hi - lo, which is negative when hi < lo. Integer types cannot always
+    // represent a negative distance, so they saturate at zero instead, and at the
+    // largest value of the type when the distance exceeds it.
+    fn span(lo: Self, hi: Self) -> Self;
+
+    // saturating_add and saturating_sub return self + other and self - other,
+    // clamped to the range of integer types instead of overflowing. They are the
+    // plain operations for floating-point types.
+    fn saturating_add(self, other: Self) -> Self;
+
+    fn saturating_sub(self, other: Self) -> Self;
+
+    fn to_f64(self) -> f64;
+
+    // from_f64 converts a f64 value, rounding to the nearest value for integer
+    // types and saturating at the bounds of the type.
+    fn from_f64(v: f64) -> Self;
+}
+
+// Float is implemented by the floating-point scalars, for the operations that
+// only make sense on a continuous domain.
+pub trait Float: Scalar + Neg<Output = Self> {
+    fn sqrt(self) -> Self;
+
+    fn hypot(self, other: Self) -> Self;
+}
+
+macro_rules! impl_float_scalar {
+    ($($t:ty),*) => {$(
+        impl Scalar for $t {
+            fn zero() -> Self {
+                0.0
+            }
+
+            fn one() -> Self {
+                1.0
+            }
+
+            fn min(self, other: Self) -> Self {
+                <$t>::min(self, other)
+            }
+
+            fn max(self, other: Self) -> Self {
+                <$t>::max(self, other)
+            }
+
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+
+            fn midpoint(lo: Self, hi: Self) -> Self {
+                0.5 * (hi + lo)
+            }
+
+            fn span(lo: Self, hi: Self) -> Self {
+                hi - lo
+            }
+
+            fn saturating_add(self, other: Self) -> Self {
+                self + other
+            }
+
+            fn saturating_sub(self, other: Self) -> Self {
+                self - other
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn from_f64(v: f64) -> Self {
+                v as $t
+            }
+        }
+
+        impl Float for $t {
+            fn sqrt(self) -> Self {
+                <$t>::sqrt(self)
+            }
+
+            fn hypot(self, other: Self) -> Self {
+                <$t>::hypot(self, other)
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_int_scalar {
+    ($abs:ident; $($t:ty),*) => {$(
+        impl Scalar for $t {
+            fn zero() -> Self {
+                0
+            }
+
+            fn one() -> Self {
+                1
+            }
+
+            fn min(self, other: Self) -> Self {
+                Ord::min(self, other)
+            }
+
+            fn max(self, other: Self) -> Self {
+                Ord::max(self, other)
+            }
+
+            fn abs(self) -> Self {
+                $abs(self)
+            }
+
+            fn midpoint(lo: Self, hi: Self) -> Self {
+                let (lo, hi) = (Ord::min(lo, hi), Ord::max(lo, hi));
+                // Written so as not to overflow: the distance between the bounds
+                // is taken unsigned, and half of it always fits in Self.
+                lo + (hi.abs_diff(lo) / 2) as $t
+            }
+
+            fn span(lo: Self, hi: Self) -> Self {
+                if hi < lo {
+                    return 0;
+                }
+                <$t>::try_from(hi.abs_diff(lo)).unwrap_or(<$t>::MAX)
+            }
+
+            fn saturating_add(self, other: Self) -> Self {
+                <$t>::saturating_add(self, other)
+            }
+
+            fn saturating_sub(self, other: Self) -> Self {
+                <$t>::saturating_sub(self, other)
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn from_f64(v: f64) -> Self {
+                v.round() as $t
+            }
+        }
+    )*};
+}
+
+fn signed_abs<T: Scalar + Neg<Output = T>>(v: T) -> T {
+    if v < T::zero() {
+        return -v;
+    }
+    return v;
+}
+
+fn unsigned_abs<T>(v: T) -> T {
+    return v;
+}
+
+impl_float_scalar!(f32, f64);
+impl_int_scalar!(signed_abs; i8, i16, i32, i64, isize);
+impl_int_scalar!(unsigned_abs; u8, u16, u32, u64, usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_semantics() {
+        assert_eq!(2, <i32 as Scalar>::midpoint(1, 4));
+        assert_eq!(-3, <i32 as Scalar>::midpoint(-4, -1));
+        assert_eq!(254, <u8 as Scalar>::midpoint(253, 255));
+        assert_eq!(0, <u32 as Scalar>::span(1, 0));
+        assert_eq!(3, <i64 as Scalar>::span(-1, 2));
+        assert_eq!(3, <i8 as Scalar>::from_f64(2.5));
+        assert_eq!(255, <u8 as Scalar>::from_f64(1000.0));
+        assert_eq!(0, <u8 as Scalar>::from_f64(-1.0));
+        assert_eq!(0, <u32 as Scalar>::saturating_sub(1, 2));
+        assert_eq!(127, <i8 as Scalar>::saturating_add(100, 100));
+        assert_eq!(-128, <i8 as Scalar>::saturating_sub(-100, 100));
+    }
+
+    #[test]
+    fn integer_extremes() {
+        let tests_i8: [(i8, i8, i8, i8); 4] = [
+            (-100, 100, 0, 127),
+            (-128, 127, -1, 127),
+            (-128, -128, -128, 0),
+            (127, -128, -1, 0),
+        ];
+        for (lo, hi, midpoint, span) in tests_i8 {
+            assert_eq!(midpoint, <i8 as Scalar>::midpoint(lo, hi));
+            assert_eq!(span, <i8 as Scalar>::span(lo, hi));
+        }
+
+        let tests_i32: [(i32, i32, i32, i32); 2] = [
+            (i32::MIN, i32::MAX, -1, i32::MAX),
+            (i32::MIN, 0, i32::MIN / 2, i32::MAX),
+        ];
+        for (lo, hi, midpoint, span) in tests_i32 {
+            assert_eq!(midpoint, <i32 as Scalar>::midpoint(lo, hi));
+            assert_eq!(span, <i32 as Scalar>::span(lo, hi));
+        }
+
+        assert_eq!(127, <u8 as Scalar>::midpoint(0, 255));
+        assert_eq!(255, <u8 as Scalar>::span(0, 255));
+        assert_eq!(
+            u64::MAX - 1,
+            <u64 as Scalar>::midpoint(u64::MAX, u64::MAX - 2)
+        );
+    }
+
+    #[test]
+    fn float_semantics() {
+        assert_eq!(2.5, <f64 as Scalar>::midpoint(1.0, 4.0));
+        assert_eq!(-1.0, <f32 as Scalar>::span(1.0, 0.0));
+        assert_eq!(5.0, <f32 as Float>::hypot(3.0, 4.0));
+    }
+}
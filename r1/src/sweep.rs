@@ -0,0 +1,201 @@
+use crate::interval::*;
+use crate::interval_set::*;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+// Batch algorithms over slices of intervals, based on a sweep along the line.
+// Intervals are closed, so intervals sharing a single endpoint do intersect, and
+// empty intervals never intersect anything, as for Interval::intersects.
+
+// Expiry orders the active intervals of a sweep by their upper bound.
+#[derive(Debug, Clone, Copy)]
+struct Expiry {
+    hi: f64,
+    index: usize,
+}
+
+impl PartialEq for Expiry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Expiry {}
+
+impl PartialOrd for Expiry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Expiry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.hi
+            .total_cmp(&other.hi)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+// ExpiryQueue holds the indices of the intervals crossing a sweep line and drops
+// them once the line has moved past their upper bound. It is the building block
+// of the sweeps of r1 and r2, and is shared with the latter through __private.
+#[derive(Default)]
+pub struct ExpiryQueue {
+    heap: BinaryHeap<Reverse<Expiry>>,
+}
+
+impl ExpiryQueue {
+    pub fn new() -> ExpiryQueue {
+        ExpiryQueue::default()
+    }
+
+    pub fn push(&mut self, hi: f64, index: usize) {
+        self.heap.push(Reverse(Expiry { hi, index }));
+    }
+
+    // expire removes and returns the index of the next interval ending strictly
+    // before the sweep position, if any.
+    pub fn expire(&mut self, position: f64) -> Option<usize> {
+        match self.heap.peek() {
+            Some(Reverse(top)) if top.hi < position => {
+                let index = top.index;
+                self.heap.pop();
+                Some(index)
+            }
+            _ => None,
+        }
+    }
+
+    // iter returns the indices of the intervals still in the queue, in no
+    // particular order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.heap.iter().map(|Reverse(e)| e.index)
+    }
+}
+
+// interval_join returns the pairs (i, j) such that a[i] intersects b[j], in
+// O((n + k) log n) where n is the total number of intervals and k the number of
+// reported pairs. The pairs are not returned in any particular order.
+pub fn interval_join(a: &[Interval], b: &[Interval]) -> Vec<(usize, usize)> {
+    let mut events: Vec<(f64, usize, usize)> = Vec::with_capacity(a.len() + b.len());
+    for (side, intervals) in [a, b].iter().enumerate() {
+        for (index, i) in intervals.iter().enumerate() {
+            if !i.is_empty() {
+                events.push((i.lo, side, index));
+            }
+        }
+    }
+    events.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    let mut active = [ExpiryQueue::new(), ExpiryQueue::new()];
+    let mut pairs = Vec::new();
+
+    for (lo, side, index) in events {
+        let other = 1 - side;
+        while active[other].expire(lo).is_some() {}
+
+        // Every remaining interval of the other side starts before lo and ends
+        // after it, so it intersects the current one.
+        for j in active[other].iter() {
+            if side == 0 {
+                pairs.push((index, j));
+            } else {
+                pairs.push((j, index));
+            }
+        }
+
+        let hi = [a, b][side][index].hi;
+        active[side].push(hi, index);
+    }
+
+    return pairs;
+}
+
+// interval_overlap_depth returns the largest number of intervals sharing a common
+// point, in O(n log n).
+pub fn interval_overlap_depth(intervals: &[Interval]) -> usize {
+    // Starts (0) are sorted before ends (1) at the same position since the
+    // intervals are closed.
+    let mut events: Vec<(f64, u8)> = Vec::with_capacity(2 * intervals.len());
+    for i in intervals {
+        if !i.is_empty() {
+            events.push((i.lo, 0));
+            events.push((i.hi, 1));
+        }
+    }
+    events.sort_by(|x, y| x.0.total_cmp(&y.0).then(x.1.cmp(&y.1)));
+
+    let mut depth = 0;
+    let mut max_depth = 0;
+    for (_, kind) in events {
+        if kind == 0 {
+            depth += 1;
+            max_depth = max_depth.max(depth);
+        } else {
+            depth -= 1;
+        }
+    }
+
+    return max_depth;
+}
+
+// interval_coverage returns the union of the intervals as a set of disjoint
+// intervals, in O(n log n).
+pub fn interval_coverage(intervals: &[Interval]) -> IntervalSet {
+    return interval_set_from_intervals(intervals);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intervals(bounds: &[(f64, f64)]) -> Vec<Interval> {
+        bounds.iter().map(|&(lo, hi)| Interval { lo, hi }).collect()
+    }
+
+    #[test]
+    fn join() {
+        let a = intervals(&[(0.0, 1.0), (2.0, 5.0), (6.0, 7.0), (1.0, 0.0)]);
+        let b = intervals(&[(1.0, 2.0), (3.0, 4.0), (8.0, 9.0), (-1.0, 10.0)]);
+
+        let mut pairs = interval_join(&a, &b);
+        pairs.sort();
+
+        let mut want: Vec<(usize, usize)> = Vec::new();
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                if x.intersects(*y) {
+                    want.push((i, j));
+                }
+            }
+        }
+
+        assert_eq!(want, pairs);
+        assert_eq!(vec![(0, 0), (0, 3), (1, 0), (1, 1), (1, 3), (2, 3)], pairs);
+    }
+
+    #[test]
+    fn overlap_depth() {
+        let tests_array: [(Vec<Interval>, usize); 4] = [
+            (intervals(&[]), 0),
+            (intervals(&[(0.0, 1.0), (1.0, 2.0)]), 2),
+            (intervals(&[(0.0, 1.0), (1.5, 2.0), (2.5, 3.0)]), 1),
+            (
+                intervals(&[(0.0, 10.0), (1.0, 3.0), (2.0, 4.0), (3.0, 5.0), (6.0, 5.0)]),
+                4,
+            ),
+        ];
+
+        for (list, want) in tests_array {
+            assert_eq!(want, interval_overlap_depth(&list));
+        }
+    }
+
+    #[test]
+    fn coverage() {
+        let set = interval_coverage(&intervals(&[(3.0, 4.0), (0.0, 1.0), (0.5, 2.0)]));
+
+        assert_eq!(&intervals(&[(0.0, 2.0), (3.0, 4.0)])[..], set.intervals());
+        assert_eq!(3.0, set.length());
+    }
+}
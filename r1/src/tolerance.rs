@@ -0,0 +1,167 @@
+// Tolerance describes how far apart two floating-point values may be and still be
+// considered equal by the approx_equal_with methods of r1, r2 and r3. Every
+// comparison is symmetric in its two arguments.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    // Absolute accepts values whose difference is at most the given amount.
+    Absolute(f64),
+    // Relative accepts values whose difference is at most the given fraction of
+    // the largest magnitude of the two.
+    Relative(f64),
+    // Ulps accepts values separated by at most the given number of representable
+    // doubles.
+    Ulps(u64),
+}
+
+// EPSILON is a small number that represents a reasonable level of noise between
+// two values that can be considered to be equal. It is the amount used by the
+// approx_equal methods.
+pub const EPSILON: f64 = 1e-15;
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance::Absolute(EPSILON)
+    }
+}
+
+// ordered maps a double to an integer such that consecutive doubles map to
+// consecutive integers. Both zeros map to 0.
+fn ordered(x: f64) -> i64 {
+    let bits = x.to_bits() as i64;
+    if bits < 0 {
+        return -(bits & i64::MAX);
+    }
+
+    return bits;
+}
+
+// ulps returns the number of representable doubles between a and b.
+fn ulps(a: f64, b: f64) -> u64 {
+    return ordered(a).abs_diff(ordered(b));
+}
+
+impl Tolerance {
+    // approx_eq reports whether a and b are equal up to the tolerance.
+    pub fn approx_eq(self, a: f64, b: f64) -> bool {
+        if a == b {
+            return true;
+        }
+
+        if a.is_nan() || b.is_nan() {
+            return false;
+        }
+
+        match self {
+            Tolerance::Absolute(e) => (a - b).abs() <= e,
+            Tolerance::Relative(r) => (a - b).abs() <= r * a.abs().max(b.abs()),
+            Tolerance::Ulps(n) => ulps(a, b) <= n,
+        }
+    }
+
+    // collapses reports whether the range [lo, hi] could be made empty by moving
+    // each of its bounds by no more than the tolerance, i.e. whether it is
+    // approximately equal to an empty range.
+    pub fn collapses(self, lo: f64, hi: f64) -> bool {
+        if hi <= lo {
+            return true;
+        }
+
+        match self {
+            Tolerance::Absolute(e) => hi - lo <= 2.0 * e,
+            Tolerance::Relative(r) => hi - lo <= 2.0 * r * lo.abs().max(hi.abs()),
+            Tolerance::Ulps(n) => ulps(lo, hi) <= n.saturating_mul(2),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct ToleranceTest {
+        tolerance: Tolerance,
+        a: f64,
+        b: f64,
+        want: bool,
+    }
+
+    #[test]
+    fn approx_eq() {
+        let tests_array: [ToleranceTest; 10] = [
+            ToleranceTest {
+                tolerance: Tolerance::default(),
+                a: 1.0,
+                b: 1.0 + 4.0 * f64::EPSILON,
+                want: true,
+            },
+            ToleranceTest {
+                tolerance: Tolerance::default(),
+                a: 1.0,
+                b: 1.0 + 6.0 * f64::EPSILON,
+                want: false,
+            },
+            ToleranceTest {
+                tolerance: Tolerance::Absolute(0.5),
+                a: -0.25,
+                b: 0.25,
+                want: true,
+            },
+            ToleranceTest {
+                tolerance: Tolerance::Relative(1e-3),
+                a: 1000.0,
+                b: 1000.9,
+                want: true,
+            },
+            ToleranceTest {
+                tolerance: Tolerance::Relative(1e-3),
+                a: 1.0,
+                b: 1.9,
+                want: false,
+            },
+            ToleranceTest {
+                tolerance: Tolerance::Ulps(1),
+                a: 1.0,
+                b: 1.0f64.next_up(),
+                want: true,
+            },
+            ToleranceTest {
+                tolerance: Tolerance::Ulps(1),
+                a: 1.0,
+                b: 1.0f64.next_up().next_up(),
+                want: false,
+            },
+            ToleranceTest {
+                tolerance: Tolerance::Ulps(2),
+                a: -f64::MIN_POSITIVE * f64::EPSILON,
+                b: f64::MIN_POSITIVE * f64::EPSILON,
+                want: true,
+            },
+            ToleranceTest {
+                tolerance: Tolerance::Ulps(0),
+                a: -0.0,
+                b: 0.0,
+                want: true,
+            },
+            ToleranceTest {
+                tolerance: Tolerance::Absolute(1.0),
+                a: f64::NAN,
+                b: 0.0,
+                want: false,
+            },
+        ];
+
+        for test in tests_array {
+            assert_eq!(test.want, test.tolerance.approx_eq(test.a, test.b));
+            assert_eq!(test.want, test.tolerance.approx_eq(test.b, test.a));
+        }
+    }
+
+    #[test]
+    fn collapses() {
+        assert!(Tolerance::Absolute(0.5).collapses(0.0, 1.0));
+        assert!(!Tolerance::Absolute(0.5).collapses(0.0, 1.5));
+        assert!(Tolerance::Relative(0.1).collapses(10.0, 11.0));
+        assert!(Tolerance::Ulps(1).collapses(1.0, 1.0f64.next_up().next_up()));
+        assert!(Tolerance::Ulps(0).collapses(1.0, 0.0));
+    }
+}
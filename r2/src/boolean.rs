@@ -0,0 +1,820 @@
+use crate::point::*;
+use crate::polygon::*;
+use crate::predicates::*;
+use crate::segment::*;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+// MAX_SPLIT_ROUNDS bounds the number of times edges are split at the rounded
+// crossing points created by the previous round.
+const MAX_SPLIT_ROUNDS: usize = 8;
+
+// MAX_SPLIT_GROWTH bounds the number of edges, as a multiple of their number
+// after the first round, beyond which the rounds are stopped.
+const MAX_SPLIT_GROWTH: usize = 4;
+
+// SCALE_EXPONENT is the binary exponent given to the largest coordinate of the
+// operands by the overlay. The coordinates smaller than 2^-SCALE_EXPONENT once
+// scaled, far below the rounding errors of the largest one, are flushed to zero.
+// The products computed by the predicates and the intersections of the segments
+// then neither overflow nor underflow.
+const SCALE_EXPONENT: i32 = 400;
+
+// SNAP_ULPS is the distance, in units of the last place of the coordinates, at
+// which crossing points are snapped to the endpoints of the crossing segments.
+const SNAP_ULPS: f64 = 8.0;
+
+// BooleanOp is a set operation between two polygonal regions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOp {
+    Union,
+    Intersection,
+    Difference,
+    SymmetricDifference,
+}
+
+impl BooleanOp {
+    // contains reports whether a point inside of the first operand or not, and
+    // inside of the second one or not, is inside of the result.
+    fn contains(self, a: bool, b: bool) -> bool {
+        return match self {
+            BooleanOp::Union => a || b,
+            BooleanOp::Intersection => a && b,
+            BooleanOp::Difference => a && !b,
+            BooleanOp::SymmetricDifference => a != b,
+        };
+    }
+}
+
+impl Polygon {
+    // boolean returns the result of the set operation between the polygon and
+    // another one, see MultiPolygon::boolean.
+    pub fn boolean(&self, other: &Polygon, op: BooleanOp) -> MultiPolygon {
+        return overlay(&oriented_rings(self), &oriented_rings(other), op);
+    }
+
+    pub fn union(&self, other: &Polygon) -> MultiPolygon {
+        return self.boolean(other, BooleanOp::Union);
+    }
+
+    pub fn intersection(&self, other: &Polygon) -> MultiPolygon {
+        return self.boolean(other, BooleanOp::Intersection);
+    }
+
+    pub fn difference(&self, other: &Polygon) -> MultiPolygon {
+        return self.boolean(other, BooleanOp::Difference);
+    }
+
+    pub fn symmetric_difference(&self, other: &Polygon) -> MultiPolygon {
+        return self.boolean(other, BooleanOp::SymmetricDifference);
+    }
+}
+
+impl MultiPolygon {
+    // boolean returns the result of the set operation between the polygons and
+    // other ones. The rings of the operands may be given in any orientation, and
+    // polygons of the same operand which overlap are merged.
+    //
+    // The result is made of normalized polygons whose rings do not cross each
+    // other, but may touch at shared vertices. The vertices are vertices of the
+    // operands or their rounded crossing points, except for collinear ones which
+    // are removed.
+    // Regions of zero area, such as the shared edge of two adjacent squares
+    // intersected together, are dropped. The result is empty if a coordinate of
+    // the operands is not finite.
+    pub fn boolean(&self, other: &MultiPolygon, op: BooleanOp) -> MultiPolygon {
+        let rings = |m: &MultiPolygon| -> Vec<Vec<Point>> {
+            return m.polygons.iter().flat_map(oriented_rings).collect();
+        };
+
+        return overlay(&rings(self), &rings(other), op);
+    }
+
+    pub fn union(&self, other: &MultiPolygon) -> MultiPolygon {
+        return self.boolean(other, BooleanOp::Union);
+    }
+
+    pub fn intersection(&self, other: &MultiPolygon) -> MultiPolygon {
+        return self.boolean(other, BooleanOp::Intersection);
+    }
+
+    pub fn difference(&self, other: &MultiPolygon) -> MultiPolygon {
+        return self.boolean(other, BooleanOp::Difference);
+    }
+
+    pub fn symmetric_difference(&self, other: &MultiPolygon) -> MultiPolygon {
+        return self.boolean(other, BooleanOp::SymmetricDifference);
+    }
+}
+
+// oriented_rings returns the non-empty rings of the polygon, the outer one
+// counter-clockwise and the holes clockwise, so that the winding number of the
+// rings is 1 inside of the polygon and 0 outside of it.
+fn oriented_rings(polygon: &Polygon) -> Vec<Vec<Point>> {
+    let mut polygon = polygon.clone();
+    polygon.normalize_orientation();
+
+    return std::iter::once(polygon.exterior)
+        .chain(polygon.holes)
+        .filter(|ring| !ring.is_empty())
+        .collect();
+}
+
+// Edge is a piece of the boundary of the operands. Crossing it from its right to
+// its left changes the winding numbers of the two operands by winding.
+#[derive(Clone, Copy)]
+struct Edge {
+    segment: Segment,
+    winding: [i32; 2],
+}
+
+// overlay computes the set operation by overlaying the rings of both operands:
+// the edges are split at their intersections into a planar subdivision, whose
+// faces are labelled with their winding numbers with respect to each operand.
+// The edges separating a face inside of the result from one outside of it are
+// then linked into the rings of the result.
+//
+// The coordinates are first scaled by a power of two, see SCALE_EXPONENT, and the
+// result scaled back. Apart from the flushed coordinates, this is exact and
+// changes none of the decisions of the overlay, but keeps it robust to very small
+// or very large coordinates.
+fn overlay(a: &[Vec<Point>], b: &[Vec<Point>], op: BooleanOp) -> MultiPolygon {
+    let Some(exponent) = scale_exponent(a.iter().chain(b)) else {
+        return MultiPolygon::default();
+    };
+    let flushed = |p: Point| {
+        let flush = |v: f64| {
+            if v.abs() < 2f64.powi(-SCALE_EXPONENT) {
+                return 0.0;
+            }
+            return v;
+        };
+        let p = scale(p, exponent);
+        return Point {
+            x: flush(p.x),
+            y: flush(p.y),
+        };
+    };
+
+    let mut edges: Vec<Edge> = Vec::new();
+    for (rings, winding) in [(a, [1, 0]), (b, [0, 1])] {
+        for ring in rings {
+            let n = ring.len();
+            for i in 0..n {
+                let segment = Segment {
+                    a: flushed(ring[i]),
+                    b: flushed(ring[(i + 1) % n]),
+                };
+                if !segment.is_degenerate() {
+                    edges.push(Edge { segment, winding });
+                }
+            }
+        }
+    }
+
+    let subdivision = Subdivision::new(&split_edges(edges));
+    let labels = subdivision.face_labels();
+    let inside = |h: usize| {
+        let [a, b] = labels[h];
+        return op.contains(a != 0, b != 0);
+    };
+    let kept: Vec<bool> = (0..subdivision.origin.len())
+        .map(|h| inside(h) && !inside(h ^ 1))
+        .collect();
+
+    let mut result = assemble(subdivision.rings(&kept));
+    for polygon in &mut result.polygons {
+        for ring in std::iter::once(&mut polygon.exterior).chain(&mut polygon.holes) {
+            for p in ring.iter_mut() {
+                *p = scale(*p, -exponent);
+            }
+        }
+    }
+
+    return result;
+}
+
+// scale_exponent returns the exponent of the power of two by which the overlay
+// scales the coordinates of the rings, or None if one of them is not finite.
+fn scale_exponent<'a>(rings: impl Iterator<Item = &'a Vec<Point>>) -> Option<i32> {
+    let mut max: f64 = 0.0;
+    for p in rings.flatten() {
+        if !p.x.is_finite() || !p.y.is_finite() {
+            return None;
+        }
+        max = max.max(p.x.abs()).max(p.y.abs());
+    }
+
+    if max == 0.0 {
+        return Some(0);
+    }
+
+    return Some(SCALE_EXPONENT - max.log2().floor() as i32);
+}
+
+// scale returns p multiplied by 2^exponent. The factor is applied in two halves,
+// each of them representable.
+fn scale(p: Point, exponent: i32) -> Point {
+    let (first, second) = (2f64.powi(exponent / 2), 2f64.powi(exponent - exponent / 2));
+    return Point {
+        x: p.x * first * second,
+        y: p.y * first * second,
+    };
+}
+
+// split_edges splits the edges at their intersections with each other, so that
+// they only meet at shared endpoints. Crossing points are rounded, which may make
+// the pieces cross other edges nearby, hence the repeated rounds.
+//
+// Rounds stop early once the edges have grown by MAX_SPLIT_GROWTH since the first
+// one, which only happens when rounding keeps creating crossings, e.g. for nearly
+// collinear edges. Some edges may then still cross each other, but the overlay
+// does not run out of memory.
+fn split_edges(mut edges: Vec<Edge>) -> Vec<Edge> {
+    let mut max_edges = usize::MAX;
+    for round in 0..MAX_SPLIT_ROUNDS {
+        if edges.len() > max_edges {
+            break;
+        }
+
+        let segments: Vec<Segment> = edges.iter().map(|e| e.segment).collect();
+        let mut cuts: Vec<Vec<Point>> = vec![Vec::new(); edges.len()];
+        for (i, j, intersection) in segment_intersections(&segments) {
+            let points = match intersection {
+                SegmentIntersection::None => vec![],
+                SegmentIntersection::Point(p) => vec![snap(p, [segments[i], segments[j]])],
+                SegmentIntersection::Segment(s) => vec![s.a, s.b],
+            };
+            for p in points {
+                for k in [i, j] {
+                    if p != segments[k].a && p != segments[k].b {
+                        cuts[k].push(p);
+                    }
+                }
+            }
+        }
+
+        if cuts.iter().all(|c| c.is_empty()) {
+            break;
+        }
+
+        let mut split = Vec::with_capacity(edges.len());
+        for (edge, mut points) in edges.into_iter().zip(cuts) {
+            let Segment { a, b } = edge.segment;
+            let d = b.sub(a);
+            points.sort_by(|p, q| p.sub(a).dot(d).total_cmp(&q.sub(a).dot(d)));
+            points.push(b);
+
+            let mut start = a;
+            for p in points {
+                if p != start {
+                    split.push(Edge {
+                        segment: Segment { a: start, b: p },
+                        winding: edge.winding,
+                    });
+                    start = p;
+                }
+            }
+        }
+        edges = split;
+        if round == 0 {
+            max_edges = MAX_SPLIT_GROWTH * edges.len();
+        }
+    }
+
+    return edges;
+}
+
+// snap returns the endpoint of the segments closest to p if it lies within a few
+// rounding errors of p, and p otherwise. Segments crossing next to an endpoint
+// are thus made to meet at that endpoint, rather than split into tiny pieces
+// whose rounded ends may cross again.
+fn snap(p: Point, segments: [Segment; 2]) -> Point {
+    let mut closest = (p, f64::INFINITY);
+    for q in segments.iter().flat_map(|s| [s.a, s.b]) {
+        let distance = (p.x - q.x).abs().max((p.y - q.y).abs());
+        if distance <= SNAP_ULPS * f64::EPSILON * q.x.abs().max(q.y.abs()) && distance < closest.1 {
+            closest = (q, distance);
+        }
+    }
+
+    return closest.0;
+}
+
+// Subdivision is the planar subdivision made by edges which only meet at their
+// endpoints. Each edge is made of two half-edges of opposite directions, h and
+// h ^ 1, and the face on the left of a half-edge is traversed by following next.
+struct Subdivision {
+    vertices: Vec<Point>,
+    // origin is the vertex each half-edge starts from.
+    origin: Vec<usize>,
+    // winding is the change of the winding numbers of the operands when crossing
+    // each half-edge from its right to its left.
+    winding: Vec<[i32; 2]>,
+    // outgoing lists the half-edges starting from each vertex, sorted
+    // counter-clockwise from the direction of the positive x axis.
+    outgoing: Vec<Vec<usize>>,
+    // position is the index of each half-edge in the outgoing list of its origin.
+    position: Vec<usize>,
+}
+
+impl Subdivision {
+    // new merges the equal vertices and the edges joining the same vertices,
+    // dropping the edges whose windings cancel out.
+    fn new(edges: &[Edge]) -> Subdivision {
+        let mut vertices: Vec<Point> = Vec::new();
+        let mut vertex_index: HashMap<(u64, u64), usize> = HashMap::new();
+        let mut vertex = |p: Point| {
+            // Adding zero turns -0.0 into 0.0.
+            let p = Point {
+                x: p.x + 0.0,
+                y: p.y + 0.0,
+            };
+            return *vertex_index
+                .entry((p.x.to_bits(), p.y.to_bits()))
+                .or_insert_with(|| {
+                    vertices.push(p);
+                    return vertices.len() - 1;
+                });
+        };
+
+        let mut merged: Vec<((usize, usize), [i32; 2])> = Vec::new();
+        let mut edge_index: HashMap<(usize, usize), usize> = HashMap::new();
+        for edge in edges {
+            let (u, v) = (vertex(edge.segment.a), vertex(edge.segment.b));
+            let (key, sign) = if u < v { ((u, v), 1) } else { ((v, u), -1) };
+            let i = *edge_index.entry(key).or_insert_with(|| {
+                merged.push((key, [0, 0]));
+                return merged.len() - 1;
+            });
+            for (w, dw) in merged[i].1.iter_mut().zip(edge.winding) {
+                *w += sign * dw;
+            }
+        }
+
+        let mut subdivision = Subdivision {
+            vertices,
+            origin: Vec::new(),
+            winding: Vec::new(),
+            outgoing: Vec::new(),
+            position: Vec::new(),
+        };
+        for ((u, v), [wa, wb]) in merged {
+            if wa != 0 || wb != 0 {
+                subdivision.origin.extend([u, v]);
+                subdivision.winding.extend([[wa, wb], [-wa, -wb]]);
+            }
+        }
+
+        subdivision.outgoing = vec![Vec::new(); subdivision.vertices.len()];
+        for h in 0..subdivision.origin.len() {
+            subdivision.outgoing[subdivision.origin[h]].push(h);
+        }
+        for v in 0..subdivision.vertices.len() {
+            let mut outgoing = std::mem::take(&mut subdivision.outgoing[v]);
+            outgoing.sort_by(|&g, &h| subdivision.angle_cmp(g, h));
+            subdivision.outgoing[v] = outgoing;
+        }
+        subdivision.position = vec![0; subdivision.origin.len()];
+        for outgoing in &subdivision.outgoing {
+            for (i, &h) in outgoing.iter().enumerate() {
+                subdivision.position[h] = i;
+            }
+        }
+
+        return subdivision;
+    }
+
+    fn destination(&self, h: usize) -> usize {
+        return self.origin[h ^ 1];
+    }
+
+    // angle_cmp compares the directions of two half-edges with the same origin
+    // by their angle with the positive x axis, in [0, 2π).
+    fn angle_cmp(&self, g: usize, h: usize) -> Ordering {
+        let o = self.vertices[self.origin[g]];
+        let (p, q) = (
+            self.vertices[self.destination(g)],
+            self.vertices[self.destination(h)],
+        );
+        let lower = |d: Point| d.y < 0.0 || (d.y == 0.0 && d.x < 0.0);
+
+        return lower(p.sub(o)).cmp(&lower(q.sub(o))).then_with(|| {
+            let side = orient2d(o, p, q);
+            if side > 0.0 {
+                return Ordering::Less;
+            }
+            if side < 0.0 {
+                return Ordering::Greater;
+            }
+            return Ordering::Equal;
+        });
+    }
+
+    // next returns the half-edge following h around the face on its left, which
+    // is the first one clockwise from the twin of h around its destination.
+    fn next(&self, h: usize) -> usize {
+        let outgoing = &self.outgoing[self.destination(h)];
+        let n = outgoing.len();
+        return outgoing[(self.position[h ^ 1] + n - 1) % n];
+    }
+
+    // face_labels returns the winding numbers of the operands in the face on the
+    // left of each half-edge.
+    fn face_labels(&self) -> Vec<[i32; 2]> {
+        let mut face = vec![usize::MAX; self.origin.len()];
+        let mut faces: Vec<Vec<usize>> = Vec::new();
+        for start in 0..self.origin.len() {
+            if face[start] != usize::MAX {
+                continue;
+            }
+
+            let mut h = start;
+            let mut cycle = Vec::new();
+            while face[h] == usize::MAX {
+                face[h] = faces.len();
+                cycle.push(h);
+                h = self.next(h);
+            }
+            faces.push(cycle);
+        }
+
+        // The lowest vertex of each connected component comes first, and the face
+        // just below it is the outer face of the component, on the left of its
+        // last outgoing half-edge. Its label is computed directly, and the others
+        // follow by crossing the edges of the component.
+        let mut order: Vec<usize> = (0..self.vertices.len()).collect();
+        order.sort_by(|&u, &v| {
+            let (p, q) = (self.vertices[u], self.vertices[v]);
+            return p.y.total_cmp(&q.y).then(p.x.total_cmp(&q.x));
+        });
+
+        let mut labels: Vec<Option<[i32; 2]>> = vec![None; faces.len()];
+        for v in order {
+            let Some(&outer) = self.outgoing[v].last() else {
+                continue;
+            };
+            if labels[face[outer]].is_some() {
+                continue;
+            }
+
+            labels[face[outer]] = Some(self.winding_below(self.vertices[v]));
+            let mut stack = vec![face[outer]];
+            while let Some(f) = stack.pop() {
+                let label = labels[f].unwrap();
+                for &h in &faces[f] {
+                    let twin = face[h ^ 1];
+                    if labels[twin].is_none() {
+                        let w = self.winding[h];
+                        labels[twin] = Some([label[0] - w[0], label[1] - w[1]]);
+                        stack.push(twin);
+                    }
+                }
+            }
+        }
+
+        return face.iter().map(|&f| labels[f].unwrap()).collect();
+    }
+
+    // winding_below returns the winding numbers of the operands just below p, the
+    // lowest vertex of its connected component, by counting the edges crossed by
+    // a ray going left from there. No edge can contain p without ending at it.
+    fn winding_below(&self, p: Point) -> [i32; 2] {
+        let mut winding = [0, 0];
+        for h in (0..self.origin.len()).step_by(2) {
+            let (a, b) = (
+                self.vertices[self.origin[h]],
+                self.vertices[self.destination(h)],
+            );
+            // Leaving an upward edge on the left decreases the winding.
+            let (low, high, sign) = if a.y < b.y { (a, b, -1) } else { (b, a, 1) };
+            if low.y < p.y && p.y <= high.y && orient2d(low, high, p) < 0.0 {
+                for (w, dw) in winding.iter_mut().zip(self.winding[h]) {
+                    *w += sign * dw;
+                }
+            }
+        }
+
+        return winding;
+    }
+
+    // rings links the kept half-edges into rings, turning as far left as
+    // possible at each vertex, so that rings touching at a vertex are kept apart.
+    // The vertices lying on the segment joining their neighbors are removed,
+    // unless another ring goes through them.
+    fn rings(&self, kept: &[bool]) -> Vec<Vec<Point>> {
+        let mut degree = vec![0; self.vertices.len()];
+        for h in (0..kept.len()).filter(|&h| kept[h]) {
+            degree[self.origin[h]] += 1;
+        }
+
+        let mut rings = Vec::new();
+        let mut visited = vec![false; kept.len()];
+        for start in 0..kept.len() {
+            if !kept[start] || visited[start] {
+                continue;
+            }
+
+            let mut ring = Vec::new();
+            let mut h = start;
+            while !visited[h] {
+                visited[h] = true;
+                ring.push(self.origin[h]);
+
+                let outgoing = &self.outgoing[self.destination(h)];
+                let n = outgoing.len();
+                let mut i = self.position[h ^ 1];
+                loop {
+                    i = (i + n - 1) % n;
+                    if kept[outgoing[i]] {
+                        break;
+                    }
+                }
+                h = outgoing[i];
+            }
+
+            let n = ring.len();
+            let ring = (0..n)
+                .filter(|&i| {
+                    let (p, v, q) = (
+                        self.vertices[ring[(i + n - 1) % n]],
+                        self.vertices[ring[i]],
+                        self.vertices[ring[(i + 1) % n]],
+                    );
+                    return degree[ring[i]] > 1
+                        || orient2d(p, v, q) != 0.0
+                        || v.sub(p).dot(q.sub(v)) <= 0.0;
+                })
+                .map(|i| self.vertices[ring[i]])
+                .collect();
+            rings.push(ring);
+        }
+
+        return rings;
+    }
+}
+
+// assemble makes polygons out of counter-clockwise outer rings and clockwise
+// holes, putting each hole in the smallest outer ring around it.
+fn assemble(rings: Vec<Vec<Point>>) -> MultiPolygon {
+    let mut polygons: Vec<Polygon> = Vec::new();
+    let mut areas: Vec<f64> = Vec::new();
+    let mut holes: Vec<Vec<Point>> = Vec::new();
+    for ring in rings {
+        if ring.len() < 3 {
+            continue;
+        }
+        let area = ring_signed_area(&ring);
+        if area > 0.0 {
+            polygons.push(polygon_from_points(&ring));
+            areas.push(area);
+        } else if area < 0.0 {
+            holes.push(ring);
+        }
+    }
+
+    for hole in holes {
+        // The midpoint of an edge of the hole is on no other ring.
+        let p = hole[0].add(hole[1]).mul(0.5);
+        let outer = (0..polygons.len())
+            .filter(|&i| {
+                let exterior = &polygons[i].exterior;
+                return polygons[i].bound().contains_point(p)
+                    && ring_winding_number(exterior, p) != 0;
+            })
+            .min_by(|&i, &j| areas[i].total_cmp(&areas[j]));
+        if let Some(i) = outer {
+            polygons[i].holes.push(hole);
+        }
+    }
+
+    return MultiPolygon::new(polygons);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    const OPS: [BooleanOp; 4] = [
+        BooleanOp::Union,
+        BooleanOp::Intersection,
+        BooleanOp::Difference,
+        BooleanOp::SymmetricDifference,
+    ];
+
+    fn ring(coords: &[(f64, f64)]) -> Vec<Point> {
+        coords.iter().map(|&(x, y)| Point { x, y }).collect()
+    }
+
+    fn square(x: f64, y: f64, size: f64) -> Vec<Point> {
+        return ring(&[(x, y), (x + size, y), (x + size, y + size), (x, y + size)]);
+    }
+
+    // star returns a star-shaped polygon with a star-shaped hole around the same
+    // center.
+    fn star(center: Point, random: &mut impl FnMut() -> f64) -> Polygon {
+        let mut ring = |count: usize, radius: f64, depth: f64| -> Vec<Point> {
+            return (0..count)
+                .map(|i| {
+                    let angle = 2.0 * std::f64::consts::PI * i as f64 / count as f64;
+                    let r = radius * (1.0 - depth * random());
+                    return center.add(Point {
+                        x: r * angle.cos(),
+                        y: r * angle.sin(),
+                    });
+                })
+                .collect();
+        };
+
+        return Polygon::new(ring(24, 1.0, 0.5), vec![ring(7, 0.4, 0.5)]);
+    }
+
+    // check_valid checks that the polygons are normalized and that their rings
+    // only meet at vertices.
+    fn check_valid(m: &MultiPolygon) {
+        let mut segments: Vec<Segment> = Vec::new();
+        for polygon in &m.polygons {
+            assert!(polygon.is_normalized());
+            for ring in std::iter::once(&polygon.exterior).chain(&polygon.holes) {
+                assert!(ring.len() >= 3);
+                for i in 0..ring.len() {
+                    segments.push(Segment {
+                        a: ring[i],
+                        b: ring[(i + 1) % ring.len()],
+                    });
+                }
+            }
+        }
+
+        for (i, j, intersection) in segment_intersections(&segments) {
+            let SegmentIntersection::Point(p) = intersection else {
+                panic!("segments {i} and {j} overlap");
+            };
+            assert!(p == segments[i].a || p == segments[i].b);
+            assert!(p == segments[j].a || p == segments[j].b);
+        }
+    }
+
+    #[test]
+    fn squares() {
+        let a = polygon_from_points(&square(0.0, 0.0, 2.0));
+        let b = polygon_from_points(&square(1.0, 1.0, 2.0));
+
+        let tests_array: [(BooleanOp, f64, usize, usize); 4] = [
+            (BooleanOp::Union, 7.0, 1, 8),
+            (BooleanOp::Intersection, 1.0, 1, 4),
+            (BooleanOp::Difference, 3.0, 1, 6),
+            (BooleanOp::SymmetricDifference, 6.0, 2, 12),
+        ];
+        for (op, area, polygons, vertices) in tests_array {
+            let result = a.boolean(&b, op);
+            check_valid(&result);
+            assert_eq!(area, result.area());
+            assert_eq!(polygons, result.polygons.len());
+            let count: usize = result.polygons.iter().map(|p| p.exterior.len()).sum();
+            assert_eq!(vertices, count);
+        }
+
+        // Squares sharing an edge, or nested.
+        let c = polygon_from_points(&square(2.0, 0.0, 2.0));
+        let union = a.union(&c);
+        assert_eq!(1, union.polygons.len());
+        assert_eq!(4, union.polygons[0].exterior.len());
+        assert!(a.intersection(&c).is_empty());
+        assert_eq!(a.difference(&c).area(), 4.0);
+
+        let d = polygon_from_points(&square(0.5, 0.5, 1.0));
+        let difference = a.difference(&d);
+        assert_eq!(1, difference.polygons.len());
+        assert_eq!(1, difference.polygons[0].holes.len());
+        assert_eq!(3.0, difference.area());
+        assert!(difference.polygons[0].is_normalized());
+        assert!(d.difference(&a).is_empty());
+
+        // An island inside of the hole of another polygon.
+        let island = d.union(&polygon_from_points(&square(0.75, 0.75, 0.5)));
+        assert_eq!(1.0, island.area());
+        let island = polygon_from_points(&square(0.75, 0.75, 0.5));
+        let island = difference.union(&MultiPolygon::new(vec![island]));
+        assert_eq!(2, island.polygons.len());
+        assert_eq!(3.25, island.area());
+
+        // Operands equal to each other or empty.
+        assert_eq!(4.0, a.union(&a).area());
+        assert!(a.difference(&a).is_empty());
+        assert_eq!(4.0, a.union(&Polygon::default()).area());
+        assert!(a.intersection(&Polygon::default()).is_empty());
+    }
+
+    #[test]
+    fn touching() {
+        // Squares touching at a corner stay apart.
+        let a = polygon_from_points(&square(0.0, 0.0, 1.0));
+        let b = polygon_from_points(&square(1.0, 1.0, 1.0));
+        let union = a.union(&b);
+        check_valid(&union);
+        assert_eq!(2, union.polygons.len());
+
+        // A hole touching the outer ring at a vertex.
+        let frame = Polygon::new(
+            square(0.0, 0.0, 4.0),
+            vec![ring(&[(0.0, 0.0), (2.0, 1.0), (1.0, 2.0)])],
+        );
+        let result = frame.intersection(&polygon_from_points(&square(-1.0, -1.0, 6.0)));
+        check_valid(&result);
+        assert_eq!(14.5, result.area());
+        assert_eq!(1, result.polygons.len());
+
+        // A multipolygon whose polygons overlap is merged.
+        let m = MultiPolygon::new(vec![a.clone(), polygon_from_points(&square(0.5, 0.0, 1.0))]);
+        let merged = m.union(&MultiPolygon::default());
+        assert_eq!(1, merged.polygons.len());
+        assert_eq!(1.5, merged.area());
+    }
+
+    #[test]
+    fn extreme_coordinates() {
+        // Thin strips whose coordinate products underflow, crossing at the origin.
+        let strips = |e: f64| {
+            return (
+                polygon_from_points(&ring(&[(0.0, -e), (1.0, -e), (1.0, e), (0.0, e)])),
+                polygon_from_points(&ring(&[(-e, 0.0), (e, 0.0), (e, 1.0), (-e, 1.0)])),
+            );
+        };
+        let e = 1e-170;
+        let (a, b) = strips(e);
+        let union = a.union(&b);
+        assert_eq!(1, union.polygons.len());
+        assert_eq!(8, union.polygons[0].exterior.len());
+        assert!((union.area() - 4.0 * e).abs() < 1e-12 * e);
+        let intersection = a.intersection(&b);
+        assert_eq!(1, intersection.polygons.len());
+        assert_eq!(4, intersection.polygons[0].exterior.len());
+
+        // Pieces at the origin which used to make the rounds of split_edges blow
+        // up.
+        let e = 1e-200;
+        let (a, b) = strips(e);
+        let pieces = MultiPolygon::new(vec![
+            polygon_from_points(&ring(&[(-e, -e), (e, -e), (e, e), (-e, e)])),
+            a,
+            b,
+        ]);
+        let unit = MultiPolygon::new(vec![polygon_from_points(&square(0.0, 0.0, 1.0))]);
+        let union = unit.union(&pieces);
+        assert_eq!(1, union.polygons.len());
+        assert_eq!(1.0, union.area());
+
+        // Large coordinates, whose products overflow.
+        let a = polygon_from_points(&square(0.0, 0.0, 2e200));
+        let b = polygon_from_points(&square(1e200, 1e200, 2e200));
+        let union = a.union(&b);
+        assert_eq!(1, union.polygons.len());
+        assert_eq!(8, union.polygons[0].exterior.len());
+        assert_eq!(1, a.intersection(&b).polygons.len());
+
+        // Coordinates which are not finite.
+        let nan = polygon_from_points(&ring(&[(0.0, 0.0), (f64::NAN, 0.0), (1.0, 1.0)]));
+        assert!(a.union(&nan).is_empty());
+        let infinite = polygon_from_points(&square(0.0, 0.0, f64::INFINITY));
+        assert!(infinite.intersection(&a).is_empty());
+    }
+
+    #[test]
+    fn random() {
+        let mut random = random_sequence(5);
+
+        for _ in 0..20 {
+            let a = star(Point { x: 0.0, y: 0.0 }, &mut random);
+            let b = star(
+                Point {
+                    x: random() - 0.5,
+                    y: random() - 0.5,
+                },
+                &mut random,
+            );
+
+            let results: Vec<MultiPolygon> = OPS.iter().map(|&op| a.boolean(&b, op)).collect();
+            for result in &results {
+                check_valid(result);
+            }
+
+            let areas: Vec<f64> = results.iter().map(|r| r.area()).collect();
+            assert!((areas[0] + areas[1] - a.area() - b.area()).abs() < 1e-12);
+            assert!((areas[2] + areas[1] - a.area()).abs() < 1e-12);
+            assert!((areas[3] + areas[1] - areas[0]).abs() < 1e-12);
+
+            for _ in 0..200 {
+                let p = Point {
+                    x: 3.0 * random() - 1.5,
+                    y: 3.0 * random() - 1.5,
+                };
+                let (in_a, in_b) = (a.contains_point(p), b.contains_point(p));
+                for (op, result) in OPS.iter().zip(&results) {
+                    assert_eq!(op.contains(in_a, in_b), result.contains_point(p));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,558 @@
+use crate::point::*;
+use crate::polygon::*;
+use crate::polyline::*;
+use std::f64::consts::PI;
+
+// MAX_ARC_STEPS bounds the number of chords approximating a full circle, however
+// small the arc tolerance.
+const MAX_ARC_STEPS: f64 = 4096.0;
+
+// Join is the shape given to the buffer around the outer side of a vertex where
+// the boundary turns.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Join {
+    // Round joins the offset edges with a circular arc around the vertex.
+    Round,
+    // Miter extends the offset edges until they meet, unless they meet farther
+    // from the vertex than the given multiple of the distance, in which case
+    // they are joined with a bevel as in SVG.
+    Miter(f64),
+    // Bevel joins the ends of the offset edges with a straight line.
+    Bevel,
+}
+
+// Cap is the shape given to the buffer around the ends of an open polyline, and
+// around a single point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cap {
+    // Round ends the buffer with a half disk, or a disk around a point.
+    Round,
+    // Square extends the buffer by the distance past the ends, or makes a square
+    // around a point.
+    Square,
+    // Flat ends the buffer at the ends, and leaves nothing around a point.
+    Flat,
+}
+
+// BufferStyle configures the shape of a buffer. The arcs of round joins and caps
+// are approximated by chords, which deviate from the arcs by at most
+// arc_tolerance times the distance. arc_tolerance is taken in (0, 1]: larger
+// values are treated as 1, and other ones, including NaN, give the finest arcs
+// allowed by MAX_ARC_STEPS.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BufferStyle {
+    pub join: Join,
+    pub cap: Cap,
+    pub arc_tolerance: f64,
+}
+
+impl Default for BufferStyle {
+    fn default() -> Self {
+        BufferStyle {
+            join: Join::Round,
+            cap: Cap::Round,
+            arc_tolerance: 1e-3,
+        }
+    }
+}
+
+impl BufferStyle {
+    // arc returns the points of the arc of the given signed angle around the
+    // center, starting at center + start, with a positive angle going
+    // counter-clockwise. The ends of the arc are left out.
+    fn arc(self, center: Point, start: Point, angle: f64) -> Vec<Point> {
+        // A chord spanning the angle step lies within 1 - cos(step / 2) times
+        // the radius of the arc.
+        let tolerance = if self.arc_tolerance > 0.0 {
+            self.arc_tolerance.min(1.0)
+        } else {
+            0.0
+        };
+        let step = (2.0 * (1.0 - tolerance).acos()).clamp(2.0 * PI / MAX_ARC_STEPS, PI / 2.0);
+        let count = (angle.abs() / step).ceil().max(1.0) as usize;
+        let (sin, cos) = (angle / count as f64).sin_cos();
+
+        let mut points = Vec::with_capacity(count - 1);
+        let mut offset = start;
+        for _ in 1..count {
+            offset = Point {
+                x: offset.x * cos - offset.y * sin,
+                y: offset.x * sin + offset.y * cos,
+            };
+            points.push(center.add(offset));
+        }
+
+        return points;
+    }
+}
+
+// point_buffer returns the region within the distance of p, a disk or a square
+// depending on the cap of the style. It is empty for a distance which is not
+// positive, or a flat cap.
+pub fn point_buffer(p: Point, distance: f64, style: BufferStyle) -> Polygon {
+    if distance <= 0.0 || distance.is_nan() {
+        return Polygon::default();
+    }
+
+    let start = Point {
+        x: distance,
+        y: 0.0,
+    };
+    return match style.cap {
+        Cap::Round => {
+            let mut ring = vec![p.add(start)];
+            ring.extend(style.arc(p, start, 2.0 * PI));
+            polygon_from_points(&ring)
+        }
+        Cap::Square => {
+            let (lo, hi) = (
+                p.sub(Point {
+                    x: distance,
+                    y: distance,
+                }),
+                p.add(Point {
+                    x: distance,
+                    y: distance,
+                }),
+            );
+            polygon_from_points(&[
+                lo,
+                Point { x: hi.x, y: lo.y },
+                hi,
+                Point { x: lo.x, y: hi.y },
+            ])
+        }
+        Cap::Flat => Polygon::default(),
+    };
+}
+
+impl Polyline {
+    // buffer returns the region within the distance of the polyline, with the
+    // joins and caps of the style. A closed polyline gets no caps. The result is
+    // empty for a distance which is not positive or not finite.
+    pub fn buffer(&self, distance: f64, style: BufferStyle) -> MultiPolygon {
+        if distance <= 0.0 || !distance.is_finite() {
+            return MultiPolygon::default();
+        }
+
+        let mut points = self.points.clone();
+        points.dedup();
+        if points.is_empty() {
+            return MultiPolygon::default();
+        }
+        if points.len() == 1 {
+            let polygon = point_buffer(points[0], distance, style);
+            return MultiPolygon::new(vec![polygon]).union(&MultiPolygon::default());
+        }
+
+        let pieces = if self.is_closed() {
+            points.pop();
+            ring_pieces(&points, distance, style)
+        } else {
+            line_pieces(&points, distance, style)
+        };
+        return MultiPolygon::new(pieces).union(&MultiPolygon::default());
+    }
+}
+
+impl Polygon {
+    // buffer returns the polygon grown by the distance, or shrunk by its opposite
+    // if it is negative, with the joins of the style. The joins shape the outer
+    // side of the turns of the boundary: the convex vertices when growing, and
+    // the reflex ones when shrinking. The result is empty for a distance which is
+    // not finite.
+    pub fn buffer(&self, distance: f64, style: BufferStyle) -> MultiPolygon {
+        return MultiPolygon::new(vec![self.clone()]).buffer(distance, style);
+    }
+}
+
+impl MultiPolygon {
+    // buffer returns the polygons grown by the distance, or shrunk by its opposite
+    // if it is negative, see Polygon::buffer.
+    pub fn buffer(&self, distance: f64, style: BufferStyle) -> MultiPolygon {
+        if !distance.is_finite() {
+            return MultiPolygon::default();
+        }
+
+        let mut pieces = Vec::new();
+        for polygon in &self.polygons {
+            for ring in std::iter::once(&polygon.exterior).chain(&polygon.holes) {
+                let mut ring = ring.clone();
+                ring.dedup();
+                while ring.len() > 1 && ring[0] == ring[ring.len() - 1] {
+                    ring.pop();
+                }
+                pieces.extend(ring_pieces(&ring, distance.abs(), style));
+            }
+        }
+
+        let pieces = MultiPolygon::new(pieces);
+        if distance < 0.0 {
+            return self.difference(&pieces);
+        }
+
+        return self.union(&pieces);
+    }
+}
+
+// left_normal returns the vector of the given length perpendicular to the
+// segment from a to b, on its left.
+fn left_normal(a: Point, b: Point, length: f64) -> Point {
+    let d = b.sub(a);
+    let scale = length / d.norm();
+    return Point {
+        x: -d.y * scale,
+        y: d.x * scale,
+    };
+}
+
+// edge_piece returns the rectangle made by the segment from a to b offset by n
+// on both sides. Its ends go through a and b, so that they share their edges
+// exactly with the pieces of the joins and caps.
+fn edge_piece(a: Point, b: Point, n: Point) -> Polygon {
+    return polygon_from_points(&[a.sub(n), b.sub(n), b, b.add(n), a.add(n), a]);
+}
+
+// join_piece returns the region covering the gap left on the outer side of the
+// turn at v between the pieces of the edges coming from u and going to w, whose
+// left normals are n1 and n2. It is None if the edges are aligned.
+fn join_piece(
+    (u, v, w): (Point, Point, Point),
+    (n1, n2): (Point, Point),
+    distance: f64,
+    style: BufferStyle,
+) -> Option<Polygon> {
+    let (d1, d2) = (v.sub(u), w.sub(v));
+    let turn = d1.cross(d2);
+    if turn == 0.0 && d1.dot(d2) > 0.0 {
+        return None;
+    }
+
+    // The outer side is on the right of a left turn, and on the left of a right
+    // turn. A U-turn is taken as a left turn.
+    let (start, end) = if turn >= 0.0 {
+        (n1.mul(-1.0), n2.mul(-1.0))
+    } else {
+        (n1, n2)
+    };
+    let angle = turn.atan2(d1.dot(d2));
+    let angle = if turn == 0.0 { PI } else { angle };
+
+    let mut ring = vec![v, v.add(start)];
+    match style.join {
+        Join::Round => ring.extend(style.arc(v, start, angle)),
+        Join::Miter(limit) => {
+            // The offset edges meet at distance / cos(angle / 2) from v, along the
+            // bisector of the normals.
+            let cos = (angle / 2.0).cos();
+            if cos * limit >= 1.0 {
+                let bisector = start.add(end).normalize();
+                ring.push(v.add(bisector.mul(distance / cos)));
+            }
+        }
+        Join::Bevel => {}
+    }
+    ring.push(v.add(end));
+
+    return Some(polygon_from_points(&ring));
+}
+
+// ring_pieces returns the pieces whose union is the region within the distance of
+// the closed ring, given without repeated points.
+fn ring_pieces(ring: &[Point], distance: f64, style: BufferStyle) -> Vec<Polygon> {
+    let n = ring.len();
+    if n < 2 {
+        return ring
+            .iter()
+            .map(|&p| {
+                point_buffer(
+                    p,
+                    distance,
+                    BufferStyle {
+                        cap: Cap::Round,
+                        ..style
+                    },
+                )
+            })
+            .collect();
+    }
+
+    let normals: Vec<Point> = (0..n)
+        .map(|i| left_normal(ring[i], ring[(i + 1) % n], distance))
+        .collect();
+
+    let mut pieces = Vec::with_capacity(2 * n);
+    for i in 0..n {
+        let (u, v, w) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+        pieces.push(edge_piece(v, w, normals[i]));
+        pieces.extend(join_piece(
+            (u, v, w),
+            (normals[(i + n - 1) % n], normals[i]),
+            distance,
+            style,
+        ));
+    }
+
+    return pieces;
+}
+
+// line_pieces returns the pieces whose union is the region within the distance of
+// the open polyline, given with at least two points and without repeated
+// consecutive points.
+fn line_pieces(line: &[Point], distance: f64, style: BufferStyle) -> Vec<Polygon> {
+    let n = line.len();
+    let normals: Vec<Point> = line
+        .windows(2)
+        .map(|w| left_normal(w[0], w[1], distance))
+        .collect();
+
+    let mut pieces = Vec::with_capacity(2 * n);
+    for i in 0..n - 1 {
+        pieces.push(edge_piece(line[i], line[i + 1], normals[i]));
+        if i > 0 {
+            let vertices = (line[i - 1], line[i], line[i + 1]);
+            pieces.extend(join_piece(
+                vertices,
+                (normals[i - 1], normals[i]),
+                distance,
+                style,
+            ));
+        }
+    }
+
+    // The caps go counter-clockwise around the ends, from the left of the first
+    // segment and from the right of the last one.
+    let ends = [
+        (line[0], normals[0], line[0].sub(line[1])),
+        (
+            line[n - 1],
+            normals[n - 2].mul(-1.0),
+            line[n - 1].sub(line[n - 2]),
+        ),
+    ];
+    for (p, start, outward) in ends {
+        let end = start.mul(-1.0);
+        let ring = match style.cap {
+            Cap::Round => {
+                let mut ring = vec![p, p.add(start)];
+                ring.extend(style.arc(p, start, PI));
+                ring.push(p.add(end));
+                ring
+            }
+            Cap::Square => {
+                let extension = outward.mul(distance / outward.norm());
+                vec![
+                    p,
+                    p.add(start),
+                    p.add(start).add(extension),
+                    p.add(end).add(extension),
+                    p.add(end),
+                ]
+            }
+            Cap::Flat => continue,
+        };
+        pieces.push(polygon_from_points(&ring));
+    }
+
+    return pieces;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segment::*;
+    use crate::test_util::*;
+
+    fn points(coords: &[(f64, f64)]) -> Vec<Point> {
+        coords.iter().map(|&(x, y)| Point { x, y }).collect()
+    }
+
+    fn style(join: Join, cap: Cap) -> BufferStyle {
+        BufferStyle {
+            join,
+            cap,
+            ..BufferStyle::default()
+        }
+    }
+
+    // distance_to_chain returns the distance from p to the chain of points,
+    // closed or not.
+    fn distance_to_chain(chain: &[Point], closed: bool, p: Point) -> f64 {
+        let mut segments: Vec<Segment> = chain
+            .windows(2)
+            .map(|w| Segment { a: w[0], b: w[1] })
+            .collect();
+        if closed {
+            segments.push(Segment {
+                a: chain[chain.len() - 1],
+                b: chain[0],
+            });
+        }
+
+        return segments
+            .iter()
+            .map(|s| s.distance_to_point(p))
+            .fold(f64::INFINITY, f64::min);
+    }
+
+    #[test]
+    fn points_and_lines() {
+        let p = Point { x: 1.0, y: 2.0 };
+        let disk = point_buffer(p, 2.0, BufferStyle::default());
+        assert!(disk.area() < 4.0 * PI);
+        assert!(disk.area() > 4.0 * PI * (1.0 - 2e-3));
+        assert_eq!(
+            16.0,
+            point_buffer(p, 2.0, style(Join::Round, Cap::Square)).area()
+        );
+        assert!(point_buffer(p, 2.0, style(Join::Round, Cap::Flat)).is_empty());
+        assert!(point_buffer(p, 0.0, BufferStyle::default()).is_empty());
+
+        // Invalid arc tolerances give the finest arcs, and large ones a square.
+        for arc_tolerance in [0.0, -1.0, f64::NAN] {
+            let disk = point_buffer(
+                p,
+                2.0,
+                BufferStyle {
+                    arc_tolerance,
+                    ..BufferStyle::default()
+                },
+            );
+            assert_eq!(MAX_ARC_STEPS as usize, disk.exterior.len());
+        }
+        let coarse = BufferStyle {
+            arc_tolerance: 5.0,
+            ..BufferStyle::default()
+        };
+        assert_eq!(8.0, point_buffer(p, 2.0, coarse).area());
+
+        // A polyline turning left at a right angle, whose segments are 4 long.
+        let line = polyline_from_points(&points(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0)]));
+        let tests_array: [(Join, Cap, f64); 5] = [
+            (Join::Miter(2.0), Cap::Flat, 16.0),
+            (Join::Miter(1.2), Cap::Flat, 15.5),
+            (Join::Bevel, Cap::Flat, 15.5),
+            (Join::Bevel, Cap::Square, 19.5),
+            (Join::Round, Cap::Round, 15.0 + 5.0 * PI / 4.0),
+        ];
+        for (join, cap, area) in tests_array {
+            let buffer = line.buffer(1.0, style(join, cap));
+            assert_eq!(1, buffer.polygons.len());
+            assert!((buffer.area() - area).abs() < 1e-2, "{join:?} {cap:?}");
+        }
+
+        // A closed polyline leaves a hole in the middle.
+        let square = polyline_from_points(&points(&[
+            (0.0, 0.0),
+            (4.0, 0.0),
+            (4.0, 4.0),
+            (0.0, 4.0),
+            (0.0, 0.0),
+        ]));
+        let buffer = square.buffer(1.0, style(Join::Miter(2.0), Cap::Round));
+        assert_eq!(1, buffer.polygons[0].holes.len());
+        assert_eq!(32.0, buffer.area());
+
+        let point = polyline_from_points(&points(&[(1.0, 1.0), (1.0, 1.0)]));
+        assert_eq!(
+            4.0,
+            point.buffer(1.0, style(Join::Round, Cap::Square)).area()
+        );
+        assert!(line.buffer(-1.0, BufferStyle::default()).is_empty());
+        assert!(Polyline::default()
+            .buffer(1.0, BufferStyle::default())
+            .is_empty());
+    }
+
+    #[test]
+    fn polygons() {
+        let square =
+            polygon_from_points(&points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]));
+        assert_eq!(
+            16.0,
+            square
+                .buffer(1.0, style(Join::Miter(2.0), Cap::Round))
+                .area()
+        );
+        assert_eq!(
+            14.0,
+            square.buffer(1.0, style(Join::Bevel, Cap::Round)).area()
+        );
+        let round = square.buffer(1.0, BufferStyle::default()).area();
+        assert!(round < 12.0 + PI && round > 12.0 + PI * (1.0 - 2e-3));
+        for join in [Join::Round, Join::Miter(2.0), Join::Bevel] {
+            assert_eq!(1.0, square.buffer(-0.5, style(join, Cap::Round)).area());
+            assert!(square.buffer(-1.0, style(join, Cap::Round)).is_empty());
+        }
+
+        // Growing a polygon shrinks its holes, until they close.
+        let frame = Polygon::new(
+            points(&[(0.0, 0.0), (6.0, 0.0), (6.0, 6.0), (0.0, 6.0)]),
+            vec![points(&[(2.0, 2.0), (4.0, 2.0), (4.0, 4.0), (2.0, 4.0)])],
+        );
+        let miter = style(Join::Miter(2.0), Cap::Round);
+        assert_eq!(48.0, frame.buffer(0.5, miter).area());
+        assert_eq!(64.0, frame.buffer(1.0, miter).area());
+        assert_eq!(1, frame.buffer(1.0, miter).polygons.len());
+
+        // Distances which are not finite, or too small to move the vertices.
+        for distance in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(square.buffer(distance, BufferStyle::default()).is_empty());
+            assert!(frame.buffer(distance, miter).is_empty());
+            let line = polyline_from_points(&square.exterior);
+            assert!(line.buffer(distance, BufferStyle::default()).is_empty());
+        }
+        for distance in [1e-250, -1e-250] {
+            let buffer = square.buffer(distance, BufferStyle::default());
+            assert_eq!(1, buffer.polygons.len());
+            assert_eq!(4.0, buffer.area());
+        }
+    }
+
+    #[test]
+    fn round_distance() {
+        let mut random = random_sequence(3);
+
+        // A star-shaped polygon, with reflex vertices, and a polyline through its
+        // vertices.
+        let ring: Vec<Point> = (0..12)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / 12.0;
+                let radius = 1.0 + random();
+                return Point {
+                    x: radius * angle.cos(),
+                    y: radius * angle.sin(),
+                };
+            })
+            .collect();
+        let polygon = polygon_from_points(&ring);
+        let line = polyline_from_points(&ring);
+
+        let distance = 0.3;
+        let grown = polygon.buffer(distance, BufferStyle::default());
+        let shrunk = polygon.buffer(-distance, BufferStyle::default());
+        let around = line.buffer(distance, BufferStyle::default());
+        let slack = 2e-3 * distance;
+
+        for _ in 0..2000 {
+            let p = Point {
+                x: 6.0 * random() - 3.0,
+                y: 6.0 * random() - 3.0,
+            };
+            let to_boundary = distance_to_chain(&ring, true, p);
+            let inside = polygon.contains_point(p);
+            if (to_boundary - distance).abs() < slack {
+                continue;
+            }
+
+            let near = to_boundary < distance;
+            assert_eq!(inside || near, grown.contains_point(p));
+            assert_eq!(inside && !near, shrunk.contains_point(p));
+
+            let to_line = distance_to_chain(&line.points, false, p);
+            if (to_line - distance).abs() >= slack {
+                assert_eq!(to_line < distance, around.contains_point(p));
+            }
+        }
+    }
+}
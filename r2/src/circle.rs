@@ -0,0 +1,256 @@
+use crate::point::*;
+use crate::rect::*;
+use std::f64::consts::PI;
+
+// Circle is the closed disk of the given radius around its center. A circle of
+// negative radius is empty.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Circle {
+    pub center: Point,
+    pub radius: f64,
+}
+
+// empty_circle constructs the canonical empty circle.
+pub fn empty_circle() -> Circle {
+    return Circle {
+        center: Point { x: 0.0, y: 0.0 },
+        radius: -1.0,
+    };
+}
+
+impl Circle {
+    pub fn new(center: Point, radius: f64) -> Circle {
+        Circle { center, radius }
+    }
+
+    pub fn is_empty(self) -> bool {
+        return self.radius < 0.0;
+    }
+
+    pub fn area(self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        return PI * self.radius * self.radius;
+    }
+
+    pub fn perimeter(self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        return 2.0 * PI * self.radius;
+    }
+
+    // bound returns the bounding rectangle of the circle, or the empty rectangle
+    // for an empty circle.
+    pub fn bound(self) -> Rect {
+        if self.is_empty() {
+            return empty_rect();
+        }
+
+        return rect_from_center_size(
+            self.center,
+            Point {
+                x: 2.0 * self.radius,
+                y: 2.0 * self.radius,
+            },
+        );
+    }
+
+    // contains_point reports whether p lies inside the circle or on its boundary.
+    pub fn contains_point(self, p: Point) -> bool {
+        return p.sub(self.center).norm() <= self.radius;
+    }
+}
+
+// circle_from_diameter returns the smallest circle through a and b.
+fn circle_from_diameter(a: Point, b: Point) -> Circle {
+    let center = a.add(b).mul(0.5);
+    return Circle {
+        center,
+        radius: a.sub(center).norm().max(b.sub(center).norm()),
+    };
+}
+
+// circumcircle returns the circle through a, b and c, or the smallest circle
+// containing them if they are collinear.
+fn circumcircle(a: Point, b: Point, c: Point) -> Circle {
+    let (ab, ac) = (b.sub(a), c.sub(a));
+    let d = 2.0 * ab.cross(ac);
+    if d == 0.0 {
+        let circles = [
+            circle_from_diameter(a, b),
+            circle_from_diameter(a, c),
+            circle_from_diameter(b, c),
+        ];
+        return circles
+            .into_iter()
+            .max_by(|x, y| x.radius.total_cmp(&y.radius))
+            .unwrap();
+    }
+
+    let (ab2, ac2) = (ab.dot(ab), ac.dot(ac));
+    let center = a.add(Point {
+        x: (ac.y * ab2 - ab.y * ac2) / d,
+        y: (ab.x * ac2 - ac.x * ab2) / d,
+    });
+    let radius = [a, b, c]
+        .iter()
+        .map(|p| p.sub(center).norm())
+        .fold(0.0, f64::max);
+
+    return Circle { center, radius };
+}
+
+// minimum_enclosing_circle returns the smallest circle containing the points,
+// or the empty circle if there are none, with Welzl's algorithm in expected O(n)
+// time. The points are shuffled in a fixed pseudo-random order, so that the
+// result does not depend on chance. The circle is grown by the rounding errors
+// as needed to contain every point, as reported by Circle::contains_point.
+pub fn minimum_enclosing_circle(points: &[Point]) -> Circle {
+    if points.is_empty() {
+        return empty_circle();
+    }
+
+    let mut shuffled = points.to_vec();
+    let mut state: u64 = 0x2545f4914f6cdd1d;
+    for i in (1..shuffled.len()).rev() {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        shuffled.swap(i, ((state >> 33) % (i as u64 + 1)) as usize);
+    }
+
+    // A point outside of the circle of the points before it lies on the boundary
+    // of their smallest circle, and so do two such points in the inner loops.
+    let outside = |circle: Circle, p: Point| {
+        return p.sub(circle.center).norm() > circle.radius * (1.0 + 1e-14);
+    };
+    let mut circle = Circle::new(shuffled[0], 0.0);
+    for i in 1..shuffled.len() {
+        if !outside(circle, shuffled[i]) {
+            continue;
+        }
+
+        circle = Circle::new(shuffled[i], 0.0);
+        for j in 0..i {
+            if !outside(circle, shuffled[j]) {
+                continue;
+            }
+
+            circle = circle_from_diameter(shuffled[i], shuffled[j]);
+            for k in 0..j {
+                if outside(circle, shuffled[k]) {
+                    circle = circumcircle(shuffled[i], shuffled[j], shuffled[k]);
+                }
+            }
+        }
+    }
+
+    for &p in points {
+        circle.radius = circle.radius.max(p.sub(circle.center).norm());
+    }
+
+    return circle;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    fn points(coords: &[(f64, f64)]) -> Vec<Point> {
+        coords.iter().map(|&(x, y)| Point { x, y }).collect()
+    }
+
+    #[test]
+    fn measures() {
+        let circle = Circle::new(Point { x: 1.0, y: 2.0 }, 2.0);
+        assert_eq!(4.0 * PI, circle.area());
+        assert_eq!(4.0 * PI, circle.perimeter());
+        assert!(circle
+            .bound()
+            .approx_equal(rect_from_points(&points(&[(-1.0, 0.0), (3.0, 4.0)]))));
+        assert!(circle.contains_point(Point { x: 3.0, y: 2.0 }));
+        assert!(!circle.contains_point(Point { x: 2.5, y: 3.5 }));
+
+        assert!(empty_circle().is_empty());
+        assert_eq!(0.0, empty_circle().area());
+        assert!(empty_circle().bound().is_empty());
+    }
+
+    #[test]
+    fn minimum_enclosing() {
+        let tests_array: [(Vec<Point>, Point, f64); 5] = [
+            (points(&[(1.0, 1.0)]), Point { x: 1.0, y: 1.0 }, 0.0),
+            (
+                points(&[(1.0, 1.0), (1.0, 1.0)]),
+                Point { x: 1.0, y: 1.0 },
+                0.0,
+            ),
+            (
+                points(&[(0.0, 0.0), (4.0, 0.0), (1.0, 0.0)]),
+                Point { x: 2.0, y: 0.0 },
+                2.0,
+            ),
+            (
+                points(&[(0.0, 0.0), (4.0, 0.0), (2.0, 1.0), (2.0, -1.0)]),
+                Point { x: 2.0, y: 0.0 },
+                2.0,
+            ),
+            (
+                points(&[(-1.0, 0.0), (1.0, 0.0), (0.0, 3.0), (0.0, 1.0)]),
+                Point {
+                    x: 0.0,
+                    y: 4.0 / 3.0,
+                },
+                5.0 / 3.0,
+            ),
+        ];
+        for (list, center, radius) in tests_array {
+            let circle = minimum_enclosing_circle(&list);
+            assert!(circle.center.approx_equal(center));
+            assert!((circle.radius - radius).abs() < 1e-15);
+        }
+        assert!(minimum_enclosing_circle(&[]).is_empty());
+    }
+
+    #[test]
+    fn minimum_enclosing_random() {
+        let mut random = random_sequence(6);
+
+        for n in 1..30 {
+            let list: Vec<Point> = (0..n)
+                .map(|_| Point {
+                    x: random() * 10.0,
+                    y: random() * 4.0,
+                })
+                .collect();
+            let circle = minimum_enclosing_circle(&list);
+            assert!(list.iter().all(|&p| circle.contains_point(p)));
+
+            // The smallest circle goes through two or three of the points.
+            let mut best = f64::INFINITY;
+            for i in 0..n {
+                for j in i..n {
+                    let mut candidates = vec![circle_from_diameter(list[i], list[j])];
+                    for k in j + 1..n {
+                        candidates.push(circumcircle(list[i], list[j], list[k]));
+                    }
+                    for c in candidates {
+                        if list
+                            .iter()
+                            .all(|&p| p.sub(c.center).norm() <= c.radius * (1.0 + 1e-12))
+                        {
+                            best = best.min(c.radius);
+                        }
+                    }
+                }
+            }
+            assert!((circle.radius - best).abs() <= 1e-12 * best);
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use crate::point::*;
+use crate::polygon::*;
+use crate::rect::*;
+use crate::segment::*;
+
+// Clipping of segments, polylines and polygons to a rectangle. The rectangle is
+// closed, so geometry running along its boundary is kept. Points computed on the
+// boundary are rounded, but always lie in the rectangle; input points inside the
+// rectangle are returned unchanged.
+
+impl Rect {
+    // clip_segment returns the part of the segment lying in the rectangle, or None
+    // if they do not intersect. It uses the Liang–Barsky algorithm, and keeps the
+    // direction of the segment.
+    pub fn clip_segment(self, s: Segment) -> Option<Segment> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let d = s.b.sub(s.a);
+        let mut t0: f64 = 0.0;
+        let mut t1: f64 = 1.0;
+
+        // Each boundary line, as p * t <= q for the points a + t * d inside it.
+        let constraints = [
+            (-d.x, s.a.x - self.x.lo),
+            (d.x, self.x.hi - s.a.x),
+            (-d.y, s.a.y - self.y.lo),
+            (d.y, self.y.hi - s.a.y),
+        ];
+
+        for (p, q) in constraints {
+            if p == 0.0 {
+                // Parallel to this boundary: either always or never inside.
+                if q < 0.0 {
+                    return None;
+                }
+                continue;
+            }
+
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+        }
+
+        if t0 > t1 {
+            return None;
+        }
+
+        let a = if t0 == 0.0 {
+            s.a
+        } else {
+            self.clamp_point(s.a.add(d.mul(t0)))
+        };
+        let b = if t1 == 1.0 {
+            s.b
+        } else {
+            self.clamp_point(s.a.add(d.mul(t1)))
+        };
+
+        return Some(Segment { a, b });
+    }
+
+    // clip_polyline returns the parts of the polyline lying in the rectangle, in
+    // order. The polyline is split wherever it leaves the rectangle, and parts
+    // reduced to a single point, where the polyline only touches the rectangle,
+    // are kept.
+    pub fn clip_polyline(self, points: &[Point]) -> Vec<Vec<Point>> {
+        let mut parts: Vec<Vec<Point>> = Vec::new();
+        if points.len() == 1 {
+            if self.contains_point(points[0]) {
+                parts.push(vec![points[0]]);
+            }
+            return parts;
+        }
+
+        // Whether the last part may go on with the next segment.
+        let mut open = false;
+        for edge in points.windows(2) {
+            let clipped = self.clip_segment(Segment {
+                a: edge[0],
+                b: edge[1],
+            });
+
+            match clipped {
+                Some(s) => {
+                    if open && s.a == edge[0] {
+                        parts.last_mut().unwrap().push(s.b);
+                    } else if s.a == s.b {
+                        parts.push(vec![s.a]);
+                    } else {
+                        parts.push(vec![s.a, s.b]);
+                    }
+                    open = s.b == edge[1];
+                }
+                None => open = false,
+            }
+        }
+
+        return parts;
+    }
+
+    // clip_ring returns the part of the ring lying in the rectangle, using the
+    // Sutherland–Hodgman algorithm. A concave ring crossing the rectangle several
+    // times is returned as a single ring, whose separate parts are joined by edges
+    // running along the boundary of the rectangle. An empty ring is returned when
+    // less than three vertices are left.
+    pub fn clip_ring(self, ring: &[Point]) -> Vec<Point> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+
+        let mut output = ring.to_vec();
+        output = clip_ring_to_line(&output, |p| p.x >= self.x.lo, |a, b| at_x(a, b, self.x.lo));
+        output = clip_ring_to_line(&output, |p| p.x <= self.x.hi, |a, b| at_x(a, b, self.x.hi));
+        output = clip_ring_to_line(&output, |p| p.y >= self.y.lo, |a, b| at_y(a, b, self.y.lo));
+        output = clip_ring_to_line(&output, |p| p.y <= self.y.hi, |a, b| at_y(a, b, self.y.hi));
+
+        return clean_clipped_ring(output);
+    }
+
+    // clip_polygon clips the outer ring and the holes of the polygon with
+    // clip_ring. Holes falling outside of the rectangle are dropped, and the
+    // result is empty if the outer ring is.
+    pub fn clip_polygon(self, polygon: &Polygon) -> Polygon {
+        let exterior = self.clip_ring(&polygon.exterior);
+        if exterior.is_empty() {
+            return Polygon::default();
+        }
+
+        let holes = polygon
+            .holes
+            .iter()
+            .map(|h| self.clip_ring(h))
+            .filter(|h| !h.is_empty())
+            .collect();
+
+        return Polygon::new(exterior, holes);
+    }
+}
+
+// clip_ring_to_line keeps the part of the ring on the inner side of a boundary
+// line, inserting the crossing points computed by cross.
+pub(crate) fn clip_ring_to_line(
+    ring: &[Point],
+    inside: impl Fn(Point) -> bool,
+    cross: impl Fn(Point, Point) -> Point,
+) -> Vec<Point> {
+    let mut output = Vec::with_capacity(ring.len() + 4);
+    if ring.is_empty() {
+        return output;
+    }
+
+    let mut prev = ring[ring.len() - 1];
+    for &cur in ring {
+        if inside(cur) {
+            if !inside(prev) {
+                output.push(cross(prev, cur));
+            }
+            output.push(cur);
+        } else if inside(prev) {
+            output.push(cross(prev, cur));
+        }
+        prev = cur;
+    }
+
+    return output;
+}
+
+// clean_clipped_ring removes the repeated vertices left by clip_ring_to_line
+// where the ring touches a boundary line, and returns an empty ring when less
+// than three vertices are left.
+pub(crate) fn clean_clipped_ring(mut ring: Vec<Point>) -> Vec<Point> {
+    ring.dedup();
+    while ring.len() > 1 && ring[0] == ring[ring.len() - 1] {
+        ring.pop();
+    }
+
+    if ring.len() < 3 {
+        return Vec::new();
+    }
+
+    return ring;
+}
+
+// at_x returns the point of the segment [a, b] at abscissa x, which lies strictly
+// between those of a and b.
+fn at_x(a: Point, b: Point, x: f64) -> Point {
+    let t = (x - a.x) / (b.x - a.x);
+    let y = a.y + t * (b.y - a.y);
+    return Point {
+        x,
+        y: y.clamp(a.y.min(b.y), a.y.max(b.y)),
+    };
+}
+
+// at_y returns the point of the segment [a, b] at ordinate y, which lies strictly
+// between those of a and b.
+fn at_y(a: Point, b: Point, y: f64) -> Point {
+    let t = (y - a.y) / (b.y - a.y);
+    let x = a.x + t * (b.x - a.x);
+    return Point {
+        x: x.clamp(a.x.min(b.x), a.x.max(b.x)),
+        y,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> Rect {
+        rect_from_points(&[Point { x: x0, y: y0 }, Point { x: x1, y: y1 }])
+    }
+
+    fn points(coords: &[(f64, f64)]) -> Vec<Point> {
+        coords.iter().map(|&(x, y)| Point { x, y }).collect()
+    }
+
+    fn segment(ax: f64, ay: f64, bx: f64, by: f64) -> Segment {
+        Segment {
+            a: Point { x: ax, y: ay },
+            b: Point { x: bx, y: by },
+        }
+    }
+
+    #[test]
+    fn clip_segment() {
+        let r = rect(0.0, 0.0, 4.0, 2.0);
+
+        let tests_array: [(Segment, Option<Segment>); 8] = [
+            // Inside.
+            (
+                segment(1.0, 1.0, 3.0, 1.5),
+                Some(segment(1.0, 1.0, 3.0, 1.5)),
+            ),
+            // Crossing the whole rectangle, backwards.
+            (
+                segment(5.0, 1.0, -1.0, 1.0),
+                Some(segment(4.0, 1.0, 0.0, 1.0)),
+            ),
+            // Leaving through the top.
+            (
+                segment(1.0, 1.0, 3.0, 3.0),
+                Some(segment(1.0, 1.0, 2.0, 2.0)),
+            ),
+            // Diagonal through two corners.
+            (
+                segment(-1.0, -0.5, 5.0, 2.5),
+                Some(segment(0.0, 0.0, 4.0, 2.0)),
+            ),
+            // Along the boundary.
+            (
+                segment(-1.0, 0.0, 1.0, 0.0),
+                Some(segment(0.0, 0.0, 1.0, 0.0)),
+            ),
+            // Outside, parallel.
+            (segment(-1.0, 3.0, 5.0, 3.0), None),
+            // Touching a corner.
+            (
+                segment(3.0, 3.0, 5.0, 1.0),
+                Some(segment(4.0, 2.0, 4.0, 2.0)),
+            ),
+            // Outside, across a corner.
+            (segment(3.0, 3.0, 6.0, 1.0), None),
+        ];
+
+        for (s, want) in tests_array {
+            assert_eq!(want, r.clip_segment(s));
+        }
+
+        assert_eq!(None, empty_rect().clip_segment(segment(0.0, 0.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn clip_polyline() {
+        let r = rect(0.0, 0.0, 2.0, 2.0);
+
+        // In, out through the right, back in, then touching a corner from outside.
+        let line = points(&[
+            (1.0, 1.0),
+            (3.0, 1.0),
+            (3.0, 0.5),
+            (1.0, 0.5),
+            (1.0, 1.5),
+            (1.0, 3.0),
+            (3.0, 3.0),
+            (3.0, 1.0),
+            (4.0, 0.0),
+        ]);
+        let want = vec![
+            points(&[(1.0, 1.0), (2.0, 1.0)]),
+            points(&[(2.0, 0.5), (1.0, 0.5), (1.0, 1.5), (1.0, 2.0)]),
+        ];
+        assert_eq!(want, r.clip_polyline(&line));
+
+        let touching = points(&[(3.0, 1.0), (1.0, 3.0)]);
+        assert_eq!(vec![points(&[(2.0, 2.0)])], r.clip_polyline(&touching));
+        assert!(r
+            .clip_polyline(&points(&[(5.0, 5.0), (6.0, 6.0)]))
+            .is_empty());
+    }
+
+    #[test]
+    fn clip_polygon() {
+        let r = rect(0.0, 0.0, 2.0, 2.0);
+
+        // A square centered on the corner of the rectangle, with a hole across
+        // the corner and another one outside.
+        let polygon = Polygon::new(
+            points(&[(1.0, 1.0), (3.0, 1.0), (3.0, 3.0), (1.0, 3.0)]),
+            vec![
+                points(&[(1.5, 1.5), (1.5, 2.5), (2.5, 2.5), (2.5, 1.5)]),
+                points(&[(2.5, 2.5), (2.5, 2.8), (2.8, 2.8)]),
+            ],
+        );
+
+        let clipped = r.clip_polygon(&polygon);
+        assert_eq!(
+            points(&[(1.0, 2.0), (1.0, 1.0), (2.0, 1.0), (2.0, 2.0)]),
+            clipped.exterior
+        );
+        assert_eq!(1, clipped.holes.len());
+        assert_eq!(1.0 - 0.25, clipped.area());
+
+        let outside = polygon_from_points(&points(&[(3.0, 3.0), (4.0, 3.0), (4.0, 4.0)]));
+        assert!(r.clip_polygon(&outside).is_empty());
+
+        let triangle = polygon_from_points(&points(&[(-2.0, 0.0), (4.0, 0.0), (1.0, 3.0)]));
+        let clipped = r.clip_polygon(&triangle);
+        assert_eq!(
+            points(&[(0.0, 2.0), (0.0, 0.0), (2.0, 0.0), (2.0, 2.0)]),
+            clipped.exterior
+        );
+    }
+}
@@ -0,0 +1,891 @@
+use crate::point::*;
+use crate::predicates::*;
+use crate::rect::*;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+// Triangulation is a triangulation of a set of points: the triangles, given as
+// triples of indices into points in counter-clockwise order, cover the convex
+// hull of the points without overlapping. Every distinct point is a vertex of
+// some triangle; duplicate points are represented by their first occurrence
+// only. Points that are all collinear have no triangles.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Triangulation {
+    pub points: Vec<Point>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl Triangulation {
+    // edges returns the edges of the triangles, each one once as a pair of point
+    // indices (i, j) with i < j, sorted.
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let mut edges: Vec<(usize, usize)> = Vec::with_capacity(3 * self.triangles.len());
+        for t in &self.triangles {
+            for i in 0..3 {
+                let (a, b) = (t[i], t[(i + 1) % 3]);
+                edges.push((a.min(b), a.max(b)));
+            }
+        }
+        edges.sort();
+        edges.dedup();
+
+        return edges;
+    }
+}
+
+// delaunay_triangulation returns the Delaunay triangulation of the points, in
+// which the circumcircle of every triangle has no point inside of it. Points
+// lying on a common circle can be triangulated in several such ways, and one of
+// them is chosen. Points are inserted one at a time along a space-filling curve,
+// so that the expected running time is O(n log n) for most inputs. The points
+// must be finite.
+pub fn delaunay_triangulation(points: &[Point]) -> Triangulation {
+    let mesh = match Mesh::new(points) {
+        Some(mesh) => mesh,
+        None => {
+            return Triangulation {
+                points: points.to_vec(),
+                triangles: Vec::new(),
+            };
+        }
+    };
+
+    return mesh.triangulation();
+}
+
+// ConstraintError is returned by constrained_delaunay_triangulation when the
+// constraints cannot all be edges of a triangulation of the points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintError {
+    // The constraint at the given position refers to a point that does not
+    // exist.
+    InvalidIndex(usize),
+    // The constraints at the given positions cross each other.
+    Crossing(usize, usize),
+}
+
+impl fmt::Display for ConstraintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstraintError::InvalidIndex(k) => {
+                write!(f, "constraint {} refers to a missing point", k)
+            }
+            ConstraintError::Crossing(j, k) => {
+                write!(f, "constraints {} and {} cross each other", j, k)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConstraintError {}
+
+// constrained_delaunay_triangulation returns the constrained Delaunay
+// triangulation of the points, in which every constraint, given as a pair of
+// point indices, is made of edges of the triangles, and which is otherwise as
+// close as possible to the Delaunay triangulation: the circumcircle of every
+// triangle has no point inside of it that can be seen from the triangle without
+// crossing a constraint. A constraint passing through other points is split
+// there into several edges. Constraints may share endpoints and overlap, but
+// must not cross each other.
+pub fn constrained_delaunay_triangulation(
+    points: &[Point],
+    constraints: &[(usize, usize)],
+) -> Result<Triangulation, ConstraintError> {
+    for (k, &(u, v)) in constraints.iter().enumerate() {
+        if u >= points.len() || v >= points.len() {
+            return Err(ConstraintError::InvalidIndex(k));
+        }
+    }
+
+    let mut mesh = match Mesh::new(points) {
+        Some(mesh) => mesh,
+        None => {
+            return Ok(Triangulation {
+                points: points.to_vec(),
+                triangles: Vec::new(),
+            });
+        }
+    };
+
+    for (k, &(u, v)) in constraints.iter().enumerate() {
+        mesh.insert_constraint(mesh.canonical[u], mesh.canonical[v], k)?;
+    }
+
+    return Ok(mesh.triangulation());
+}
+
+// GHOST is the vertex at infinity. Each edge of the convex hull is shared by a
+// real triangle and a ghost triangle made of the edge and GHOST, so that every
+// edge has two triangles and the triangles around each vertex form a cycle.
+const GHOST: usize = usize::MAX;
+
+// NONE marks a missing triangle or an edge which is not a constraint.
+const NONE: usize = usize::MAX;
+
+// Mesh is the triangulation under construction. The edge i of a triangle is the
+// one opposite its vertex i, going from vertex i + 1 to vertex i + 2.
+struct Mesh {
+    points: Vec<Point>,
+    // The vertices of each triangle, in counter-clockwise order. The ghost
+    // triangle of a hull edge going clockwise around the hull from a to b is
+    // [a, b, GHOST].
+    vertices: Vec<[usize; 3]>,
+    // The triangle across each edge.
+    neighbors: Vec<[usize; 3]>,
+    // The index of the constraint each edge is part of, or NONE.
+    constraints: Vec<[usize; 3]>,
+    alive: Vec<bool>,
+    free: Vec<usize>,
+    // A live triangle around each vertex, NONE for duplicate points.
+    vertex_triangle: Vec<usize>,
+    // The vertex standing for each point, which is itself but for duplicates.
+    canonical: Vec<usize>,
+    // A real triangle to start point location from.
+    last: usize,
+}
+
+impl Mesh {
+    // new triangulates the points, or returns None if they are all collinear.
+    fn new(points: &[Point]) -> Option<Mesh> {
+        let order = hilbert_order(points);
+
+        let a = *order.first()?;
+        let b = *order.iter().find(|&&i| points[i] != points[a])?;
+        let c = *order
+            .iter()
+            .find(|&&i| orient2d(points[a], points[b], points[i]) != 0.0)?;
+        let (b, c) = if orient2d(points[a], points[b], points[c]) > 0.0 {
+            (b, c)
+        } else {
+            (c, b)
+        };
+
+        let mut mesh = Mesh {
+            points: points.to_vec(),
+            vertices: Vec::new(),
+            neighbors: Vec::new(),
+            constraints: Vec::new(),
+            alive: Vec::new(),
+            free: Vec::new(),
+            vertex_triangle: vec![NONE; points.len()],
+            canonical: (0..points.len()).collect(),
+            last: 0,
+        };
+
+        let triangles = [[a, b, c], [b, a, GHOST], [c, b, GHOST], [a, c, GHOST]];
+        let created: Vec<usize> = triangles.iter().map(|&t| mesh.add_triangle(t)).collect();
+        mesh.link_all(&created);
+
+        for &i in &order {
+            if i != a && i != b && i != c {
+                mesh.insert_point(i);
+            }
+        }
+
+        return Some(mesh);
+    }
+
+    fn triangulation(&self) -> Triangulation {
+        let mut triangles: Vec<[usize; 3]> = Vec::new();
+        for (t, v) in self.vertices.iter().enumerate() {
+            if !self.alive[t] || v.contains(&GHOST) {
+                continue;
+            }
+
+            // Start from the smallest index, so that the output is canonical.
+            let first = (0..3).min_by_key(|&i| v[i]).unwrap();
+            triangles.push([v[first], v[(first + 1) % 3], v[(first + 2) % 3]]);
+        }
+        triangles.sort();
+
+        return Triangulation {
+            points: self.points.clone(),
+            triangles,
+        };
+    }
+
+    fn add_triangle(&mut self, v: [usize; 3]) -> usize {
+        let t = match self.free.pop() {
+            Some(t) => {
+                self.vertices[t] = v;
+                self.neighbors[t] = [NONE; 3];
+                self.constraints[t] = [NONE; 3];
+                self.alive[t] = true;
+                t
+            }
+            None => {
+                self.vertices.push(v);
+                self.neighbors.push([NONE; 3]);
+                self.constraints.push([NONE; 3]);
+                self.alive.push(true);
+                self.vertices.len() - 1
+            }
+        };
+
+        for &vertex in &v {
+            if vertex != GHOST {
+                self.vertex_triangle[vertex] = t;
+            }
+        }
+        if !v.contains(&GHOST) {
+            self.last = t;
+        }
+
+        return t;
+    }
+
+    fn remove_triangle(&mut self, t: usize) {
+        self.alive[t] = false;
+        self.free.push(t);
+    }
+
+    // edge_index returns the index of the edge of t joining x and y, in either
+    // direction.
+    fn edge_index(&self, t: usize, x: usize, y: usize) -> usize {
+        let v = self.vertices[t];
+        return (0..3).find(|&i| v[i] != x && v[i] != y).unwrap();
+    }
+
+    // link makes t and n neighbors across the edge i of t.
+    fn link(&mut self, t: usize, i: usize, n: usize) {
+        let v = self.vertices[t];
+        let j = self.edge_index(n, v[(i + 1) % 3], v[(i + 2) % 3]);
+        self.neighbors[t][i] = n;
+        self.neighbors[n][j] = t;
+        self.constraints[t][i] = self.constraints[n][j];
+    }
+
+    // link_all links the given triangles with each other across their shared
+    // edges.
+    fn link_all(&mut self, triangles: &[usize]) {
+        let mut edges: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+        for &t in triangles {
+            let v = self.vertices[t];
+            for i in 0..3 {
+                edges.insert((v[(i + 1) % 3], v[(i + 2) % 3]), (t, i));
+            }
+        }
+        for (&(x, y), &(t, i)) in &edges {
+            let (n, j) = edges[&(y, x)];
+            self.neighbors[t][i] = n;
+            self.neighbors[n][j] = t;
+        }
+    }
+
+    // in_conflict reports whether the triangle t would not be a Delaunay
+    // triangle any more once p is inserted: p lies inside its circumcircle, or
+    // for a ghost triangle strictly outside of its hull edge or on it.
+    fn in_conflict(&self, t: usize, p: Point) -> bool {
+        let [a, b, c] = self.vertices[t];
+        if c == GHOST {
+            let (a, b) = (self.points[a], self.points[b]);
+            let side = orient2d(a, b, p);
+            return side > 0.0 || (side == 0.0 && rect_from_points(&[a, b]).contains_point(p));
+        }
+
+        return incircle(self.points[a], self.points[b], self.points[c], p) > 0.0;
+    }
+
+    // locate returns a triangle in conflict with p, or the vertex equal to p.
+    fn locate(&self, p: Point) -> Result<usize, usize> {
+        let mut t = self.last;
+        let mut step = 0;
+        'walk: loop {
+            let v = self.vertices[t];
+            if v[2] == GHOST {
+                return Ok(t);
+            }
+
+            // Leave the triangle through an edge having p strictly on its other
+            // side. The first edge tried changes with each step, which prevents
+            // the walk from cycling.
+            step += 1;
+            for k in 0..3 {
+                let i = (step + k) % 3;
+                let (a, b) = (self.points[v[(i + 1) % 3]], self.points[v[(i + 2) % 3]]);
+                if orient2d(a, b, p) < 0.0 {
+                    t = self.neighbors[t][i];
+                    continue 'walk;
+                }
+            }
+
+            if let Some(&vertex) = v.iter().find(|&&i| self.points[i] == p) {
+                return Err(vertex);
+            }
+            return Ok(t);
+        }
+    }
+
+    // insert_point adds the point of index p to the triangulation, replacing the
+    // triangles in conflict with it by triangles joining it to the boundary of
+    // their union (the Bowyer–Watson algorithm).
+    fn insert_point(&mut self, p: usize) {
+        let point = self.points[p];
+        let start = match self.locate(point) {
+            Ok(t) => t,
+            Err(vertex) => {
+                self.canonical[p] = vertex;
+                return;
+            }
+        };
+
+        // Collect the conflicting triangles, and the edges bounding them as
+        // (x, y, outer) with outer the triangle across.
+        let mut cavity = vec![start];
+        let mut visited: HashMap<usize, bool> = HashMap::from([(start, true)]);
+        let mut boundary: Vec<(usize, usize, usize)> = Vec::new();
+        let mut k = 0;
+        while k < cavity.len() {
+            let t = cavity[k];
+            k += 1;
+            let v = self.vertices[t];
+            for i in 0..3 {
+                let n = self.neighbors[t][i];
+                let conflict = match visited.get(&n) {
+                    Some(&conflict) => conflict,
+                    None => {
+                        let conflict = self.in_conflict(n, point);
+                        visited.insert(n, conflict);
+                        if conflict {
+                            cavity.push(n);
+                        }
+                        conflict
+                    }
+                };
+                if !conflict {
+                    boundary.push((v[(i + 1) % 3], v[(i + 2) % 3], n));
+                }
+            }
+        }
+
+        for &t in &cavity {
+            self.remove_triangle(t);
+        }
+
+        // Join p to every boundary edge. A ghost triangle [GHOST, x, p] or
+        // [x, GHOST, p] is rotated to keep GHOST last.
+        let mut by_start: HashMap<usize, usize> = HashMap::new();
+        let mut created = Vec::with_capacity(boundary.len());
+        for &(x, y, outer) in &boundary {
+            let v = if x == GHOST {
+                [y, p, GHOST]
+            } else if y == GHOST {
+                [p, x, GHOST]
+            } else {
+                [x, y, p]
+            };
+            let t = self.add_triangle(v);
+            let i = self.edge_index(t, x, y);
+            self.link(t, i, outer);
+            by_start.insert(x, t);
+            created.push((x, y, t));
+        }
+
+        // The triangle joining p to the edge from x to y meets the one joining it
+        // to the edge starting at y along the edge from y to p.
+        for &(_, y, t) in &created {
+            let n = by_start[&y];
+            let i = self.edge_index(t, y, p);
+            self.neighbors[t][i] = n;
+            let j = self.edge_index(n, y, p);
+            self.neighbors[n][j] = t;
+        }
+    }
+
+    // find_edge returns the triangle having the edge from u to v, and the index
+    // of that edge, if u and v are joined by an edge.
+    fn find_edge(&self, u: usize, v: usize) -> Option<(usize, usize)> {
+        let start = self.vertex_triangle[u];
+        let mut t = start;
+        loop {
+            let vertices = self.vertices[t];
+            let k = vertices.iter().position(|&w| w == u).unwrap();
+            if vertices[(k + 1) % 3] == v {
+                return Some((t, (k + 2) % 3));
+            }
+
+            // Turn clockwise around u, across the edge from u to vertex k + 1.
+            t = self.neighbors[t][(k + 2) % 3];
+            if t == start {
+                return None;
+            }
+        }
+    }
+
+    // set_constraint marks the edge between u and v as part of the constraint
+    // of the given index.
+    fn set_constraint(&mut self, u: usize, v: usize, constraint: usize) {
+        let (t, i) = self.find_edge(u, v).unwrap();
+        let n = self.neighbors[t][i];
+        let j = self.edge_index(n, u, v);
+        self.constraints[t][i] = constraint;
+        self.constraints[n][j] = constraint;
+    }
+
+    // insert_constraint makes the segment from u to v a chain of edges of the
+    // triangulation, removing the edges crossing it by flips (the algorithm of
+    // Sloan) and restoring the constrained Delaunay property around it.
+    fn insert_constraint(
+        &mut self,
+        u: usize,
+        v: usize,
+        constraint: usize,
+    ) -> Result<(), ConstraintError> {
+        let mut pending = vec![(u, v)];
+        while let Some((u, v)) = pending.pop() {
+            if u == v {
+                continue;
+            }
+            if self.find_edge(u, v).is_some() {
+                self.set_constraint(u, v, constraint);
+                continue;
+            }
+
+            // The segment goes through a vertex w, or crosses the edges listed.
+            let (crossed, w) = self.crossed_edges(u, v, constraint)?;
+            if w != v {
+                pending.push((w, v));
+            }
+
+            let new_edges = self.flip_crossed_edges(u, w, crossed);
+            self.set_constraint(u, w, constraint);
+            self.legalize(new_edges);
+        }
+
+        return Ok(());
+    }
+
+    // crossed_edges walks along the segment from u to v, and returns the edges it
+    // crosses until it reaches a vertex w, which is v unless the segment goes
+    // through another vertex first.
+    fn crossed_edges(
+        &self,
+        u: usize,
+        v: usize,
+        constraint: usize,
+    ) -> Result<(Vec<(usize, usize)>, usize), ConstraintError> {
+        let (pu, pv) = (self.points[u], self.points[v]);
+
+        // Find the triangle around u in which the segment starts, as the edge
+        // (a, b) opposite u, with a on the right of the segment and b on its left.
+        let start = self.vertex_triangle[u];
+        let mut t = start;
+        let (mut a, mut b) = loop {
+            let vertices = self.vertices[t];
+            let k = vertices.iter().position(|&w| w == u).unwrap();
+            let (a, b) = (vertices[(k + 1) % 3], vertices[(k + 2) % 3]);
+            if a != GHOST && b != GHOST {
+                let side_a = orient2d(pu, pv, self.points[a]);
+                let side_b = orient2d(pu, pv, self.points[b]);
+                let ahead = |w: usize| self.points[w].sub(pu).dot(pv.sub(pu)) > 0.0;
+                if side_a == 0.0 && ahead(a) {
+                    return Ok((Vec::new(), a));
+                }
+                if side_b == 0.0 && ahead(b) {
+                    return Ok((Vec::new(), b));
+                }
+                if side_a < 0.0 && side_b > 0.0 {
+                    break (a, b);
+                }
+            }
+            t = self.neighbors[t][(k + 2) % 3];
+            debug_assert!(t != start);
+        };
+
+        let mut crossed = Vec::new();
+        loop {
+            let (n, i) = self.find_edge(b, a).unwrap();
+            if self.constraints[n][i] != NONE {
+                return Err(ConstraintError::Crossing(
+                    self.constraints[n][i],
+                    constraint,
+                ));
+            }
+            crossed.push((a, b));
+
+            let w = self.vertices[n][i];
+            let side = orient2d(pu, pv, self.points[w]);
+            if w == v || side == 0.0 {
+                return Ok((crossed, w));
+            }
+            if side < 0.0 {
+                a = w;
+            } else {
+                b = w;
+            }
+        }
+    }
+
+    // flip_crossed_edges flips the edges crossing the segment from u to v until
+    // none is left, and returns the edges created.
+    fn flip_crossed_edges(
+        &mut self,
+        u: usize,
+        v: usize,
+        crossed: Vec<(usize, usize)>,
+    ) -> Vec<(usize, usize)> {
+        let (pu, pv) = (self.points[u], self.points[v]);
+        let mut queue: VecDeque<(usize, usize)> = crossed.into();
+        let mut created = Vec::new();
+        while let Some((a, b)) = queue.pop_front() {
+            let (t, i) = self.find_edge(a, b).unwrap();
+            let c = self.vertices[t][i];
+            let n = self.neighbors[t][i];
+            let d = self.vertices[n][self.edge_index(n, a, b)];
+
+            // The quadrilateral a, d, b, c is not strictly convex: flip other
+            // edges first.
+            let (pc, pd) = (self.points[c], self.points[d]);
+            let side_a = orient2d(pc, pd, self.points[a]);
+            let side_b = orient2d(pc, pd, self.points[b]);
+            if !((side_a < 0.0 && side_b > 0.0) || (side_a > 0.0 && side_b < 0.0)) {
+                queue.push_back((a, b));
+                continue;
+            }
+
+            self.flip(t, i);
+            let side_c = orient2d(pu, pv, pc);
+            let side_d = orient2d(pu, pv, pd);
+            if (side_c < 0.0 && side_d > 0.0) || (side_c > 0.0 && side_d < 0.0) {
+                queue.push_back((c, d));
+            } else {
+                created.push((c, d));
+            }
+        }
+
+        return created;
+    }
+
+    // legalize flips the given edges, and the edges around them in turn, until
+    // all of them satisfy the constrained Delaunay property.
+    fn legalize(&mut self, edges: Vec<(usize, usize)>) {
+        let mut stack = edges;
+        while let Some((a, b)) = stack.pop() {
+            let (t, i) = match self.find_edge(a, b) {
+                Some(edge) => edge,
+                None => continue,
+            };
+            if self.constraints[t][i] != NONE {
+                continue;
+            }
+
+            let c = self.vertices[t][i];
+            let n = self.neighbors[t][i];
+            let d = self.vertices[n][self.edge_index(n, a, b)];
+            if c == GHOST || d == GHOST {
+                continue;
+            }
+
+            let p = |w: usize| self.points[w];
+            if incircle(p(a), p(b), p(c), p(d)) > 0.0 {
+                self.flip(t, i);
+                stack.extend([(a, d), (d, b), (b, c), (c, a)]);
+            }
+        }
+    }
+
+    // flip replaces the edge i of t, from a to b, and the two triangles sharing
+    // it, (a, b, c) and (b, a, d), by the edge from c to d and the triangles
+    // (c, a, d) and (d, b, c).
+    fn flip(&mut self, t: usize, i: usize) {
+        let [a, b, c] = [
+            self.vertices[t][(i + 1) % 3],
+            self.vertices[t][(i + 2) % 3],
+            self.vertices[t][i],
+        ];
+        let n = self.neighbors[t][i];
+        let j = self.edge_index(n, a, b);
+        let d = self.vertices[n][j];
+
+        let outer = |mesh: &Mesh, t: usize, x: usize, y: usize| {
+            let k = mesh.edge_index(t, x, y);
+            return mesh.neighbors[t][k];
+        };
+        let n_ca = outer(self, t, c, a);
+        let n_bc = outer(self, t, b, c);
+        let n_ad = outer(self, n, a, d);
+        let n_db = outer(self, n, d, b);
+        let constraint = |mesh: &Mesh, t: usize, x: usize, y: usize| {
+            let k = mesh.edge_index(t, x, y);
+            return mesh.constraints[t][k];
+        };
+        let c_ca = constraint(self, t, c, a);
+        let c_bc = constraint(self, t, b, c);
+        let c_ad = constraint(self, n, a, d);
+        let c_db = constraint(self, n, d, b);
+
+        self.vertices[t] = [c, a, d];
+        self.vertices[n] = [d, b, c];
+        self.neighbors[t] = [NONE, n, NONE];
+        self.neighbors[n] = [NONE, t, NONE];
+        self.constraints[t] = [c_ad, NONE, c_ca];
+        self.constraints[n] = [c_bc, NONE, c_db];
+        self.link(t, 0, n_ad);
+        self.link(t, 2, n_ca);
+        self.link(n, 0, n_bc);
+        self.link(n, 2, n_db);
+
+        self.vertex_triangle[a] = t;
+        self.vertex_triangle[c] = t;
+        self.vertex_triangle[b] = n;
+        self.vertex_triangle[d] = n;
+    }
+}
+
+// hilbert_order returns the indices of the points sorted along a Hilbert curve
+// filling their bounding rectangle, so that consecutive points are close to
+// each other.
+fn hilbert_order(points: &[Point]) -> Vec<usize> {
+    const ORDER: u32 = 16;
+    let side = (1u32 << ORDER) - 1;
+    let bound = rect_from_points(points);
+    let size = bound.size();
+    let cell = |value: f64, lo: f64, length: f64| {
+        if length <= 0.0 {
+            return 0;
+        }
+        return ((value - lo) / length * side as f64) as u32;
+    };
+
+    let mut keyed: Vec<(u64, usize)> = points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let x = cell(p.x, bound.x.lo, size.x);
+            let y = cell(p.y, bound.y.lo, size.y);
+            return (hilbert_index(ORDER, x, y), i);
+        })
+        .collect();
+    keyed.sort();
+
+    return keyed.into_iter().map(|(_, i)| i).collect();
+}
+
+// hilbert_index returns the position of the cell (x, y) along the Hilbert curve
+// filling a grid of 2^order by 2^order cells.
+fn hilbert_index(order: u32, mut x: u32, mut y: u32) -> u64 {
+    let n = 1u32 << order;
+    let mut index: u64 = 0;
+    let mut s = n / 2;
+    while s > 0 {
+        let rx = (x & s > 0) as u32;
+        let ry = (y & s > 0) as u32;
+        index += s as u64 * s as u64 * ((3 * rx) ^ ry) as u64;
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+
+    return index;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hull::*;
+    use crate::polygon::*;
+    use crate::test_util::*;
+
+    fn points(coords: &[(f64, f64)]) -> Vec<Point> {
+        coords.iter().map(|&(x, y)| Point { x, y }).collect()
+    }
+
+    fn random_points(seed: u64, count: usize) -> Vec<Point> {
+        let mut bits = random_bits(seed);
+        let mut random = || (bits() % 200) as f64 / 10.0;
+
+        // Coordinates on a coarse grid, with many collinear and cocircular
+        // points, and some duplicates.
+        return (0..count)
+            .map(|_| Point {
+                x: random(),
+                y: random(),
+            })
+            .collect();
+    }
+
+    // check_triangulation checks that the triangles are counter-clockwise and
+    // cover the convex hull of the points exactly once, using every distinct
+    // point.
+    fn check_triangulation(triangulation: &Triangulation) {
+        let points = &triangulation.points;
+        let mut area = 0.0;
+        let mut used = vec![false; points.len()];
+        for &[a, b, c] in &triangulation.triangles {
+            assert!(orient2d(points[a], points[b], points[c]) > 0.0);
+            area += polygon_from_points(&[points[a], points[b], points[c]]).area();
+            used[a] = true;
+            used[b] = true;
+            used[c] = true;
+        }
+
+        let hull = convex_hull_with(points, CollinearPoints::Keep);
+        assert!((area - hull.area()).abs() < 1e-9);
+
+        // Euler's formula, with every point on the hull boundary as a vertex.
+        let distinct = (0..points.len())
+            .filter(|&i| !points[..i].contains(&points[i]))
+            .count();
+        assert_eq!(
+            2 * distinct - 2 - hull.exterior.len(),
+            triangulation.triangles.len()
+        );
+        for i in 0..points.len() {
+            assert_eq!(!points[..i].contains(&points[i]), used[i]);
+        }
+    }
+
+    #[test]
+    fn delaunay_random() {
+        for seed in 0..5 {
+            let triangulation = delaunay_triangulation(&random_points(seed, 300));
+            check_triangulation(&triangulation);
+
+            let points = &triangulation.points;
+            for &[a, b, c] in &triangulation.triangles {
+                for &p in points {
+                    assert!(incircle(points[a], points[b], points[c], p) <= 0.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn delaunay_degenerate() {
+        let tests_array: [(Vec<Point>, usize); 6] = [
+            (vec![], 0),
+            (points(&[(1.0, 1.0)]), 0),
+            (points(&[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (1.0, 1.0)]), 0),
+            (points(&[(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (0.0, 0.0)]), 1),
+            // Points on a line, then one off it.
+            (
+                points(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0), (1.5, 1.0)]),
+                3,
+            ),
+            // A grid, whose squares are split along either diagonal.
+            (
+                (0..16)
+                    .map(|i| Point {
+                        x: (i % 4) as f64,
+                        y: (i / 4) as f64,
+                    })
+                    .collect(),
+                18,
+            ),
+        ];
+
+        for (input, want) in tests_array {
+            let triangulation = delaunay_triangulation(&input);
+            assert_eq!(want, triangulation.triangles.len());
+            if want > 0 {
+                check_triangulation(&triangulation);
+            }
+        }
+
+        // Either diagonal of a square is Delaunay.
+        let square = points(&[(0.0, 0.0), (2.0, 0.0), (0.0, 2.0), (2.0, 2.0)]);
+        let edges = delaunay_triangulation(&square).edges();
+        assert_eq!(5, edges.len());
+        assert!(edges.contains(&(0, 3)) != edges.contains(&(1, 2)));
+    }
+
+    #[test]
+    fn constrained() {
+        // A flat diamond, whose Delaunay triangulation has the short diagonal.
+        let diamond = points(&[(0.0, 0.0), (2.0, -0.5), (4.0, 0.0), (2.0, 0.5)]);
+        assert!(delaunay_triangulation(&diamond).edges().contains(&(1, 3)));
+        let triangulation = constrained_delaunay_triangulation(&diamond, &[(0, 2)]).unwrap();
+        assert_eq!(vec![[0, 1, 2], [0, 2, 3]], triangulation.triangles);
+
+        // A constraint through a point is split there.
+        let line = points(&[(0.0, 0.0), (2.0, 0.0), (4.0, 0.0), (2.0, 1.0), (2.0, -1.0)]);
+        let triangulation = constrained_delaunay_triangulation(&line, &[(0, 2)]).unwrap();
+        assert!(triangulation.edges().contains(&(0, 1)));
+        assert!(triangulation.edges().contains(&(1, 2)));
+
+        let tests_array: [(Vec<(usize, usize)>, ConstraintError); 3] = [
+            (vec![(0, 4)], ConstraintError::InvalidIndex(0)),
+            (
+                vec![(0, 2), (3, 3), (1, 3)],
+                ConstraintError::Crossing(0, 2),
+            ),
+            (
+                vec![(1, 3), (2, 2), (0, 2)],
+                ConstraintError::Crossing(0, 2),
+            ),
+        ];
+        for (constraints, want) in tests_array {
+            assert_eq!(
+                Err(want),
+                constrained_delaunay_triangulation(&diamond, &constraints)
+            );
+        }
+    }
+
+    #[test]
+    fn constrained_random() {
+        for seed in 0..5 {
+            let points = random_points(seed, 200);
+            let crosses = |(a, b): (Point, Point), (c, d): (Point, Point)| {
+                let sides = orient2d(a, b, c) * orient2d(a, b, d);
+                return sides < 0.0 && orient2d(c, d, a) * orient2d(c, d, b) < 0.0;
+            };
+            let segment = |(u, v): (usize, usize)| (points[u], points[v]);
+
+            // The constraints are the edges of the convex hull of some of the
+            // points, and the edges of an x-monotone chain through others that
+            // do not cross the hull.
+            let index = |p: Point| points.iter().position(|&q| q == p).unwrap();
+            let hull = convex_hull(&points[..40]).exterior;
+            let mut constraints: Vec<(usize, usize)> = (0..hull.len())
+                .map(|i| (index(hull[i]), index(hull[(i + 1) % hull.len()])))
+                .collect();
+            let mut chain: Vec<usize> = (40..60).collect();
+            chain.sort_by(|&i, &j| points[i].x.total_cmp(&points[j].x));
+            for pair in chain.windows(2) {
+                let edge = segment((pair[0], pair[1]));
+                if constraints.iter().all(|&c| !crosses(edge, segment(c))) {
+                    constraints.push((pair[0], pair[1]));
+                }
+            }
+
+            let triangulation = constrained_delaunay_triangulation(&points, &constraints).unwrap();
+            check_triangulation(&triangulation);
+            let constraints: Vec<(Point, Point)> = constraints.into_iter().map(segment).collect();
+
+            // No edge crosses a constraint, and every edge which is not part of
+            // a constraint is locally Delaunay, which characterizes the
+            // constrained Delaunay triangulation.
+            let on_constraint = |a: Point, b: Point| {
+                return constraints.iter().any(|&(u, v)| {
+                    let bound = rect_from_points(&[u, v]);
+                    return orient2d(u, v, a) == 0.0
+                        && orient2d(u, v, b) == 0.0
+                        && bound.contains_point(a)
+                        && bound.contains_point(b);
+                });
+            };
+            let mut opposite: HashMap<(usize, usize), usize> = HashMap::new();
+            for t in &triangulation.triangles {
+                for i in 0..3 {
+                    opposite.insert((t[(i + 1) % 3], t[(i + 2) % 3]), t[i]);
+                }
+            }
+            for (&(a, b), &c) in &opposite {
+                let (pa, pb) = (points[a], points[b]);
+                assert!(constraints.iter().all(|&s| !crosses((pa, pb), s)));
+                if let Some(&d) = opposite.get(&(b, a)) {
+                    if !on_constraint(pa, pb) {
+                        assert!(incircle(pa, pb, points[c], points[d]) <= 0.0);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use crate::point::*;
+use crate::polygon::*;
+use crate::predicates::*;
+
+// CollinearPoints tells convex_hull_with what to do with the input points lying
+// on an edge of the hull, between its two vertices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollinearPoints {
+    // Discard keeps only the corners of the hull.
+    Discard,
+    // Keep keeps every input point lying on the boundary of the hull.
+    Keep,
+}
+
+// convex_hull returns the smallest convex polygon containing the points, as a
+// counter-clockwise polygon without holes whose vertices are input points. Points
+// lying on the edges of the hull are discarded, see convex_hull_with. It is the
+// convex analogue of rect_from_points.
+pub fn convex_hull(points: &[Point]) -> Polygon {
+    return convex_hull_with(points, CollinearPoints::Discard);
+}
+
+// convex_hull_with returns the convex hull of the points, built with Andrew's
+// monotone chain algorithm in O(n log n). Duplicate points are merged. The
+// degenerate hulls of fewer than three points or of collinear points are
+// returned as the distinct extreme points, or every distinct point from one end
+// to the other with CollinearPoints::Keep, so their area is zero.
+pub fn convex_hull_with(points: &[Point], collinear: CollinearPoints) -> Polygon {
+    let mut sorted: Vec<Point> = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+
+    if sorted.len() < 3 {
+        return polygon_from_points(&sorted);
+    }
+
+    let first = sorted[0];
+    let last = sorted[sorted.len() - 1];
+    if sorted.iter().all(|&p| orient2d(first, last, p) == 0.0) {
+        if collinear == CollinearPoints::Keep {
+            return polygon_from_points(&sorted);
+        }
+        return polygon_from_points(&[first, last]);
+    }
+
+    // A point turning right, or going straight on when collinear points are
+    // discarded, is not a corner of the hull.
+    let keeps_turn = |chain: &[Point], p: Point| {
+        let n = chain.len();
+        let turn = orient2d(chain[n - 2], chain[n - 1], p);
+        match collinear {
+            CollinearPoints::Discard => turn > 0.0,
+            CollinearPoints::Keep => turn >= 0.0,
+        }
+    };
+
+    let mut hull: Vec<Point> = Vec::with_capacity(2 * sorted.len());
+
+    // Lower hull, from left to right.
+    for &p in &sorted {
+        while hull.len() >= 2 && !keeps_turn(&hull, p) {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+
+    // Upper hull, from right to left. The last point of the lower hull starts it
+    // and must not be popped. The lower hull ends by going up the rightmost
+    // vertical edge, if any, so the points on it are skipped.
+    let lower_len = hull.len() + 1;
+    for &p in sorted.iter().rev().filter(|p| p.x != last.x) {
+        while hull.len() >= lower_len && !keeps_turn(&hull, p) {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+
+    // The first point was appended again at the end of the upper hull.
+    hull.pop();
+
+    return polygon_from_points(&hull);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    fn points(coords: &[(f64, f64)]) -> Vec<Point> {
+        coords.iter().map(|&(x, y)| Point { x, y }).collect()
+    }
+
+    #[test]
+    fn square() {
+        // The corners, the middle of two edges, the center and a duplicate.
+        let input = points(&[
+            (1.0, 1.0),
+            (0.0, 0.0),
+            (2.0, 2.0),
+            (1.0, 0.0),
+            (2.0, 0.0),
+            (0.0, 2.0),
+            (0.0, 1.0),
+            (2.0, 2.0),
+        ]);
+
+        let hull = convex_hull(&input);
+        assert_eq!(
+            points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]),
+            hull.exterior
+        );
+        assert_eq!(4.0, hull.signed_area());
+
+        let hull = convex_hull_with(&input, CollinearPoints::Keep);
+        assert_eq!(
+            points(&[
+                (0.0, 0.0),
+                (1.0, 0.0),
+                (2.0, 0.0),
+                (2.0, 2.0),
+                (0.0, 2.0),
+                (0.0, 1.0),
+            ]),
+            hull.exterior
+        );
+
+        // Collinear points on the leftmost and rightmost vertical edges.
+        let input = points(&[
+            (0.0, 0.0),
+            (0.0, 1.0),
+            (0.0, 2.0),
+            (1.0, 3.0),
+            (2.0, 2.0),
+            (2.0, 1.0),
+            (2.0, 0.0),
+        ]);
+        let hull = convex_hull_with(&input, CollinearPoints::Keep);
+        assert_eq!(
+            points(&[
+                (0.0, 0.0),
+                (2.0, 0.0),
+                (2.0, 1.0),
+                (2.0, 2.0),
+                (1.0, 3.0),
+                (0.0, 2.0),
+                (0.0, 1.0)
+            ]),
+            hull.exterior
+        );
+        assert_eq!(
+            points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (1.0, 3.0), (0.0, 2.0)]),
+            convex_hull(&input).exterior
+        );
+    }
+
+    #[test]
+    fn degenerate() {
+        let tests_array: [(Vec<Point>, CollinearPoints, Vec<Point>); 5] = [
+            (vec![], CollinearPoints::Discard, vec![]),
+            (
+                points(&[(1.0, 1.0), (1.0, 1.0)]),
+                CollinearPoints::Discard,
+                points(&[(1.0, 1.0)]),
+            ),
+            (
+                points(&[(3.0, 3.0), (1.0, 1.0)]),
+                CollinearPoints::Keep,
+                points(&[(1.0, 1.0), (3.0, 3.0)]),
+            ),
+            (
+                points(&[(2.0, 2.0), (0.0, 0.0), (1.0, 1.0)]),
+                CollinearPoints::Discard,
+                points(&[(0.0, 0.0), (2.0, 2.0)]),
+            ),
+            (
+                points(&[(2.0, 2.0), (0.0, 0.0), (1.0, 1.0)]),
+                CollinearPoints::Keep,
+                points(&[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)]),
+            ),
+        ];
+
+        for (input, collinear, want) in tests_array {
+            assert_eq!(want, convex_hull_with(&input, collinear).exterior);
+        }
+    }
+
+    #[test]
+    fn contains_input() {
+        let mut bits = random_bits(7);
+        let mut random = || (bits() % 1000) as f64 / 10.0;
+        let input: Vec<Point> = (0..500)
+            .map(|_| Point {
+                x: random(),
+                y: random(),
+            })
+            .collect();
+
+        for collinear in [CollinearPoints::Discard, CollinearPoints::Keep] {
+            let hull = convex_hull_with(&input, collinear);
+            assert!(hull.is_normalized());
+            assert!(input.iter().all(|&p| hull.contains_point(p)));
+
+            // No vertex is repeated, and every turn is to the left, or straight on
+            // for kept collinear points.
+            let ring = &hull.exterior;
+            let mut unique = ring.clone();
+            unique.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+            unique.dedup();
+            assert_eq!(ring.len(), unique.len());
+            for i in 0..ring.len() {
+                let (a, b, c) = (
+                    ring[i],
+                    ring[(i + 1) % ring.len()],
+                    ring[(i + 2) % ring.len()],
+                );
+                let turn = orient2d(a, b, c);
+                match collinear {
+                    CollinearPoints::Discard => assert!(turn > 0.0),
+                    CollinearPoints::Keep => assert!(turn >= 0.0),
+                }
+            }
+        }
+    }
+}
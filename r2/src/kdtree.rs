@@ -0,0 +1,346 @@
+use crate::point::*;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+// The largest number of points scanned linearly at the bottom of the tree.
+const LEAF_SIZE: usize = 8;
+
+// KdTree is a static tree over a set of points, answering nearest neighbour and
+// radius queries in O(log n + k) on typical data. Results are given as indices
+// into the original points along with their squared distance to the query.
+//
+// The tree is implicit: the indices of the points are arranged so that every
+// range of them is split at its middle by the median along the axis on which the
+// range is the most spread, the lower half going before and the upper half after
+// it.
+pub struct KdTree {
+    points: Vec<Point>,
+    order: Vec<usize>,
+    // axes holds the splitting axis of every range, at the position of its
+    // middle.
+    axes: Vec<Axes>,
+}
+
+// kdtree_from_points builds a tree over the points in O(n log n).
+pub fn kdtree_from_points(points: &[Point]) -> KdTree {
+    let mut tree = KdTree {
+        points: points.to_vec(),
+        order: (0..points.len()).collect(),
+        axes: vec![Axes::XAxis; points.len()],
+    };
+    tree.build(0, points.len());
+
+    return tree;
+}
+
+// squared_distance returns the square of the distance between two points.
+fn squared_distance(p: Point, q: Point) -> f64 {
+    let d = p.sub(q);
+    return d.dot(d);
+}
+
+impl KdTree {
+    pub fn len(&self) -> usize {
+        return self.points.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.points.is_empty();
+    }
+
+    // points returns the points of the tree, in their original order.
+    pub fn points(&self) -> &[Point] {
+        return &self.points;
+    }
+
+    fn build(&mut self, lo: usize, hi: usize) {
+        if hi - lo <= LEAF_SIZE {
+            return;
+        }
+
+        let (mut min, mut max) = (self.points[self.order[lo]], self.points[self.order[lo]]);
+        for &i in &self.order[lo..hi] {
+            let p = self.points[i];
+            min = Point {
+                x: min.x.min(p.x),
+                y: min.y.min(p.y),
+            };
+            max = Point {
+                x: max.x.max(p.x),
+                y: max.y.max(p.y),
+            };
+        }
+        let axis = if max.x - min.x >= max.y - min.y {
+            Axes::XAxis
+        } else {
+            Axes::YAxis
+        };
+
+        let mid = lo + (hi - lo) / 2;
+        let points = &self.points;
+        self.order[lo..hi].select_nth_unstable_by(mid - lo, |&i, &j| {
+            return points[i][axis].total_cmp(&points[j][axis]);
+        });
+        self.axes[mid] = axis;
+
+        self.build(lo, mid);
+        self.build(mid + 1, hi);
+    }
+
+    // nearest returns the k points closest to q, by increasing distance. Points
+    // at equal distance are returned in no particular order.
+    pub fn nearest(&self, q: Point, k: usize) -> Vec<(usize, f64)> {
+        return self.approximate_nearest(q, k, 0.0);
+    }
+
+    // approximate_nearest returns k points close to q, by increasing distance,
+    // skipping the parts of the tree which cannot hold a point closer than
+    // 1 / (1 + epsilon) times the current k-th distance. The i-th returned point
+    // is thus at most 1 + epsilon times farther from q than the true i-th
+    // nearest point, and a positive epsilon makes the search visit fewer points.
+    pub fn approximate_nearest(&self, q: Point, k: usize, epsilon: f64) -> Vec<(usize, f64)> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let mut search = NearestSearch {
+            q,
+            k,
+            shrink: 1.0 / ((1.0 + epsilon.max(0.0)) * (1.0 + epsilon.max(0.0))),
+            heap: BinaryHeap::with_capacity(k + 1),
+        };
+        self.search_nearest(0, self.points.len(), &mut search);
+
+        let mut result: Vec<(usize, f64)> = search
+            .heap
+            .into_iter()
+            .map(|n| (n.index, n.squared_distance))
+            .collect();
+        result.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+
+        return result;
+    }
+
+    fn search_nearest(&self, lo: usize, hi: usize, search: &mut NearestSearch) {
+        if hi - lo <= LEAF_SIZE {
+            for &i in &self.order[lo..hi] {
+                search.offer(i, squared_distance(self.points[i], search.q));
+            }
+            return;
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        let i = self.order[mid];
+        let axis = self.axes[mid];
+        let diff = search.q[axis] - self.points[i][axis];
+        search.offer(i, squared_distance(self.points[i], search.q));
+
+        let (near, far) = if diff < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.search_nearest(near.0, near.1, search);
+        if search.reaches(diff * diff) {
+            self.search_nearest(far.0, far.1, search);
+        }
+    }
+
+    // within_distance returns the points at most the radius away from q, in no
+    // particular order.
+    pub fn within_distance(&self, q: Point, radius: f64) -> Vec<(usize, f64)> {
+        let mut result = Vec::new();
+        if radius >= 0.0 {
+            self.search_within(0, self.points.len(), q, radius * radius, &mut result);
+        }
+
+        return result;
+    }
+
+    fn search_within(
+        &self,
+        lo: usize,
+        hi: usize,
+        q: Point,
+        radius2: f64,
+        result: &mut Vec<(usize, f64)>,
+    ) {
+        if hi - lo <= LEAF_SIZE {
+            for &i in &self.order[lo..hi] {
+                let d = squared_distance(self.points[i], q);
+                if d <= radius2 {
+                    result.push((i, d));
+                }
+            }
+            return;
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        let i = self.order[mid];
+        let axis = self.axes[mid];
+        let diff = q[axis] - self.points[i][axis];
+        let d = squared_distance(self.points[i], q);
+        if d <= radius2 {
+            result.push((i, d));
+        }
+
+        if diff <= 0.0 || diff * diff <= radius2 {
+            self.search_within(lo, mid, q, radius2, result);
+        }
+        if diff >= 0.0 || diff * diff <= radius2 {
+            self.search_within(mid + 1, hi, q, radius2, result);
+        }
+    }
+}
+
+// NearestSearch holds the state of a k nearest neighbour search: the k closest
+// points found so far, in a max-heap so that the farthest of them comes first.
+struct NearestSearch {
+    q: Point,
+    k: usize,
+    // shrink is the factor applied to the squared distance of the k-th point
+    // found before pruning the tree, 1 for an exact search.
+    shrink: f64,
+    heap: BinaryHeap<Neighbor>,
+}
+
+impl NearestSearch {
+    fn offer(&mut self, index: usize, squared_distance: f64) {
+        if self.heap.len() < self.k {
+            self.heap.push(Neighbor {
+                squared_distance,
+                index,
+            });
+        } else if squared_distance < self.heap.peek().unwrap().squared_distance {
+            self.heap.pop();
+            self.heap.push(Neighbor {
+                squared_distance,
+                index,
+            });
+        }
+    }
+
+    // reaches reports whether a point at the given squared distance may still be
+    // among the k closest ones.
+    fn reaches(&self, squared_distance: f64) -> bool {
+        return self.heap.len() < self.k
+            || squared_distance < self.heap.peek().unwrap().squared_distance * self.shrink;
+    }
+}
+
+// Neighbor is a point found by a nearest neighbour search, ordered by distance.
+struct Neighbor {
+    squared_distance: f64,
+    index: usize,
+}
+
+impl PartialEq for Neighbor {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Neighbor {}
+
+impl PartialOrd for Neighbor {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Neighbor {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self
+            .squared_distance
+            .total_cmp(&other.squared_distance)
+            .then(self.index.cmp(&other.index));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    // points returns a deterministic pseudo-random list of points, some of them
+    // on a grid and repeated.
+    fn points(n: usize) -> Vec<Point> {
+        let mut random = random_sequence(4);
+
+        return (0..n)
+            .map(|i| {
+                let p = Point {
+                    x: random() * 100.0,
+                    y: random() * 10.0,
+                };
+                if i % 4 == 0 {
+                    return Point {
+                        x: p.x.round(),
+                        y: p.y.round(),
+                    };
+                }
+                return p;
+            })
+            .collect();
+    }
+
+    fn brute_force(list: &[Point], q: Point) -> Vec<(usize, f64)> {
+        let mut all: Vec<(usize, f64)> = list
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| (i, squared_distance(p, q)))
+            .collect();
+        all.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        return all;
+    }
+
+    #[test]
+    fn nearest() {
+        let list = points(5000);
+        let tree = kdtree_from_points(&list);
+        assert_eq!(5000, tree.len());
+
+        let queries = [
+            Point { x: 50.0, y: 5.0 },
+            Point { x: 3.0, y: 7.0 },
+            Point { x: -20.0, y: 40.0 },
+            list[17],
+        ];
+        for q in queries {
+            let all = brute_force(&list, q);
+            let distances = |result: &[(usize, f64)]| -> Vec<f64> {
+                return result.iter().map(|&(_, d)| d).collect();
+            };
+
+            for k in [1, 5, 100] {
+                let result = tree.nearest(q, k);
+                assert_eq!(distances(&all[..k]), distances(&result));
+                for &(i, d) in &result {
+                    assert_eq!(squared_distance(list[i], q), d);
+                }
+
+                let epsilon = 0.5;
+                let approximate = tree.approximate_nearest(q, k, epsilon);
+                assert_eq!(k, approximate.len());
+                for (&(_, d), &(_, exact)) in approximate.iter().zip(&all) {
+                    assert!(d <= exact * (1.0 + epsilon) * (1.0 + epsilon));
+                }
+            }
+
+            for radius in [0.0, 0.5, 3.0] {
+                let mut result = tree.within_distance(q, radius);
+                result.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+                let want: Vec<(usize, f64)> = all
+                    .iter()
+                    .copied()
+                    .filter(|&(_, d)| d <= radius * radius)
+                    .collect();
+                assert_eq!(want, result);
+            }
+        }
+
+        assert_eq!(5000, tree.nearest(queries[0], 10000).len());
+        assert!(tree.nearest(queries[0], 0).is_empty());
+        assert!(kdtree_from_points(&[]).nearest(queries[0], 3).is_empty());
+    }
+}
@@ -1,2 +1,43 @@
+#![allow(clippy::needless_return, clippy::should_implement_trait)]
+
+mod boolean;
+mod buffer;
+mod circle;
+mod clip;
+mod delaunay;
+mod hull;
+mod kdtree;
+mod oriented_rect;
 mod point;
+mod polygon;
+mod polyline;
+mod predicates;
+mod quadtree;
 mod rect;
+mod rtree;
+mod segment;
+mod sweep;
+mod sweep_status;
+#[cfg(test)]
+mod test_util;
+mod transform;
+mod voronoi;
+
+pub use boolean::*;
+pub use buffer::*;
+pub use circle::*;
+pub use delaunay::*;
+pub use hull::*;
+pub use kdtree::*;
+pub use oriented_rect::*;
+pub use point::*;
+pub use polygon::*;
+pub use polyline::*;
+pub use predicates::*;
+pub use quadtree::*;
+pub use rect::*;
+pub use rtree::*;
+pub use segment::*;
+pub use sweep::*;
+pub use transform::*;
+pub use voronoi::*;
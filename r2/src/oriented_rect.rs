@@ -0,0 +1,280 @@
+use crate::hull::*;
+use crate::point::*;
+use crate::rect::*;
+
+// OrientedRect is a rectangle rotated around its center. Its first pair of sides
+// is parallel to axis, a unit vector, and its second pair to axis.ortho(). A
+// rectangle with a negative dimension is empty.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrientedRect {
+    pub center: Point,
+    pub axis: Point,
+    pub size: Point,
+}
+
+// empty_oriented_rect constructs the canonical empty oriented rectangle.
+pub fn empty_oriented_rect() -> OrientedRect {
+    return OrientedRect {
+        center: Point { x: 0.0, y: 0.0 },
+        axis: Point { x: 1.0, y: 0.0 },
+        size: Point { x: -1.0, y: -1.0 },
+    };
+}
+
+impl OrientedRect {
+    pub fn is_empty(self) -> bool {
+        return self.size.x < 0.0 || self.size.y < 0.0;
+    }
+
+    pub fn area(self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        return self.size.x * self.size.y;
+    }
+
+    // vertices returns the four corners of the rectangle in counter-clockwise
+    // order, starting from the one at the low end of both axes.
+    pub fn vertices(self) -> [Point; 4] {
+        let u = self.axis.mul(self.size.x / 2.0);
+        let v = self.axis.ortho().mul(self.size.y / 2.0);
+        return [
+            self.center.sub(u).sub(v),
+            self.center.add(u).sub(v),
+            self.center.add(u).add(v),
+            self.center.sub(u).add(v),
+        ];
+    }
+
+    // bound returns the axis-aligned bounding rectangle of the rectangle.
+    pub fn bound(self) -> Rect {
+        if self.is_empty() {
+            return empty_rect();
+        }
+
+        return rect_from_points(&self.vertices());
+    }
+
+    // contains_point reports whether p lies inside the rectangle or on its
+    // boundary.
+    pub fn contains_point(self, p: Point) -> bool {
+        let d = p.sub(self.center);
+        return d.dot(self.axis).abs() <= self.size.x / 2.0
+            && d.dot(self.axis.ortho()).abs() <= self.size.y / 2.0;
+    }
+}
+
+// minimum_area_rect returns the rectangle of smallest area containing the
+// points, or the empty rectangle if there are none. One of its sides lies on an
+// edge of the convex hull of the points, so the rotating calipers find it in
+// O(n log n), dominated by the computation of the hull. The rectangle of
+// collinear points has a zero height and is oriented along them.
+pub fn minimum_area_rect(points: &[Point]) -> OrientedRect {
+    let hull = convex_hull(points).exterior;
+    let n = hull.len();
+    match n {
+        0 => return empty_oriented_rect(),
+        1 => {
+            return OrientedRect {
+                center: hull[0],
+                axis: Point { x: 1.0, y: 0.0 },
+                size: Point { x: 0.0, y: 0.0 },
+            }
+        }
+        2 => {
+            let d = hull[1].sub(hull[0]);
+            return OrientedRect {
+                center: hull[0].add(hull[1]).mul(0.5),
+                axis: d.normalize(),
+                size: Point {
+                    x: d.norm(),
+                    y: 0.0,
+                },
+            };
+        }
+        _ => {}
+    }
+
+    // For every edge i of the hull, with direction e and inward normal f, right
+    // and left are the vertices farthest along e and -e, and top the vertex
+    // farthest along f. They only move forward as the edge turns around the hull.
+    let next = |i: usize| (i + 1) % n;
+    let (mut right, mut top, mut left) = (1, 1, 1);
+    let mut best = empty_oriented_rect();
+    for i in 0..n {
+        let origin = hull[i];
+        let e = hull[next(i)].sub(origin).normalize();
+        let f = e.ortho();
+        let along = |j: usize, dir: Point| hull[j].sub(origin).dot(dir);
+
+        if i == 0 {
+            right = next(i);
+        }
+        while along(next(right), e) > along(right, e) {
+            right = next(right);
+        }
+        if i == 0 {
+            top = right;
+        }
+        while along(next(top), f) > along(top, f) {
+            top = next(top);
+        }
+        if i == 0 {
+            left = top;
+        }
+        while along(next(left), e) < along(left, e) {
+            left = next(left);
+        }
+
+        let (lo, hi, height) = (along(left, e), along(right, e), along(top, f));
+        let candidate = OrientedRect {
+            center: origin.add(e.mul((lo + hi) / 2.0)).add(f.mul(height / 2.0)),
+            axis: e,
+            size: Point {
+                x: hi - lo,
+                y: height,
+            },
+        };
+        if best.is_empty() || candidate.area() < best.area() {
+            best = candidate;
+        }
+    }
+
+    return best;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    // contains_approx reports whether p lies in r up to rounding errors.
+    fn contains_approx(r: OrientedRect, p: Point) -> bool {
+        let d = p.sub(r.center);
+        let eps = 1e-12 * (1.0 + r.center.norm() + r.size.norm());
+        return d.dot(r.axis).abs() <= r.size.x / 2.0 + eps
+            && d.dot(r.axis.ortho()).abs() <= r.size.y / 2.0 + eps;
+    }
+
+    #[test]
+    fn measures() {
+        let r = OrientedRect {
+            center: Point { x: 1.0, y: 1.0 },
+            axis: Point { x: 0.6, y: 0.8 },
+            size: Point { x: 10.0, y: 5.0 },
+        };
+        assert_eq!(50.0, r.area());
+
+        let want = [(0.0, -4.5), (6.0, 3.5), (2.0, 6.5), (-4.0, -1.5)];
+        for (v, (x, y)) in r.vertices().into_iter().zip(want) {
+            assert!(v.approx_equal(Point { x, y }));
+        }
+        assert!(r.bound().approx_equal(rect_from_points(&[
+            Point { x: -4.0, y: -4.5 },
+            Point { x: 6.0, y: 6.5 }
+        ])));
+        assert!(r.contains_point(r.center));
+        assert!(!r.contains_point(Point { x: 4.0, y: -1.0 }));
+
+        assert!(empty_oriented_rect().is_empty());
+        assert_eq!(0.0, empty_oriented_rect().area());
+        assert!(empty_oriented_rect().bound().is_empty());
+    }
+
+    #[test]
+    fn degenerate() {
+        assert!(minimum_area_rect(&[]).is_empty());
+
+        let p = Point { x: 2.0, y: 3.0 };
+        let r = minimum_area_rect(&[p, p]);
+        assert_eq!(p, r.center);
+        assert_eq!(0.0, r.area());
+
+        let line = [
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 3.0, y: 4.0 },
+            Point { x: 1.5, y: 2.0 },
+        ];
+        let r = minimum_area_rect(&line);
+        assert!(r.center.approx_equal(Point { x: 1.5, y: 2.0 }));
+        assert!(r.size.approx_equal(Point { x: 5.0, y: 0.0 }));
+        assert!(line.iter().all(|&p| contains_approx(r, p)));
+    }
+
+    #[test]
+    fn rotated() {
+        // A grid of points filling a 4 x 2 rectangle rotated by 30 degrees, whose
+        // axis-aligned bound is much larger.
+        let (sin, cos) = 30f64.to_radians().sin_cos();
+        let mut list = Vec::new();
+        for i in 0..=8 {
+            for j in 0..=4 {
+                let (x, y) = (i as f64 * 0.5, j as f64 * 0.5);
+                list.push(Point {
+                    x: 10.0 + x * cos - y * sin,
+                    y: -5.0 + x * sin + y * cos,
+                });
+            }
+        }
+
+        let r = minimum_area_rect(&list);
+        assert!((r.area() - 8.0).abs() < 1e-12);
+        assert!(rect_from_points(&list).area() > 11.0);
+        assert!(list.iter().all(|&p| contains_approx(r, p)));
+        let corner = r.vertices().into_iter().any(|v| {
+            return v.approx_equal(Point { x: 10.0, y: -5.0 });
+        });
+        assert!(corner);
+    }
+
+    #[test]
+    fn random() {
+        let mut random = random_sequence(9);
+
+        for n in 3..60 {
+            let list: Vec<Point> = (0..n)
+                .map(|_| Point {
+                    x: random() * 10.0 - random() * 3.0,
+                    y: random() * 4.0 + random() * 3.0,
+                })
+                .collect();
+            let r = minimum_area_rect(&list);
+            assert!(list.iter().all(|&p| contains_approx(r, p)));
+
+            // Brute force over the directions of the edges of the hull.
+            let hull = convex_hull(&list).exterior;
+            let mut best = f64::INFINITY;
+            for i in 0..hull.len() {
+                let e = hull[(i + 1) % hull.len()].sub(hull[i]).normalize();
+                let (mut lo, mut hi) = (
+                    Point {
+                        x: f64::MAX,
+                        y: f64::MAX,
+                    },
+                    Point {
+                        x: f64::MIN,
+                        y: f64::MIN,
+                    },
+                );
+                for p in &list {
+                    let q = Point {
+                        x: p.dot(e),
+                        y: p.dot(e.ortho()),
+                    };
+                    lo = Point {
+                        x: lo.x.min(q.x),
+                        y: lo.y.min(q.y),
+                    };
+                    hi = Point {
+                        x: hi.x.max(q.x),
+                        y: hi.y.max(q.y),
+                    };
+                }
+                best = best.min((hi.x - lo.x) * (hi.y - lo.y));
+            }
+            assert!((r.area() - best).abs() <= 1e-12 * best);
+        }
+    }
+}
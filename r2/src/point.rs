@@ -1,56 +1,100 @@
+use r1::__private::Scanner;
+use r1::{Float, ParseError, Scalar, Tolerance};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign,
+};
+use std::str::FromStr;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
-pub struct Point {
-    pub x: f64,
-    pub y: f64,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point<T = f64> {
+    pub x: T,
+    pub y: T,
 }
 
-impl Point {
-    pub fn add(self, p: Point) -> Point {
+// The two axes of ℝ².
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axes {
+    XAxis = 0,
+    YAxis,
+}
+
+impl<T: Scalar> Point<T> {
+    pub fn add(self, p: Point<T>) -> Point<T> {
         Point {
             x: self.x + p.x,
             y: self.y + p.y,
         }
     }
 
-    pub fn sub(self, p: Point) -> Point {
+    pub fn sub(self, p: Point<T>) -> Point<T> {
         Point {
             x: self.x - p.x,
             y: self.y - p.y,
         }
     }
 
-    pub fn mul(self, m: f64) -> Point {
+    pub fn mul(self, m: T) -> Point<T> {
         Point {
             x: self.x * m,
             y: self.y * m,
         }
     }
 
-    pub fn ortho(self) -> Point {
-        Point {
-            x: -self.y,
-            y: self.x,
-        }
-    }
-
-    pub fn dot(self, p: Point) -> f64 {
+    pub fn dot(self, p: Point<T>) -> T {
         return self.x * p.x + self.y * p.y;
     }
 
-    pub fn cross(self, p: Point) -> f64 {
+    pub fn cross(self, p: Point<T>) -> T {
         return self.x * p.y - self.y * p.x;
     }
 
-    pub fn norm(self) -> f64 {
+    // cast converts the point to another scalar type, see r1::Scalar::from_f64.
+    pub fn cast<U: Scalar>(self) -> Point<U> {
+        Point {
+            x: U::from_f64(self.x.to_f64()),
+            y: U::from_f64(self.y.to_f64()),
+        }
+    }
+}
+
+impl<T: Scalar + Neg<Output = T>> Point<T> {
+    pub fn ortho(self) -> Point<T> {
+        Point {
+            x: -self.y,
+            y: self.x,
+        }
+    }
+}
+
+impl<T: Float> Point<T> {
+    pub fn norm(self) -> T {
         return self.x.hypot(self.y);
     }
 
-    pub fn normalize(self) -> Point {
-        if self.x == 0.0 && self.y == 0.0 {
+    pub fn normalize(self) -> Point<T> {
+        if self.x == T::zero() && self.y == T::zero() {
             return self;
         }
 
-        return self.mul(1.0 / self.norm());
+        return self.mul(T::one() / self.norm());
+    }
+}
+
+impl Point {
+    // approx_equal reports whether both coordinates of the two points are equal up
+    // to the default tolerance.
+    pub fn approx_equal(self, p: Point) -> bool {
+        return self.approx_equal_with(p, Tolerance::default());
+    }
+
+    // approx_equal_with reports whether both coordinates of the two points are
+    // equal up to the given tolerance.
+    pub fn approx_equal_with(self, p: Point, tolerance: Tolerance) -> bool {
+        return tolerance.approx_eq(self.x, p.x) && tolerance.approx_eq(self.y, p.y);
     }
 
     pub fn string(self) -> String {
@@ -58,13 +102,283 @@ impl Point {
     }
 }
 
+// Display writes the point in the "(x, y)" form used by string(). Formatting
+// options apply to both coordinates, so format!("{:.12}", p) is p.string(), while
+// the default shortest representation parses back to the very same point.
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(")?;
+        fmt::Display::fmt(&self.x, f)?;
+        f.write_str(", ")?;
+        fmt::Display::fmt(&self.y, f)?;
+        f.write_str(")")
+    }
+}
+
+// FromStr parses the "(x, y)" form written by string() and Display.
+impl<T: FromStr> FromStr for Point<T> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut scanner = Scanner::new(s);
+        let p = parse_point(&mut scanner)?;
+        scanner.finish()?;
+
+        return Ok(p);
+    }
+}
+
+// parse_point reads a point written either as "(x, y)" or, as found in the
+// output of Rect::string, in its Debug form "Point { x: x, y: y }".
+pub(crate) fn parse_point<T: FromStr>(scanner: &mut Scanner) -> Result<Point<T>, ParseError> {
+    if scanner.peek("Point") {
+        scanner.expect("Point")?;
+        scanner.expect("{")?;
+        scanner.expect("x")?;
+        scanner.expect(":")?;
+        let x = scanner.number()?;
+        scanner.expect(",")?;
+        scanner.expect("y")?;
+        scanner.expect(":")?;
+        let y = scanner.number()?;
+        scanner.expect("}")?;
+
+        return Ok(Point { x, y });
+    }
+
+    scanner.expect("(")?;
+    let x = scanner.number()?;
+    scanner.expect(",")?;
+    let y = scanner.number()?;
+    scanner.expect(")")?;
+
+    return Ok(Point { x, y });
+}
+
+// Arithmetic operators, matching the add, sub and mul methods. Division by a
+// scalar follows the rules of T, so integer points are divided component-wise
+// with truncation.
+impl<T: Scalar> Add for Point<T> {
+    type Output = Point<T>;
+
+    fn add(self, p: Point<T>) -> Point<T> {
+        return Point::add(self, p);
+    }
+}
+
+impl<T: Scalar> Sub for Point<T> {
+    type Output = Point<T>;
+
+    fn sub(self, p: Point<T>) -> Point<T> {
+        return Point::sub(self, p);
+    }
+}
+
+impl<T: Scalar + Neg<Output = T>> Neg for Point<T> {
+    type Output = Point<T>;
+
+    fn neg(self) -> Point<T> {
+        Point {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
+impl<T: Scalar> Mul<T> for Point<T> {
+    type Output = Point<T>;
+
+    fn mul(self, m: T) -> Point<T> {
+        return Point::mul(self, m);
+    }
+}
+
+impl<T: Scalar> Div<T> for Point<T> {
+    type Output = Point<T>;
+
+    fn div(self, d: T) -> Point<T> {
+        Point {
+            x: self.x / d,
+            y: self.y / d,
+        }
+    }
+}
+
+impl<T: Scalar> AddAssign for Point<T> {
+    fn add_assign(&mut self, p: Point<T>) {
+        *self = *self + p;
+    }
+}
+
+impl<T: Scalar> SubAssign for Point<T> {
+    fn sub_assign(&mut self, p: Point<T>) {
+        *self = *self - p;
+    }
+}
+
+impl<T: Scalar> MulAssign<T> for Point<T> {
+    fn mul_assign(&mut self, m: T) {
+        *self = *self * m;
+    }
+}
+
+impl<T: Scalar> DivAssign<T> for Point<T> {
+    fn div_assign(&mut self, d: T) {
+        *self = *self / d;
+    }
+}
+
+impl<T: Scalar> Sum for Point<T> {
+    fn sum<I: Iterator<Item = Point<T>>>(iter: I) -> Point<T> {
+        let zero = Point {
+            x: T::zero(),
+            y: T::zero(),
+        };
+
+        return iter.fold(zero, |acc, p| acc + p);
+    }
+}
+
+impl<'a, T: Scalar> Sum<&'a Point<T>> for Point<T> {
+    fn sum<I: Iterator<Item = &'a Point<T>>>(iter: I) -> Point<T> {
+        return iter.copied().sum();
+    }
+}
+
+impl<T> Index<Axes> for Point<T> {
+    type Output = T;
+
+    fn index(&self, axis: Axes) -> &T {
+        match axis {
+            Axes::XAxis => &self.x,
+            Axes::YAxis => &self.y,
+        }
+    }
+}
+
+impl<T> IndexMut<Axes> for Point<T> {
+    fn index_mut(&mut self, axis: Axes) -> &mut T {
+        match axis {
+            Axes::XAxis => &mut self.x,
+            Axes::YAxis => &mut self.y,
+        }
+    }
+}
+
+impl<T> From<[T; 2]> for Point<T> {
+    fn from([x, y]: [T; 2]) -> Self {
+        Point { x, y }
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Point { x, y }
+    }
+}
+
+impl<T> From<Point<T>> for [T; 2] {
+    fn from(p: Point<T>) -> Self {
+        [p.x, p.y]
+    }
+}
+
+impl<T> From<Point<T>> for (T, T) {
+    fn from(p: Point<T>) -> Self {
+        (p.x, p.y)
+    }
+}
+
+// Lossless conversions between point precisions. Use Point::cast for the other
+// ones.
+macro_rules! impl_point_from {
+    ($($from:ty => $to:ty),*) => {$(
+        impl From<Point<$from>> for Point<$to> {
+            fn from(p: Point<$from>) -> Self {
+                Point {
+                    x: p.x.into(),
+                    y: p.y.into(),
+                }
+            }
+        }
+    )*};
+}
+
+impl_point_from!(
+    f32 => f64,
+    i32 => f64,
+    u32 => f64,
+    i16 => f32,
+    u16 => f32,
+    i32 => i64,
+    u32 => u64
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
+    fn cross_and_dot() {
+        let a = Point { x: 1.0, y: 0.0 };
+        let b = Point { x: 0.0, y: 1.0 };
+
+        assert_eq!(1.0, a.cross(b));
+        assert_eq!(-1.0, b.cross(a));
+        assert_eq!(0.0, a.dot(b));
+        assert_eq!(b, a.ortho());
+    }
+
+    #[test]
+    fn generic_scalars() {
+        let a: Point<i32> = Point { x: 3, y: -4 };
+        let b: Point<i32> = Point { x: 1, y: 2 };
+
+        assert_eq!(-5, a.dot(b));
+        assert_eq!(10, a.cross(b));
+        assert_eq!(Point { x: 6, y: -8 }, a.mul(2));
+
+        let f: Point<f32> = a.cast();
+        assert_eq!(5.0, f.norm());
+        assert_eq!(Point { x: 3.0, y: -4.0 }, Point::<f64>::from(a));
+        assert_eq!(Point { x: 1u8, y: 0u8 }, Point { x: 0.6, y: -2.0 }.cast());
+    }
+
+    #[test]
+    fn operators() {
+        let mut p = Point { x: 1.0, y: 2.0 };
+        let q = Point::from([3.0, -1.0]);
+
+        assert_eq!(Point { x: 4.0, y: 1.0 }, p + q);
+        assert_eq!(Point { x: -2.0, y: 3.0 }, p - q);
+        assert_eq!(Point { x: -1.0, y: -2.0 }, -p);
+        assert_eq!(Point { x: 3.0, y: 6.0 }, p * 3.0);
+        assert_eq!(Point { x: 0.5, y: 1.0 }, p / 2.0);
+        assert_eq!(Point { x: 4.0, y: 1.0 }, [p, q].iter().sum());
+
+        p += q;
+        p -= Point::from((1.0, 1.0));
+        p *= 2.0;
+        p /= 4.0;
+        assert_eq!(Point { x: 1.5, y: 0.0 }, p);
+
+        p[Axes::YAxis] = 7.0;
+        assert_eq!(1.5, p[Axes::XAxis]);
+        assert_eq!((1.5, 7.0), p.into());
+        assert_eq!([3, 2], <[i32; 2]>::from(Point { x: 7, y: 5 } / 2));
+    }
+
+    #[test]
+    fn parse() {
+        let p = Point { x: 0.1, y: -2.5 };
+
+        assert_eq!(p, p.to_string().parse().unwrap());
+        assert_eq!(p.string(), format!("{:.12}", p));
+        assert_eq!(p, p.string().parse().unwrap());
+        assert_eq!(Point { x: 1, y: 2 }, "(1,2)".parse::<Point<i32>>().unwrap());
+
+        let err = "(1.0 2.0)".parse::<Point>().unwrap_err();
+        assert_eq!(5, err.position);
+        assert_eq!(r1::ParseErrorKind::Expected(","), err.kind);
     }
 }
@@ -0,0 +1,389 @@
+use crate::point::*;
+use crate::predicates::*;
+use crate::rect::*;
+
+// A ring is a closed chain of points, given as a slice whose last point is
+// implicitly connected back to the first one. It may be written in either
+// orientation; its signed area is positive for a counter-clockwise ring.
+
+// ring_signed_area returns the area enclosed by the ring, positive if the ring is
+// counter-clockwise and negative if it is clockwise.
+pub fn ring_signed_area(ring: &[Point]) -> f64 {
+    let mut sum = 0.0;
+    for (a, b) in ring_edges(ring) {
+        sum += a.cross(b);
+    }
+
+    return 0.5 * sum;
+}
+
+// ring_perimeter returns the length of the ring, closing edge included.
+pub fn ring_perimeter(ring: &[Point]) -> f64 {
+    return ring_edges(ring).map(|(a, b)| b.sub(a).norm()).sum();
+}
+
+// ring_winding_number returns the number of times the ring winds
+// counter-clockwise around p, which is negative for a clockwise ring and zero for
+// a point outside of it. The result is meaningless for points on the ring.
+pub fn ring_winding_number(ring: &[Point], p: Point) -> i64 {
+    let mut winding = 0;
+    for (a, b) in ring_edges(ring) {
+        let side = orient2d(a, b, p);
+        if a.y <= p.y {
+            // An upward crossing with p on its left.
+            if b.y > p.y && side > 0.0 {
+                winding += 1;
+            }
+        } else if b.y <= p.y && side < 0.0 {
+            // A downward crossing with p on its right.
+            winding -= 1;
+        }
+    }
+
+    return winding;
+}
+
+// ring_contains_on_boundary reports whether p lies on one of the edges of the
+// ring.
+fn ring_contains_on_boundary(ring: &[Point], p: Point) -> bool {
+    for (a, b) in ring_edges(ring) {
+        if orient2d(a, b, p) == 0.0 && rect_from_points(&[a, b]).contains_point(p) {
+            return true;
+        }
+    }
+
+    return false;
+}
+
+// ring_moment returns the first moment of area of the ring, whose quotient by
+// the signed area is the centroid.
+fn ring_moment(ring: &[Point]) -> Point {
+    let mut moment = Point { x: 0.0, y: 0.0 };
+    for (a, b) in ring_edges(ring) {
+        moment = moment.add(a.add(b).mul(a.cross(b) / 6.0));
+    }
+
+    return moment;
+}
+
+fn ring_edges(ring: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    let n = ring.len();
+    return (0..n).map(move |i| (ring[i], ring[(i + 1) % n]));
+}
+
+// Polygon is a planar polygon made of an outer ring and zero or more holes. The
+// holes are expected to lie inside the outer ring and not to overlap each other.
+// The rings may be given in any orientation; normalize_orientation makes the
+// outer ring counter-clockwise and the holes clockwise.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polygon {
+    pub exterior: Vec<Point>,
+    pub holes: Vec<Vec<Point>>,
+}
+
+// polygon_from_points constructs a polygon without holes from its outer ring.
+pub fn polygon_from_points(points: &[Point]) -> Polygon {
+    Polygon {
+        exterior: points.to_vec(),
+        holes: Vec::new(),
+    }
+}
+
+impl Polygon {
+    pub fn new(exterior: Vec<Point>, holes: Vec<Vec<Point>>) -> Polygon {
+        Polygon { exterior, holes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.exterior.is_empty();
+    }
+
+    fn rings(&self) -> impl Iterator<Item = &[Point]> {
+        return std::iter::once(&self.exterior[..]).chain(self.holes.iter().map(|h| &h[..]));
+    }
+
+    // signed_area returns the sum of the signed areas of the rings. Once the
+    // orientation is normalized, it is the area of the polygon.
+    pub fn signed_area(&self) -> f64 {
+        return self.rings().map(ring_signed_area).sum();
+    }
+
+    // area returns the area of the polygon, that is the area of the outer ring
+    // minus the ones of the holes, whatever their orientation.
+    pub fn area(&self) -> f64 {
+        let holes: f64 = self.holes.iter().map(|h| ring_signed_area(h).abs()).sum();
+        return ring_signed_area(&self.exterior).abs() - holes;
+    }
+
+    // centroid returns the center of mass of the polygon. For a degenerate polygon
+    // of zero area, the average of the outer vertices is returned instead, or the
+    // origin if there are none.
+    pub fn centroid(&self) -> Point {
+        let (moment, area) = self.moment();
+        if area == 0.0 {
+            return vertex_average(&self.exterior);
+        }
+
+        return moment.mul(1.0 / area);
+    }
+
+    // moment returns the first moment of area of the polygon and its area.
+    fn moment(&self) -> (Point, f64) {
+        let mut moment = Point { x: 0.0, y: 0.0 };
+        let mut area = 0.0;
+
+        for (i, ring) in self.rings().enumerate() {
+            let mut m = ring_moment(ring);
+            let mut a = ring_signed_area(ring);
+            // Orient the outer ring positively and the holes negatively.
+            if (a < 0.0) == (i == 0) {
+                m = m.mul(-1.0);
+                a = -a;
+            }
+
+            moment = moment.add(m);
+            area += a;
+        }
+
+        return (moment, area);
+    }
+
+    // perimeter returns the total length of the rings, holes included.
+    pub fn perimeter(&self) -> f64 {
+        return self.rings().map(ring_perimeter).sum();
+    }
+
+    // is_normalized reports whether the outer ring is counter-clockwise and the
+    // holes clockwise.
+    pub fn is_normalized(&self) -> bool {
+        return ring_signed_area(&self.exterior) >= 0.0
+            && self.holes.iter().all(|h| ring_signed_area(h) <= 0.0);
+    }
+
+    // normalize_orientation reverses the rings as needed to make the outer ring
+    // counter-clockwise and the holes clockwise.
+    pub fn normalize_orientation(&mut self) {
+        if ring_signed_area(&self.exterior) < 0.0 {
+            self.exterior.reverse();
+        }
+
+        for hole in self.holes.iter_mut() {
+            if ring_signed_area(hole) > 0.0 {
+                hole.reverse();
+            }
+        }
+    }
+
+    // bound returns the bounding rectangle of the outer ring, or the empty
+    // rectangle for an empty polygon.
+    pub fn bound(&self) -> Rect {
+        if self.is_empty() {
+            return empty_rect();
+        }
+
+        return rect_from_points(&self.exterior);
+    }
+
+    // contains_point reports whether p lies inside the polygon or on its
+    // boundary, which includes the boundaries of the holes.
+    pub fn contains_point(&self, p: Point) -> bool {
+        if self.rings().any(|ring| ring_contains_on_boundary(ring, p)) {
+            return true;
+        }
+
+        return self.interior_contains_point(p);
+    }
+
+    // interior_contains_point reports whether p lies strictly inside the polygon.
+    pub fn interior_contains_point(&self, p: Point) -> bool {
+        if self.rings().any(|ring| ring_contains_on_boundary(ring, p)) {
+            return false;
+        }
+
+        return ring_winding_number(&self.exterior, p) != 0
+            && self.holes.iter().all(|h| ring_winding_number(h, p) == 0);
+    }
+}
+
+fn vertex_average(points: &[Point]) -> Point {
+    if points.is_empty() {
+        return Point { x: 0.0, y: 0.0 };
+    }
+
+    let sum: Point = points.iter().sum();
+    return sum.mul(1.0 / points.len() as f64);
+}
+
+// MultiPolygon is a collection of polygons whose interiors do not overlap.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiPolygon {
+    pub polygons: Vec<Polygon>,
+}
+
+impl MultiPolygon {
+    pub fn new(polygons: Vec<Polygon>) -> MultiPolygon {
+        MultiPolygon { polygons }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.polygons.iter().all(|p| p.is_empty());
+    }
+
+    pub fn signed_area(&self) -> f64 {
+        return self.polygons.iter().map(|p| p.signed_area()).sum();
+    }
+
+    pub fn area(&self) -> f64 {
+        return self.polygons.iter().map(|p| p.area()).sum();
+    }
+
+    // centroid returns the center of mass of the polygons, see Polygon::centroid
+    // for degenerate cases.
+    pub fn centroid(&self) -> Point {
+        let mut moment = Point { x: 0.0, y: 0.0 };
+        let mut area = 0.0;
+        for polygon in &self.polygons {
+            let (m, a) = polygon.moment();
+            moment = moment.add(m);
+            area += a;
+        }
+
+        if area == 0.0 {
+            let vertices: Vec<Point> = self
+                .polygons
+                .iter()
+                .flat_map(|p| p.exterior.iter().copied())
+                .collect();
+            return vertex_average(&vertices);
+        }
+
+        return moment.mul(1.0 / area);
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        return self.polygons.iter().map(|p| p.perimeter()).sum();
+    }
+
+    pub fn normalize_orientation(&mut self) {
+        for polygon in self.polygons.iter_mut() {
+            polygon.normalize_orientation();
+        }
+    }
+
+    pub fn bound(&self) -> Rect {
+        return self
+            .polygons
+            .iter()
+            .fold(empty_rect(), |bound, p| bound.union(p.bound()));
+    }
+
+    pub fn contains_point(&self, p: Point) -> bool {
+        return self
+            .polygons
+            .iter()
+            .any(|polygon| polygon.contains_point(p));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(coords: &[(f64, f64)]) -> Vec<Point> {
+        coords.iter().map(|&(x, y)| Point { x, y }).collect()
+    }
+
+    // A 4x4 square with a 2x2 square hole, both counter-clockwise.
+    fn square_with_hole() -> Polygon {
+        Polygon::new(
+            ring(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]),
+            vec![ring(&[(1.0, 1.0), (3.0, 1.0), (3.0, 3.0), (1.0, 3.0)])],
+        )
+    }
+
+    #[test]
+    fn measures() {
+        let mut p = square_with_hole();
+
+        assert_eq!(20.0, p.signed_area());
+        assert_eq!(12.0, p.area());
+        assert_eq!(24.0, p.perimeter());
+        assert_eq!(Point { x: 2.0, y: 2.0 }, p.centroid());
+        assert!(!p.is_normalized());
+
+        p.normalize_orientation();
+        assert!(p.is_normalized());
+        assert_eq!(12.0, p.signed_area());
+
+        let triangle = polygon_from_points(&ring(&[(0.0, 0.0), (0.0, 3.0), (3.0, 0.0)]));
+        assert_eq!(-4.5, triangle.signed_area());
+        assert_eq!(Point { x: 1.0, y: 1.0 }, triangle.centroid());
+
+        let l_shape = Polygon::new(
+            ring(&[
+                (0.0, 0.0),
+                (2.0, 0.0),
+                (2.0, 1.0),
+                (1.0, 1.0),
+                (1.0, 2.0),
+                (0.0, 2.0),
+            ]),
+            vec![],
+        );
+        assert!(l_shape.centroid().approx_equal(Point {
+            x: 5.0 / 6.0,
+            y: 5.0 / 6.0
+        }));
+        assert!(l_shape
+            .bound()
+            .approx_equal(rect_from_points(&ring(&[(0.0, 0.0), (2.0, 2.0)]))));
+        assert!(Polygon::default().bound().is_empty());
+    }
+
+    #[test]
+    fn contains_point() {
+        let p = square_with_hole();
+
+        let tests_array: [(Point, bool, bool); 6] = [
+            (Point { x: 0.5, y: 0.5 }, true, true),
+            (Point { x: 2.0, y: 2.0 }, false, false),
+            (Point { x: 5.0, y: 2.0 }, false, false),
+            (Point { x: 4.0, y: 2.0 }, true, false),
+            (Point { x: 1.0, y: 2.0 }, true, false),
+            (Point { x: 0.0, y: 0.0 }, true, false),
+        ];
+
+        for (point, contains, interior) in tests_array {
+            assert_eq!(contains, p.contains_point(point));
+            assert_eq!(interior, p.interior_contains_point(point));
+        }
+
+        let clockwise = ring(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)]);
+        assert_eq!(
+            -1,
+            ring_winding_number(&clockwise, Point { x: 0.5, y: 0.5 })
+        );
+    }
+
+    #[test]
+    fn multi_polygon() {
+        let mut m = MultiPolygon::new(vec![
+            square_with_hole(),
+            polygon_from_points(&ring(&[(10.0, 0.0), (10.0, 2.0), (12.0, 2.0), (12.0, 0.0)])),
+        ]);
+
+        assert_eq!(16.0, m.area());
+        assert_eq!(32.0, m.perimeter());
+        assert_eq!(Point { x: 4.25, y: 1.75 }, m.centroid());
+        assert!(m.contains_point(Point { x: 11.0, y: 1.0 }));
+        assert!(!m.contains_point(Point { x: 2.0, y: 2.0 }));
+        assert!(m
+            .bound()
+            .approx_equal(rect_from_points(&ring(&[(0.0, 0.0), (12.0, 4.0)]))));
+
+        m.normalize_orientation();
+        assert_eq!(16.0, m.signed_area());
+    }
+}
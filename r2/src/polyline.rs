@@ -0,0 +1,512 @@
+use crate::point::*;
+use crate::rect::*;
+use crate::segment::*;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+// Polyline is a chain of points joined by segments, such as a path or a GPS
+// trace. A polyline whose first and last points are equal is closed.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polyline {
+    pub points: Vec<Point>,
+}
+
+// polyline_from_points constructs a polyline going through the points in order.
+pub fn polyline_from_points(points: &[Point]) -> Polyline {
+    Polyline {
+        points: points.to_vec(),
+    }
+}
+
+// Simplification is the algorithm used by Polyline::simplify_with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Simplification {
+    // DouglasPeucker keeps the vertex farthest from the segment joining the
+    // ends of the polyline if it is farther than the tolerance, and goes on
+    // recursively on both sides of it. Every removed vertex lies within the
+    // tolerance of the result.
+    DouglasPeucker,
+    // VisvalingamWhyatt removes first the vertices forming the triangles of
+    // smallest area with their neighbors, as long as they lie within the
+    // tolerance of the segment joining their neighbors. It keeps the shape of
+    // the polyline better, but a removed vertex may end up farther than the
+    // tolerance from the result once its neighbors are removed too.
+    VisvalingamWhyatt,
+}
+
+// Topology tells Polyline::simplify_with whether the result may cross itself
+// where the polyline does not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    Ignore,
+    // Preserve keeps further vertices until the segments of the result only
+    // intersect where the polyline itself does.
+    Preserve,
+}
+
+impl Polyline {
+    pub fn new(points: Vec<Point>) -> Polyline {
+        Polyline { points }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.points.is_empty();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        return self.points.len() > 1 && self.points[0] == self.points[self.points.len() - 1];
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        return self.points.windows(2).map(|w| Segment { a: w[0], b: w[1] });
+    }
+
+    pub fn length(&self) -> f64 {
+        return self.segments().map(|s| s.length()).sum();
+    }
+
+    // bound returns the bounding rectangle of the points, or the empty rectangle
+    // for an empty polyline.
+    pub fn bound(&self) -> Rect {
+        if self.is_empty() {
+            return empty_rect();
+        }
+
+        return rect_from_points(&self.points);
+    }
+
+    // interpolate returns the point at the given fraction of the length of the
+    // polyline from its start. The fraction is clamped to [0, 1], so that the
+    // ends are returned exactly. An empty polyline gives the origin.
+    pub fn interpolate(&self, fraction: f64) -> Point {
+        let n = self.points.len();
+        if n == 0 {
+            return Point { x: 0.0, y: 0.0 };
+        }
+        if fraction >= 1.0 {
+            return self.points[n - 1];
+        }
+
+        let mut remaining = fraction.max(0.0) * self.length();
+        for s in self.segments() {
+            let length = s.length();
+            if remaining < length {
+                return s
+                    .bound()
+                    .clamp_point(s.a.add(s.b.sub(s.a).mul(remaining / length)));
+            }
+            remaining -= length;
+        }
+
+        return self.points[n - 1];
+    }
+
+    // project returns the point of the polyline closest to p, and its position
+    // as a fraction of the length of the polyline, such that interpolate gives
+    // it back up to rounding. The first closest point is returned in case of
+    // ties. An empty polyline gives the origin, and a polyline of zero length
+    // the fraction 0.
+    pub fn project(&self, p: Point) -> (Point, f64) {
+        if self.is_empty() {
+            return (Point { x: 0.0, y: 0.0 }, 0.0);
+        }
+
+        let mut best = (self.points[0], f64::INFINITY, 0.0);
+        let mut start = 0.0;
+        for s in self.segments() {
+            let q = s.closest_point(p);
+            let distance = p.sub(q).norm();
+            if distance < best.1 {
+                best = (q, distance, start + q.sub(s.a).norm());
+            }
+            start += s.length();
+        }
+        if best.1 == f64::INFINITY {
+            return (self.points[0], 0.0);
+        }
+
+        let length = self.length();
+        if length == 0.0 {
+            return (best.0, 0.0);
+        }
+
+        return (best.0, (best.2 / length).min(1.0));
+    }
+
+    // simplify returns the polyline with the vertices within the tolerance of
+    // the simplified shape removed, using the Douglas–Peucker algorithm. The
+    // remaining vertices are a subsequence of the original ones, ends included.
+    pub fn simplify(&self, tolerance: f64) -> Polyline {
+        return self.simplify_with(tolerance, Simplification::DouglasPeucker, Topology::Ignore);
+    }
+
+    // simplify_with returns the polyline simplified with the given algorithm,
+    // optionally without introducing self-intersections. The remaining vertices
+    // are a subsequence of the original ones, ends included.
+    pub fn simplify_with(
+        &self,
+        tolerance: f64,
+        simplification: Simplification,
+        topology: Topology,
+    ) -> Polyline {
+        if self.points.len() < 3 {
+            return self.clone();
+        }
+
+        let mut keep = match simplification {
+            Simplification::DouglasPeucker => douglas_peucker(&self.points, tolerance),
+            Simplification::VisvalingamWhyatt => visvalingam_whyatt(&self.points, tolerance),
+        };
+        if topology == Topology::Preserve {
+            preserve_topology(&self.points, &mut keep);
+        }
+
+        let points = self
+            .points
+            .iter()
+            .zip(&keep)
+            .filter(|(_, &k)| k)
+            .map(|(&p, _)| p)
+            .collect();
+        return Polyline::new(points);
+    }
+}
+
+// farthest returns the index of the point strictly between i and j farthest
+// from the segment joining them, and its distance.
+fn farthest(points: &[Point], i: usize, j: usize) -> (usize, f64) {
+    let s = Segment {
+        a: points[i],
+        b: points[j],
+    };
+
+    let mut best = (i + 1, -1.0);
+    for (k, &p) in points.iter().enumerate().take(j).skip(i + 1) {
+        let distance = s.distance_to_point(p);
+        if distance > best.1 {
+            best = (k, distance);
+        }
+    }
+
+    return best;
+}
+
+fn douglas_peucker(points: &[Point], tolerance: f64) -> Vec<bool> {
+    let n = points.len();
+    let mut keep = vec![false; n];
+    keep[0] = true;
+    keep[n - 1] = true;
+
+    let mut stack = vec![(0, n - 1)];
+    while let Some((i, j)) = stack.pop() {
+        if j <= i + 1 {
+            continue;
+        }
+
+        let (k, distance) = farthest(points, i, j);
+        if distance > tolerance {
+            keep[k] = true;
+            stack.push((i, k));
+            stack.push((k, j));
+        }
+    }
+
+    return keep;
+}
+
+// Removal is a vertex in the priority queue of visvalingam_whyatt, ordered so
+// that the smallest area comes first out of the max-heap. version tells apart
+// the entries made obsolete by the removal of a neighbor.
+struct Removal {
+    area: f64,
+    index: usize,
+    version: usize,
+}
+
+impl PartialEq for Removal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Removal {}
+
+impl PartialOrd for Removal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Removal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return other
+            .area
+            .total_cmp(&self.area)
+            .then(other.index.cmp(&self.index));
+    }
+}
+
+fn visvalingam_whyatt(points: &[Point], tolerance: f64) -> Vec<bool> {
+    let n = points.len();
+    let mut keep = vec![true; n];
+    let mut prev: Vec<usize> = (0..n).map(|i| i.wrapping_sub(1)).collect();
+    let mut next: Vec<usize> = (1..=n).collect();
+    let mut version = vec![0; n];
+
+    let area = |prev: usize, i: usize, next: usize| {
+        let (a, b, c) = (points[prev], points[i], points[next]);
+        return 0.5 * b.sub(a).cross(c.sub(a)).abs();
+    };
+
+    let mut heap: BinaryHeap<Removal> = (1..n - 1)
+        .map(|i| Removal {
+            area: area(i - 1, i, i + 1),
+            index: i,
+            version: 0,
+        })
+        .collect();
+
+    while let Some(Removal {
+        index, version: v, ..
+    }) = heap.pop()
+    {
+        if v != version[index] {
+            continue;
+        }
+
+        let (p, q) = (prev[index], next[index]);
+        let s = Segment {
+            a: points[p],
+            b: points[q],
+        };
+        if s.distance_to_point(points[index]) > tolerance {
+            continue;
+        }
+
+        keep[index] = false;
+        next[p] = q;
+        prev[q] = p;
+
+        // The neighbors form new triangles.
+        for neighbor in [p, q] {
+            if neighbor == 0 || neighbor == n - 1 {
+                continue;
+            }
+            version[neighbor] += 1;
+            heap.push(Removal {
+                area: area(prev[neighbor], neighbor, next[neighbor]),
+                index: neighbor,
+                version: version[neighbor],
+            });
+        }
+    }
+
+    return keep;
+}
+
+// preserve_topology keeps further vertices until no segment of the simplified
+// polyline replacing several original ones, a shortcut, intersects another
+// segment other than at their shared end.
+fn preserve_topology(points: &[Point], keep: &mut [bool]) {
+    let closed = points[0] == points[points.len() - 1];
+    loop {
+        let kept: Vec<usize> = (0..points.len()).filter(|&i| keep[i]).collect();
+        let segments: Vec<Segment> = kept
+            .windows(2)
+            .map(|w| Segment {
+                a: points[w[0]],
+                b: points[w[1]],
+            })
+            .collect();
+        let last = segments.len() - 1;
+
+        let mut split: Vec<usize> = Vec::new();
+        for (i, j, intersection) in segment_intersections(&segments) {
+            let shared = if j == i + 1 {
+                Some(segments[j].a)
+            } else if closed && i == 0 && j == last {
+                Some(segments[i].a)
+            } else {
+                None
+            };
+            if shared.is_some_and(|q| intersection == SegmentIntersection::Point(q)) {
+                continue;
+            }
+
+            for k in [i, j] {
+                if kept[k + 1] > kept[k] + 1 {
+                    split.push(k);
+                }
+            }
+        }
+
+        if split.is_empty() {
+            return;
+        }
+        for k in split {
+            let (index, _) = farthest(points, kept[k], kept[k + 1]);
+            keep[index] = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    fn points(coords: &[(f64, f64)]) -> Vec<Point> {
+        coords.iter().map(|&(x, y)| Point { x, y }).collect()
+    }
+
+    fn random_walk(seed: u64, count: usize) -> Polyline {
+        let mut random = random_sequence(seed);
+
+        let mut p = Point { x: 0.0, y: 0.0 };
+        let mut heading: f64 = 0.0;
+        let mut line = Vec::with_capacity(count);
+        for _ in 0..count {
+            line.push(p);
+            heading += random() - 0.5;
+            p = p.add(Point {
+                x: heading.cos(),
+                y: heading.sin(),
+            });
+        }
+
+        return Polyline::new(line);
+    }
+
+    // is_subsequence reports whether the points of the simplified polyline are
+    // some of the original ones, in order, ends included.
+    fn is_subsequence(original: &Polyline, simplified: &Polyline) -> bool {
+        let mut rest = original.points.iter();
+        return simplified.points.first() == original.points.first()
+            && simplified.points.last() == original.points.last()
+            && simplified.points.iter().all(|p| rest.any(|q| q == p));
+    }
+
+    // new_intersections returns the number of intersections between segments
+    // of the simplified polyline, other than the shared ends of consecutive
+    // segments, involving a segment that is not in the original.
+    fn new_intersections(original: &Polyline, simplified: &Polyline) -> usize {
+        let original: Vec<Segment> = original.segments().collect();
+        let segments: Vec<Segment> = simplified.segments().collect();
+        return segment_intersections(&segments)
+            .into_iter()
+            .filter(|&(i, j, intersection)| {
+                let shared =
+                    j == i + 1 && intersection == SegmentIntersection::Point(segments[j].a);
+                let shortcut = !original.contains(&segments[i]) || !original.contains(&segments[j]);
+                return !shared && shortcut;
+            })
+            .count();
+    }
+
+    #[test]
+    fn measures() {
+        let line = polyline_from_points(&points(&[(0.0, 0.0), (3.0, 0.0), (3.0, 4.0), (3.0, 4.0)]));
+        assert_eq!(7.0, line.length());
+        assert!(!line.is_closed());
+
+        let tests_array: [(f64, Point); 5] = [
+            (-1.0, Point { x: 0.0, y: 0.0 }),
+            (0.0, Point { x: 0.0, y: 0.0 }),
+            (2.0 / 7.0, Point { x: 2.0, y: 0.0 }),
+            (5.0 / 7.0, Point { x: 3.0, y: 2.0 }),
+            (1.5, Point { x: 3.0, y: 4.0 }),
+        ];
+        for (fraction, want) in tests_array {
+            assert!(line.interpolate(fraction).approx_equal(want));
+        }
+
+        let tests_array: [(Point, Point, f64); 4] = [
+            (
+                Point { x: 1.0, y: -1.0 },
+                Point { x: 1.0, y: 0.0 },
+                1.0 / 7.0,
+            ),
+            (
+                Point { x: 5.0, y: 2.0 },
+                Point { x: 3.0, y: 2.0 },
+                5.0 / 7.0,
+            ),
+            (Point { x: -2.0, y: -2.0 }, Point { x: 0.0, y: 0.0 }, 0.0),
+            (Point { x: 3.0, y: 9.0 }, Point { x: 3.0, y: 4.0 }, 1.0),
+        ];
+        for (p, want, fraction) in tests_array {
+            let (q, f) = line.project(p);
+            assert!(q.approx_equal(want));
+            assert!((f - fraction).abs() < 1e-15);
+            assert!(line.interpolate(f).approx_equal(q));
+        }
+
+        assert_eq!(
+            Point { x: 0.0, y: 0.0 },
+            Polyline::default().interpolate(0.5)
+        );
+        let point = polyline_from_points(&points(&[(1.0, 1.0)]));
+        assert_eq!(
+            (Point { x: 1.0, y: 1.0 }, 0.0),
+            point.project(Point { x: 0.0, y: 0.0 })
+        );
+    }
+
+    #[test]
+    fn simplify() {
+        let line = polyline_from_points(&points(&[
+            (0.0, 0.0),
+            (1.0, 0.1),
+            (2.0, -0.1),
+            (3.0, 5.0),
+            (4.0, 6.0),
+            (5.0, 7.0),
+            (6.0, 8.1),
+            (7.0, 9.0),
+            (8.0, 9.0),
+            (9.0, 9.0),
+        ]));
+
+        let want = points(&[(0.0, 0.0), (2.0, -0.1), (3.0, 5.0), (7.0, 9.0), (9.0, 9.0)]);
+        assert_eq!(want, line.simplify(0.5).points);
+        for topology in [Topology::Ignore, Topology::Preserve] {
+            let simplified = line.simplify_with(0.5, Simplification::VisvalingamWhyatt, topology);
+            assert_eq!(want, simplified.points);
+        }
+
+        // Only the vertices exactly on the line through their neighbors go.
+        let mut collinear = line.points.clone();
+        collinear.retain(|p| p.x != 4.0 && p.x != 8.0);
+        assert_eq!(collinear, line.simplify(0.0).points);
+        assert_eq!(2, line.simplify(100.0).points.len());
+    }
+
+    #[test]
+    fn simplify_random() {
+        let mut crossed = false;
+        for seed in 0..10 {
+            let line = random_walk(seed, 2000);
+            for simplification in [
+                Simplification::DouglasPeucker,
+                Simplification::VisvalingamWhyatt,
+            ] {
+                let loose = line.simplify_with(3.0, simplification, Topology::Ignore);
+                let preserved = line.simplify_with(3.0, simplification, Topology::Preserve);
+                assert!(is_subsequence(&line, &loose));
+                assert!(is_subsequence(&line, &preserved));
+                assert!(preserved.points.len() < line.points.len() / 4);
+                assert_eq!(0, new_intersections(&line, &preserved));
+                crossed |= new_intersections(&line, &loose) > 0;
+
+                // Douglas–Peucker keeps every point within the tolerance.
+                if simplification == Simplification::DouglasPeucker {
+                    for &p in &line.points {
+                        assert!(loose.segments().any(|s| s.distance_to_point(p) <= 3.0));
+                    }
+                }
+            }
+        }
+        assert!(crossed);
+    }
+}
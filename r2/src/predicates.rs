@@ -0,0 +1,497 @@
+use crate::point::*;
+
+// Robust geometric predicates, after Jonathan Shewchuk, "Adaptive Precision
+// Floating-Point Arithmetic and Fast Robust Geometric Predicates" (1997).
+//
+// The predicates first evaluate their determinant in plain floating-point, and
+// return it when an error bound shows that its sign is right, which is the case
+// for all but nearly degenerate inputs. Otherwise they evaluate it again with
+// more and more precision, exactly in the last resort, using expansions: sums of
+// non-overlapping doubles sorted by increasing magnitude, whose value is exact.
+// The returned value always has the sign of the exact determinant, while its
+// magnitude is only approximate.
+
+// EPSILON is half the distance from 1 to the next double, the largest relative
+// error of a rounded operation.
+const EPSILON: f64 = f64::EPSILON / 2.0;
+
+const RESULT_ERR_BOUND: f64 = (3.0 + 8.0 * EPSILON) * EPSILON;
+const CCW_ERR_BOUND_A: f64 = (3.0 + 16.0 * EPSILON) * EPSILON;
+const CCW_ERR_BOUND_B: f64 = (2.0 + 12.0 * EPSILON) * EPSILON;
+const CCW_ERR_BOUND_C: f64 = (9.0 + 64.0 * EPSILON) * EPSILON * EPSILON;
+const ICC_ERR_BOUND_A: f64 = (10.0 + 96.0 * EPSILON) * EPSILON;
+const ICC_ERR_BOUND_B: f64 = (4.0 + 48.0 * EPSILON) * EPSILON;
+
+// orient2d returns a positive value if a, b and c are in counter-clockwise
+// order, a negative value if they are in clockwise order and zero if they are
+// collinear. The sign is exact; the value approximates twice the signed area of
+// the triangle, that is b.sub(a).cross(c.sub(a)).
+pub fn orient2d(a: Point, b: Point, c: Point) -> f64 {
+    let det_left = (a.x - c.x) * (b.y - c.y);
+    let det_right = (a.y - c.y) * (b.x - c.x);
+    let det = det_left - det_right;
+
+    // The error is proportional to the sum of the magnitudes of both products,
+    // and there is none when they have opposite signs.
+    let det_sum = if det_left > 0.0 {
+        if det_right <= 0.0 {
+            return det;
+        }
+        det_left + det_right
+    } else if det_left < 0.0 {
+        if det_right >= 0.0 {
+            return det;
+        }
+        -det_left - det_right
+    } else {
+        return det;
+    };
+
+    let err_bound = CCW_ERR_BOUND_A * det_sum;
+    if det >= err_bound || -det >= err_bound {
+        return det;
+    }
+
+    return orient2d_adapt(a, b, c, det_sum);
+}
+
+// orient2d_adapt evaluates orient2d with increasing precision.
+fn orient2d_adapt(a: Point, b: Point, c: Point, det_sum: f64) -> f64 {
+    let acx = a.x - c.x;
+    let bcx = b.x - c.x;
+    let acy = a.y - c.y;
+    let bcy = b.y - c.y;
+
+    // Exact determinant of the rounded differences.
+    let (left, left_tail) = two_product(acx, bcy);
+    let (right, right_tail) = two_product(acy, bcx);
+    let b_expansion = expansion_diff(&[left_tail, left], &[right_tail, right]);
+    let mut det = estimate(&b_expansion);
+    let err_bound = CCW_ERR_BOUND_B * det_sum;
+    if det >= err_bound || -det >= err_bound {
+        return det;
+    }
+
+    let acx_tail = two_diff_tail(a.x, c.x, acx);
+    let bcx_tail = two_diff_tail(b.x, c.x, bcx);
+    let acy_tail = two_diff_tail(a.y, c.y, acy);
+    let bcy_tail = two_diff_tail(b.y, c.y, bcy);
+    if acx_tail == 0.0 && acy_tail == 0.0 && bcx_tail == 0.0 && bcy_tail == 0.0 {
+        return det;
+    }
+
+    // First order correction for the rounding of the differences.
+    let err_bound = CCW_ERR_BOUND_C * det_sum + RESULT_ERR_BOUND * det.abs();
+    det += (acx * bcy_tail + bcy * acx_tail) - (acy * bcx_tail + bcx * acy_tail);
+    if det >= err_bound || -det >= err_bound {
+        return det;
+    }
+
+    // Exact determinant of the exact differences.
+    let acx = [acx_tail, acx];
+    let bcx = [bcx_tail, bcx];
+    let acy = [acy_tail, acy];
+    let bcy = [bcy_tail, bcy];
+    let exact = expansion_diff(
+        &expansion_product(&acx, &bcy),
+        &expansion_product(&acy, &bcx),
+    );
+    return most_significant(&exact);
+}
+
+// incircle returns a positive value if d lies inside the circle passing through
+// a, b and c, a negative value if it lies outside and zero if the four points are
+// cocircular, provided that a, b and c are in counter-clockwise order; the signs
+// are reversed when they are in clockwise order. The sign is exact.
+pub fn incircle(a: Point, b: Point, c: Point, d: Point) -> f64 {
+    let adx = a.x - d.x;
+    let bdx = b.x - d.x;
+    let cdx = c.x - d.x;
+    let ady = a.y - d.y;
+    let bdy = b.y - d.y;
+    let cdy = c.y - d.y;
+
+    let bdx_cdy = bdx * cdy;
+    let cdx_bdy = cdx * bdy;
+    let a_lift = adx * adx + ady * ady;
+
+    let cdx_ady = cdx * ady;
+    let adx_cdy = adx * cdy;
+    let b_lift = bdx * bdx + bdy * bdy;
+
+    let adx_bdy = adx * bdy;
+    let bdx_ady = bdx * ady;
+    let c_lift = cdx * cdx + cdy * cdy;
+
+    let det =
+        a_lift * (bdx_cdy - cdx_bdy) + b_lift * (cdx_ady - adx_cdy) + c_lift * (adx_bdy - bdx_ady);
+
+    let permanent = (bdx_cdy.abs() + cdx_bdy.abs()) * a_lift
+        + (cdx_ady.abs() + adx_cdy.abs()) * b_lift
+        + (adx_bdy.abs() + bdx_ady.abs()) * c_lift;
+    let err_bound = ICC_ERR_BOUND_A * permanent;
+    if det > err_bound || -det > err_bound {
+        return det;
+    }
+
+    return incircle_adapt(a, b, c, d, permanent);
+}
+
+// incircle_adapt evaluates incircle with increasing precision.
+fn incircle_adapt(a: Point, b: Point, c: Point, d: Point, permanent: f64) -> f64 {
+    let adx = a.x - d.x;
+    let bdx = b.x - d.x;
+    let cdx = c.x - d.x;
+    let ady = a.y - d.y;
+    let bdy = b.y - d.y;
+    let cdy = c.y - d.y;
+
+    // Exact determinant of the rounded differences.
+    let rounded = incircle_expansion([[&[adx], &[ady]], [&[bdx], &[bdy]], [&[cdx], &[cdy]]]);
+    let det = estimate(&rounded);
+    let err_bound = ICC_ERR_BOUND_B * permanent;
+    if det >= err_bound || -det >= err_bound {
+        return det;
+    }
+
+    let adx_tail = two_diff_tail(a.x, d.x, adx);
+    let bdx_tail = two_diff_tail(b.x, d.x, bdx);
+    let cdx_tail = two_diff_tail(c.x, d.x, cdx);
+    let ady_tail = two_diff_tail(a.y, d.y, ady);
+    let bdy_tail = two_diff_tail(b.y, d.y, bdy);
+    let cdy_tail = two_diff_tail(c.y, d.y, cdy);
+    if [adx_tail, bdx_tail, cdx_tail, ady_tail, bdy_tail, cdy_tail]
+        .iter()
+        .all(|&t| t == 0.0)
+    {
+        return most_significant(&rounded);
+    }
+
+    // Exact determinant of the exact differences.
+    let exact = incircle_expansion([
+        [&[adx_tail, adx], &[ady_tail, ady]],
+        [&[bdx_tail, bdx], &[bdy_tail, bdy]],
+        [&[cdx_tail, cdx], &[cdy_tail, cdy]],
+    ]);
+    return most_significant(&exact);
+}
+
+// incircle_expansion returns the incircle determinant of three points given by
+// their coordinates relative to the fourth one, as expansions.
+fn incircle_expansion(p: [[&[f64]; 2]; 3]) -> Vec<f64> {
+    let lift = |[x, y]: [&[f64]; 2]| {
+        return expansion_sum(&expansion_product(x, x), &expansion_product(y, y));
+    };
+    let cross = |[x0, y0]: [&[f64]; 2], [x1, y1]: [&[f64]; 2]| {
+        return expansion_diff(&expansion_product(x0, y1), &expansion_product(y0, x1));
+    };
+
+    let a = expansion_product(&lift(p[0]), &cross(p[1], p[2]));
+    let b = expansion_product(&lift(p[1]), &cross(p[2], p[0]));
+    let c = expansion_product(&lift(p[2]), &cross(p[0], p[1]));
+    return expansion_sum(&expansion_sum(&a, &b), &c);
+}
+
+// two_sum returns the rounded sum of a and b and its rounding error.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+    return (x, (a - a_virtual) + (b - b_virtual));
+}
+
+// fast_two_sum is two_sum for |a| >= |b|.
+fn fast_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    return (x, b - (x - a));
+}
+
+// two_diff_tail returns the rounding error of x, the rounded difference a - b.
+fn two_diff_tail(a: f64, b: f64, x: f64) -> f64 {
+    let b_virtual = a - x;
+    let a_virtual = x + b_virtual;
+    return (a - a_virtual) + (b_virtual - b);
+}
+
+// two_product returns the rounded product of a and b and its rounding error,
+// which a fused multiply-add computes exactly.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    return (x, a.mul_add(b, -x));
+}
+
+// grow_expansion returns the expansion of e + b, without zero components.
+fn grow_expansion(e: &[f64], b: f64) -> Vec<f64> {
+    let mut h = Vec::with_capacity(e.len() + 1);
+    let mut q = b;
+    for &component in e {
+        let (sum, error) = two_sum(q, component);
+        if error != 0.0 {
+            h.push(error);
+        }
+        q = sum;
+    }
+    if q != 0.0 || h.is_empty() {
+        h.push(q);
+    }
+
+    return h;
+}
+
+fn expansion_sum(e: &[f64], f: &[f64]) -> Vec<f64> {
+    let mut h = e.to_vec();
+    for &component in f {
+        h = grow_expansion(&h, component);
+    }
+
+    return h;
+}
+
+fn expansion_diff(e: &[f64], f: &[f64]) -> Vec<f64> {
+    let negated: Vec<f64> = f.iter().map(|&c| -c).collect();
+    return expansion_sum(e, &negated);
+}
+
+// scale_expansion returns the expansion of e * b, without zero components.
+fn scale_expansion(e: &[f64], b: f64) -> Vec<f64> {
+    let mut h = Vec::with_capacity(2 * e.len());
+    let (mut q, error) = two_product(e[0], b);
+    if error != 0.0 {
+        h.push(error);
+    }
+    for &component in &e[1..] {
+        let (product, product_error) = two_product(component, b);
+        let (sum, error) = two_sum(q, product_error);
+        if error != 0.0 {
+            h.push(error);
+        }
+        let (next, error) = fast_two_sum(product, sum);
+        if error != 0.0 {
+            h.push(error);
+        }
+        q = next;
+    }
+    if q != 0.0 || h.is_empty() {
+        h.push(q);
+    }
+
+    return h;
+}
+
+fn expansion_product(e: &[f64], f: &[f64]) -> Vec<f64> {
+    let mut h = vec![0.0];
+    for &component in f {
+        h = expansion_sum(&h, &scale_expansion(e, component));
+    }
+
+    return h;
+}
+
+// estimate returns the value of the expansion, rounded.
+fn estimate(e: &[f64]) -> f64 {
+    return e.iter().sum();
+}
+
+// most_significant returns the largest component of the expansion, which has the
+// sign of its value.
+fn most_significant(e: &[f64]) -> f64 {
+    return e[e.len() - 1];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    // SCALE turns the coordinates used below, multiples of 2^-53 in [0.5, 32),
+    // into integers for exact reference computations.
+    const SCALE: f64 = (1u64 << 53) as f64;
+
+    fn exact(x: f64) -> i128 {
+        let scaled = x * SCALE;
+        assert_eq!(scaled, scaled.trunc());
+        return scaled as i128;
+    }
+
+    fn orient2d_exact(a: Point, b: Point, c: Point) -> i128 {
+        let (ax, ay, bx, by, cx, cy) = (
+            exact(a.x),
+            exact(a.y),
+            exact(b.x),
+            exact(b.y),
+            exact(c.x),
+            exact(c.y),
+        );
+        return (ax - cx) * (by - cy) - (ay - cy) * (bx - cx);
+    }
+
+    fn sign(x: f64) -> i128 {
+        if x > 0.0 {
+            return 1;
+        }
+        if x < 0.0 {
+            return -1;
+        }
+        return 0;
+    }
+
+    fn point(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    #[test]
+    fn orient2d_signs() {
+        let tests_array: [(Point, Point, Point, i128); 4] = [
+            (point(0.0, 0.0), point(1.0, 0.0), point(0.0, 1.0), 1),
+            (point(0.0, 0.0), point(0.0, 1.0), point(1.0, 0.0), -1),
+            (point(0.0, 0.0), point(1.0, 1.0), point(3.0, 3.0), 0),
+            (point(0.1, 0.1), point(0.2, 0.2), point(0.1, 0.1), 0),
+        ];
+
+        for (a, b, c, want) in tests_array {
+            assert_eq!(want, sign(orient2d(a, b, c)));
+            assert_eq!(-want, sign(orient2d(b, a, c)));
+        }
+    }
+
+    #[test]
+    fn orient2d_near_collinear() {
+        // A grid of points around the line y = x, one unit in the last place apart
+        // near 0.5, tested against the segment from 12 to 24: the classic example
+        // where the sign of plain floating-point evaluation is wrong at random.
+        let ulp = EPSILON;
+        let b = point(12.0, 12.0);
+        let c = point(24.0, 24.0);
+        let mut wrong_naive = 0;
+        for i in 0..64 {
+            for j in 0..64 {
+                let a = point(0.5 + i as f64 * ulp, 0.5 + j as f64 * ulp);
+                let want = orient2d_exact(a, b, c).signum();
+                assert_eq!(want, sign(orient2d(a, b, c)));
+                if sign(b.sub(a).cross(c.sub(a))) != want {
+                    wrong_naive += 1;
+                }
+            }
+        }
+        assert!(wrong_naive > 0);
+    }
+
+    #[test]
+    fn orient2d_random() {
+        let mut random = random_bits(11);
+
+        // Points close to the line through two random points, rounded to
+        // multiples of 2^-53 in [0.5, 32).
+        let snap = |x: f64| (x * SCALE).round() / SCALE;
+        for _ in 0..2000 {
+            let mut coordinate = || 0.5 + (random() % 31_000_000) as f64 / 1_000_000.0;
+            let a = point(snap(coordinate()), snap(coordinate()));
+            let b = point(snap(coordinate()), snap(coordinate()));
+            let t = (random() % 1000) as f64 / 1000.0;
+            let mut c = a.add(b.sub(a).mul(t));
+            let offset = (random() % 9) as f64 - 4.0;
+            c.y += offset * 2.0 * EPSILON * c.y.abs();
+            let c = point(snap(c.x), snap(c.y));
+
+            assert_eq!(orient2d_exact(a, b, c).signum(), sign(orient2d(a, b, c)));
+        }
+    }
+
+    #[test]
+    fn incircle_signs() {
+        let (a, b, c) = (point(0.0, 0.0), point(1.0, 0.0), point(1.0, 1.0));
+
+        let tests_array: [(Point, i128); 5] = [
+            (point(0.5, 0.5), 1),
+            (point(2.0, 2.0), -1),
+            (point(0.0, 1.0), 0),
+            // One unit in the last place inside or outside of the circle.
+            (point(0.0, 1.0 - EPSILON), 1),
+            (point(0.0, 1.0 + 2.0 * EPSILON), -1),
+        ];
+
+        for (d, want) in tests_array {
+            assert_eq!(want, sign(incircle(a, b, c, d)));
+            assert_eq!(-want, sign(incircle(a, c, b, d)));
+        }
+
+        // Cocircular points far from the origin, where the lifted coordinates are
+        // rounded, nudged across the circle by one unit in the last place.
+        let offset = point(1e6 + 0.25, -3e5 - 0.5);
+        let (a, b, c) = (a.add(offset), b.add(offset), c.add(offset));
+        let d = point(0.0, 1.0).add(offset);
+        assert_eq!(0.0, incircle(a, b, c, d));
+        let ulp = 2.0 * EPSILON * d.x;
+        assert!(incircle(a, b, c, point(d.x + ulp, d.y)) > 0.0);
+        assert!(incircle(a, b, c, point(d.x - ulp, d.y)) < 0.0);
+    }
+
+    #[test]
+    fn incircle_near_cocircular() {
+        let mut random = random_bits(5);
+
+        // Points of the circle of radius 5 with integer coordinates, scaled and
+        // moved to a random center on a grid of step 2^-30, the fourth one then
+        // moved by up to two steps. The exact determinant fits in an i128 once
+        // the coordinates are turned into integers.
+        let grid = (1u64 << 30) as f64;
+        let lattice = [
+            (3.0, 4.0),
+            (4.0, 3.0),
+            (5.0, 0.0),
+            (0.0, 5.0),
+            (-3.0, 4.0),
+            (-4.0, -3.0),
+            (-5.0, 0.0),
+            (0.0, -5.0),
+            (3.0, -4.0),
+            (-3.0, -4.0),
+        ];
+        let mut wrong_naive = 0;
+        for _ in 0..20000 {
+            let cx = (random() % (40 << 30)) as f64 / grid + 12.0;
+            let cy = (random() % (40 << 30)) as f64 / grid + 12.0;
+            let scale = 2f64.powi(-3 - (random() % 24) as i32);
+            let mut vertex = || {
+                let (x, y) = lattice[(random() % 10) as usize];
+                return point(cx + x * scale, cy + y * scale);
+            };
+            let (a, b, c, mut d) = (vertex(), vertex(), vertex(), vertex());
+            d.x += ((random() % 5) as f64 - 2.0) / grid;
+            d.y += ((random() % 5) as f64 - 2.0) / grid;
+
+            let p: Vec<(i128, i128)> = [a, b, c]
+                .iter()
+                .map(|v| (((v.x - d.x) * grid) as i128, ((v.y - d.y) * grid) as i128))
+                .collect();
+            let lift = |(x, y): (i128, i128)| x * x + y * y;
+            let cross = |(x0, y0): (i128, i128), (x1, y1): (i128, i128)| x0 * y1 - y0 * x1;
+            let want = (lift(p[0]) * cross(p[1], p[2])
+                + lift(p[1]) * cross(p[2], p[0])
+                + lift(p[2]) * cross(p[0], p[1]))
+            .signum();
+            assert_eq!(want, sign(incircle(a, b, c, d)));
+
+            let naive = |a: Point, b: Point, c: Point| {
+                let (a, b, c) = (a.sub(d), b.sub(d), c.sub(d));
+                return a.dot(a) * b.cross(c) + b.dot(b) * c.cross(a) + c.dot(c) * a.cross(b);
+            };
+            if sign(naive(a, b, c)) != want {
+                wrong_naive += 1;
+            }
+        }
+        assert!(wrong_naive > 0);
+    }
+
+    #[test]
+    fn expansions() {
+        // (1 + 2^-60)^2 = 1 + 2^-59 + 2^-120.
+        let e = grow_expansion(&[2f64.powi(-60)], 1.0);
+        assert_eq!(vec![2f64.powi(-60), 1.0], e);
+        assert_eq!(
+            vec![2f64.powi(-120), 2f64.powi(-59), 1.0],
+            expansion_product(&e, &e)
+        );
+        assert_eq!(vec![0.0], expansion_diff(&e, &e));
+    }
+}
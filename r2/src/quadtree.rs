@@ -0,0 +1,383 @@
+use crate::point::*;
+use crate::rect::*;
+
+// MAX_DEPTH bounds the depth of the tree, so that many points at the same
+// position do not split quadrants forever. Leaves at that depth hold any number
+// of points.
+const MAX_DEPTH: usize = 32;
+
+// QuadTree stores points along with a payload, inside of a fixed rectangular
+// domain. A quadrant holding more points than the capacity is split into four
+// equal quadrants, and quadrants whose points fit again in a single one are
+// merged back on removal. Unlike the RTree, whose nodes adapt to the data, the
+// shape of the tree only depends on the points it holds, which makes insertions
+// and removals cheap and local, at the cost of a deeper tree on clustered data.
+pub struct QuadTree<V> {
+    root: Quad<V>,
+    capacity: usize,
+    len: usize,
+}
+
+struct Quad<V> {
+    bound: Rect,
+    kind: QuadKind<V>,
+}
+
+enum QuadKind<V> {
+    Leaf(Vec<(Point, V)>),
+    // The children are indexed by 1 for the upper half in x plus 2 for the upper
+    // half in y, see Quad::child_index.
+    Internal(Box<[Quad<V>; 4]>, usize),
+}
+
+impl<V> QuadTree<V> {
+    // new returns an empty tree over the given domain, whose quadrants hold at
+    // most capacity points before being split. A capacity of zero is taken as
+    // one.
+    pub fn new(bound: Rect, capacity: usize) -> QuadTree<V> {
+        return QuadTree {
+            root: Quad {
+                bound,
+                kind: QuadKind::Leaf(Vec::new()),
+            },
+            capacity: capacity.max(1),
+            len: 0,
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.len;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len == 0;
+    }
+
+    // bound returns the domain of the tree.
+    pub fn bound(&self) -> Rect {
+        return self.root.bound;
+    }
+
+    // insert adds the point with its payload. It gives the payload back as an
+    // error if the point lies outside of the domain, as reported by
+    // Rect::contains_point.
+    pub fn insert(&mut self, p: Point, value: V) -> Result<(), V> {
+        if !self.root.bound.contains_point(p) {
+            return Err(value);
+        }
+
+        self.root.insert(p, value, self.capacity, 0);
+        self.len += 1;
+        return Ok(());
+    }
+
+    // remove deletes one entry at exactly the given point and returns its
+    // payload, or None if there is no such entry.
+    pub fn remove(&mut self, p: Point) -> Option<V> {
+        return self.remove_where(p, &|_| true);
+    }
+
+    // remove_entry deletes one entry holding exactly the given point and value,
+    // and returns its payload. Use it rather than remove when several entries
+    // may share the same point.
+    pub fn remove_entry(&mut self, p: Point, value: &V) -> Option<V>
+    where
+        V: PartialEq,
+    {
+        return self.remove_where(p, &|v| v == value);
+    }
+
+    fn remove_where(&mut self, p: Point, matches: &dyn Fn(&V) -> bool) -> Option<V> {
+        if !self.root.bound.contains_point(p) {
+            return None;
+        }
+
+        let removed = self.root.remove(p, matches, self.capacity)?;
+        self.len -= 1;
+        return Some(removed);
+    }
+
+    // contained_in returns the entries whose point lies inside the window, as
+    // reported by Rect::contains_point.
+    pub fn contained_in(&self, window: Rect) -> Vec<(Point, &V)> {
+        let mut result = Vec::new();
+        self.root.query(
+            &|b| b.intersects(window),
+            &|p| window.contains_point(p),
+            &mut result,
+        );
+        return result;
+    }
+
+    // within_distance returns the entries whose point lies at most the radius
+    // away from the center.
+    pub fn within_distance(&self, center: Point, radius: f64) -> Vec<(Point, &V)> {
+        let mut result = Vec::new();
+        self.root.query(
+            &|b| b.distance_to_point(center) <= radius,
+            &|p| p.sub(center).norm() <= radius,
+            &mut result,
+        );
+        return result;
+    }
+
+    // entries returns all entries, in no particular order.
+    pub fn entries(&self) -> Vec<(Point, &V)> {
+        let mut result = Vec::new();
+        self.root.query(&|_| true, &|_| true, &mut result);
+        return result;
+    }
+}
+
+impl<V> Quad<V> {
+    fn len(&self) -> usize {
+        return match &self.kind {
+            QuadKind::Leaf(entries) => entries.len(),
+            QuadKind::Internal(_, len) => *len,
+        };
+    }
+
+    // child_index returns the index of the child quadrant holding p. Points on
+    // the lines through the center go to the upper quadrants.
+    fn child_index(&self, p: Point) -> usize {
+        let center = self.bound.center();
+        return (p.x >= center.x) as usize + 2 * (p.y >= center.y) as usize;
+    }
+
+    fn insert(&mut self, p: Point, value: V, capacity: usize, depth: usize) {
+        let i = self.child_index(p);
+        match &mut self.kind {
+            QuadKind::Leaf(entries) => {
+                entries.push((p, value));
+                if entries.len() > capacity && depth < MAX_DEPTH {
+                    self.split(capacity, depth);
+                }
+            }
+            QuadKind::Internal(children, len) => {
+                *len += 1;
+                children[i].insert(p, value, capacity, depth + 1);
+            }
+        }
+    }
+
+    // split turns the leaf into an internal node with four children, and spreads
+    // its entries among them.
+    fn split(&mut self, capacity: usize, depth: usize) {
+        let (lo, center, hi) = (self.bound.lo(), self.bound.center(), self.bound.hi());
+        let quadrant = |i: usize| {
+            let (x0, x1) = if i & 1 == 0 {
+                (lo.x, center.x)
+            } else {
+                (center.x, hi.x)
+            };
+            let (y0, y1) = if i & 2 == 0 {
+                (lo.y, center.y)
+            } else {
+                (center.y, hi.y)
+            };
+            return Quad {
+                bound: rect_from_points(&[Point { x: x0, y: y0 }, Point { x: x1, y: y1 }]),
+                kind: QuadKind::Leaf(Vec::new()),
+            };
+        };
+
+        let mut children = Box::new([quadrant(0), quadrant(1), quadrant(2), quadrant(3)]);
+        let kind = std::mem::replace(&mut self.kind, QuadKind::Leaf(Vec::new()));
+        let QuadKind::Leaf(entries) = kind else {
+            unreachable!("only leaves are split");
+        };
+
+        let len = entries.len();
+        for (p, value) in entries {
+            children[self.child_index(p)].insert(p, value, capacity, depth + 1);
+        }
+        self.kind = QuadKind::Internal(children, len);
+    }
+
+    fn remove(&mut self, p: Point, matches: &dyn Fn(&V) -> bool, capacity: usize) -> Option<V> {
+        let i = self.child_index(p);
+        let removed = match &mut self.kind {
+            QuadKind::Leaf(entries) => {
+                let index = entries.iter().position(|(q, v)| *q == p && matches(v))?;
+                return Some(entries.swap_remove(index).1);
+            }
+            QuadKind::Internal(children, len) => {
+                let removed = children[i].remove(p, matches, capacity)?;
+                *len -= 1;
+                removed
+            }
+        };
+
+        // Merge the children back once their entries fit in a single leaf.
+        if self.len() <= capacity {
+            self.merge();
+        }
+
+        return Some(removed);
+    }
+
+    // merge turns the node into a leaf holding all the entries of its subtree.
+    fn merge(&mut self) {
+        let mut entries = Vec::with_capacity(self.len());
+        let kind = std::mem::replace(&mut self.kind, QuadKind::Leaf(Vec::new()));
+        collect_entries(kind, &mut entries);
+        self.kind = QuadKind::Leaf(entries);
+    }
+
+    // query appends to result the entries of the quadrants whose bound matches,
+    // and whose point matches.
+    fn query<'a>(
+        &'a self,
+        bound_matches: &dyn Fn(Rect) -> bool,
+        point_matches: &dyn Fn(Point) -> bool,
+        result: &mut Vec<(Point, &'a V)>,
+    ) {
+        if !bound_matches(self.bound) {
+            return;
+        }
+
+        match &self.kind {
+            QuadKind::Leaf(entries) => {
+                for (p, v) in entries {
+                    if point_matches(*p) {
+                        result.push((*p, v));
+                    }
+                }
+            }
+            QuadKind::Internal(children, _) => {
+                for child in children.iter() {
+                    child.query(bound_matches, point_matches, result);
+                }
+            }
+        }
+    }
+}
+
+fn collect_entries<V>(kind: QuadKind<V>, entries: &mut Vec<(Point, V)>) {
+    match kind {
+        QuadKind::Leaf(mut leaf) => entries.append(&mut leaf),
+        QuadKind::Internal(children, _) => {
+            let children: [Quad<V>; 4] = *children;
+            for child in children {
+                collect_entries(child.kind, entries);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    fn sorted(entries: Vec<(Point, &usize)>) -> Vec<usize> {
+        let mut values: Vec<usize> = entries.into_iter().map(|(_, &v)| v).collect();
+        values.sort_unstable();
+        return values;
+    }
+
+    // points returns a deterministic pseudo-random list of points in [0, 100]²,
+    // half of them in a small cluster and some of them repeated.
+    fn points(n: usize) -> Vec<Point> {
+        let mut random = random_sequence(2);
+
+        let mut points: Vec<Point> = (0..n)
+            .map(|i| {
+                let scale = if i % 2 == 0 { 100.0 } else { 1e-3 };
+                Point {
+                    x: 50.0 + scale * (random() - 0.5),
+                    y: 50.0 + scale * (random() - 0.5),
+                }
+            })
+            .collect();
+        for i in (0..n).step_by(10) {
+            points[i] = points[0];
+        }
+
+        return points;
+    }
+
+    fn check_queries(tree: &QuadTree<usize>, list: &[Point], alive: &[bool]) {
+        let alive_and = |pred: &dyn Fn(Point) -> bool| {
+            (0..list.len())
+                .filter(|&i| alive[i] && pred(list[i]))
+                .collect::<Vec<_>>()
+        };
+
+        let windows = [
+            rect_from_points(&[Point { x: 20.0, y: 30.0 }, Point { x: 45.0, y: 50.0 }]),
+            rect_from_points(&[Point { x: 49.9, y: 49.9 }, Point { x: 50.0, y: 50.0 }]),
+            rect_from_points(&[list[0], list[0]]),
+        ];
+        for window in windows {
+            assert_eq!(
+                alive_and(&|p| window.contains_point(p)),
+                sorted(tree.contained_in(window))
+            );
+        }
+
+        for (center, radius) in [
+            (list[1], 1e-4),
+            (Point { x: 30.0, y: 70.0 }, 15.0),
+            (list[0], 0.0),
+        ] {
+            assert_eq!(
+                alive_and(&|p| p.sub(center).norm() <= radius),
+                sorted(tree.within_distance(center, radius))
+            );
+        }
+        assert_eq!(alive_and(&|_| true), sorted(tree.entries()));
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let bound = rect_from_points(&[Point { x: 0.0, y: 0.0 }, Point { x: 100.0, y: 100.0 }]);
+        let list = points(2000);
+        let mut tree = QuadTree::new(bound, 8);
+        for (i, &p) in list.iter().enumerate() {
+            assert_eq!(Ok(()), tree.insert(p, i));
+        }
+        assert_eq!(Err(7), tree.insert(Point { x: 100.5, y: 0.0 }, 7));
+        assert_eq!(2000, tree.len());
+
+        let mut alive = vec![true; list.len()];
+        check_queries(&tree, &list, &alive);
+
+        for i in (0..list.len()).step_by(3) {
+            assert_eq!(Some(i), tree.remove_entry(list[i], &i));
+            alive[i] = false;
+        }
+        assert_eq!(None, tree.remove_entry(list[0], &0));
+        assert_eq!(None, tree.remove(Point { x: -1.0, y: 0.0 }));
+        assert_eq!(1333, tree.len());
+        check_queries(&tree, &list, &alive);
+
+        // Move the remaining points, as a simulation would.
+        for i in 0..list.len() {
+            if alive[i] {
+                assert_eq!(Some(i), tree.remove_entry(list[i], &i));
+                let moved = Point {
+                    x: 100.0 - list[i].x,
+                    y: list[i].y,
+                };
+                assert_eq!(Ok(()), tree.insert(moved, i));
+            }
+        }
+        let moved: Vec<Point> = list
+            .iter()
+            .map(|p| Point {
+                x: 100.0 - p.x,
+                y: p.y,
+            })
+            .collect();
+        check_queries(&tree, &moved, &alive);
+
+        for i in 0..list.len() {
+            if alive[i] {
+                assert!(tree.remove(moved[i]).is_some());
+            }
+        }
+        assert!(tree.is_empty());
+        assert!(matches!(tree.root.kind, QuadKind::Leaf(ref entries) if entries.is_empty()));
+    }
+}
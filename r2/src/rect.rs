@@ -1,19 +1,27 @@
-use crate::point::Point;
+use crate::point::{parse_point, Point};
+use r1::__private::Scanner;
 use r1::*;
+use std::fmt;
+use std::str::FromStr;
 
 extern crate r1;
 
 #[derive(Debug, Clone, Copy)]
-pub struct Rect {
-    pub x: r1::Interval,
-    pub y: r1::Interval,
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(deserialize = "T: serde::Deserialize<'de>"))
+)]
+pub struct Rect<T = f64> {
+    pub x: r1::Interval<T>,
+    pub y: r1::Interval<T>,
 }
 
-pub fn rect_from_points(points: &[Point]) -> Rect {
-    if points.len() == 0 {
+pub fn rect_from_points<T: Scalar>(points: &[Point<T>]) -> Rect<T> {
+    if points.is_empty() {
         return Rect {
-            x: Interval { lo: 0.0, hi: 0.0 },
-            y: Interval { lo: 0.0, hi: 0.0 },
+            x: Interval::from_point(T::zero()),
+            y: Interval::from_point(T::zero()),
         };
     }
 
@@ -37,14 +45,15 @@ pub fn rect_from_points(points: &[Point]) -> Rect {
 
 // rect_from_center_size(constructs a rectangle with the given center and size.
 // Both dimensions of size must be non-negative.
-pub fn rect_from_center_size(center: Point, size: Point) -> Rect {
+pub fn rect_from_center_size<T: Scalar>(center: Point<T>, size: Point<T>) -> Rect<T> {
+    let two = T::one() + T::one();
     let ix = Interval {
-        lo: center.x - size.x / 2.0,
-        hi: center.x + size.x / 2.0,
+        lo: center.x - size.x / two,
+        hi: center.x + size.x / two,
     };
     let iy = Interval {
-        lo: center.y - size.y / 2.0,
-        hi: center.y + size.y / 2.0,
+        lo: center.y - size.y / two,
+        hi: center.y + size.y / two,
     };
     return Rect { x: ix, y: iy };
 }
@@ -59,14 +68,28 @@ pub fn empty_rect() -> Rect {
     };
 }
 
-impl Rect {
+impl<T: Scalar> Rect<T> {
+    // empty returns the canonical empty rectangle of the given scalar type, the
+    // generic counterpart of empty_rect.
+    pub fn empty() -> Rect<T> {
+        return Rect {
+            x: Interval::empty(),
+            y: Interval::empty(),
+        };
+    }
+
     pub fn is_valid(self) -> bool {
         return self.x.is_empty() == self.y.is_empty();
     }
 
+    // is_empty reports whether the rectangle contains no point.
+    pub fn is_empty(self) -> bool {
+        return self.x.is_empty() || self.y.is_empty();
+    }
+
     // vertices returns all four vertices of the rectangle. Vertices are returned in
     // CCW direction starting with the lower left corner.
-    pub fn vertices(self) -> [Point; 4] {
+    pub fn vertices(self) -> [Point<T>; 4] {
         [
             Point {
                 x: self.x.lo,
@@ -87,7 +110,7 @@ impl Rect {
         ]
     }
 
-    pub fn vertex_i_j(self, i: i64, j: i64) -> Point {
+    pub fn vertex_i_j(self, i: i64, j: i64) -> Point<T> {
         let mut x = self.x.lo;
         if i == 1 {
             x = self.x.hi
@@ -101,70 +124,79 @@ impl Rect {
         return Point { x, y };
     }
 
-    pub fn lo(self) -> Point {
+    pub fn lo(self) -> Point<T> {
         Point {
             x: self.x.lo,
             y: self.y.lo,
         }
     }
 
-    pub fn hi(self) -> Point {
+    pub fn hi(self) -> Point<T> {
         Point {
             x: self.x.hi,
             y: self.y.hi,
         }
     }
 
-    pub fn center(self) -> Point {
+    pub fn center(self) -> Point<T> {
         Point {
             x: self.x.center(),
             y: self.y.center(),
         }
     }
 
-    pub fn size(self) -> Point {
+    pub fn size(self) -> Point<T> {
         Point {
             x: self.x.length(),
             y: self.y.length(),
         }
     }
 
+    // area returns the area of the rectangle, 0 for an empty one.
+    pub fn area(self) -> T {
+        if self.is_empty() {
+            return T::zero();
+        }
+
+        return self.x.length() * self.y.length();
+    }
+
     // contains_point reports whether the rectangle contains the given point.
     // Rectangles are closed regions, i.e. they contain their boundary.
-    pub fn contains_point(self, p: Point) -> bool {
+    pub fn contains_point(self, p: Point<T>) -> bool {
         return self.x.contains(p.x) && self.y.contains(p.y);
     }
 
     // interior_contains_point returns true iff the given point is contained in the interior
     // of the region (i.e. the region excluding its boundary).
-    pub fn interior_contains_point(self, p: Point) -> bool {
+    pub fn interior_contains_point(self, p: Point<T>) -> bool {
         return self.x.interior_contains(p.x) && self.y.interior_contains(p.y);
     }
 
-    pub fn contains(self, r: Rect) -> bool {
+    pub fn contains(self, r: Rect<T>) -> bool {
         return self.x.contains_interval(r.x) && self.y.contains_interval(r.y);
     }
 
-    pub fn interior_contains(self, r: Rect) -> bool {
+    pub fn interior_contains(self, r: Rect<T>) -> bool {
         return self.x.interior_contains_interval(r.x) && self.y.interior_contains_interval(r.y);
     }
 
-    pub fn intersects(self, r: Rect) -> bool {
+    pub fn intersects(self, r: Rect<T>) -> bool {
         return self.x.intersects(r.x) && self.y.intersects(r.y);
     }
 
-    pub fn interior_intersects(self, r: Rect) -> bool {
+    pub fn interior_intersects(self, r: Rect<T>) -> bool {
         return self.x.interior_intersects(r.x) && self.y.interior_intersects(r.y);
     }
 
-    pub fn add_point(self, p: Point) -> Rect {
+    pub fn add_point(self, p: Point<T>) -> Rect<T> {
         return Rect {
             x: self.x.add_point(p.x),
             y: self.y.add_point(p.y),
         };
     }
 
-    pub fn add_rect(self, r: Rect) -> Rect {
+    pub fn add_rect(self, r: Rect<T>) -> Rect<T> {
         return Rect {
             x: self.x.union(r.x),
             y: self.y.union(r.y),
@@ -173,7 +205,7 @@ impl Rect {
 
     // clamp_point returns the closest point in the rectangle to the given point.
     // The rectangle must be non-empty.
-    pub fn clamp_point(self, p: Point) -> Point {
+    pub fn clamp_point(self, p: Point<T>) -> Point<T> {
         return Point {
             x: self.x.clamp_point(p.x),
             y: self.y.clamp_point(p.y),
@@ -184,19 +216,19 @@ impl Rect {
     // by margin.X, and in y-direction by margin.Y. If either margin is empty,
     // then shrink the interval on the corresponding sides instead. The resulting
     // rectangle may be empty. Any expansion of an empty rectangle remains empty.
-    pub fn expanded(self, margin: Point) -> Rect {
-        let xx: Interval = self.x.expanded(margin.x);
-        let yy: Interval = self.y.expanded(margin.y);
+    pub fn expanded(self, margin: Point<T>) -> Rect<T> {
+        let xx: Interval<T> = self.x.expanded(margin.x);
+        let yy: Interval<T> = self.y.expanded(margin.y);
 
         if xx.is_empty() || yy.is_empty() {
-            return empty_rect();
+            return Rect::empty();
         }
 
         return Rect { x: xx, y: yy };
     }
 
     // expanded_by_margin returns a Rect that has been expanded by the amount on all sides.
-    pub fn expanded_by_margin(self, margin: f64) -> Rect {
+    pub fn expanded_by_margin(self, margin: T) -> Rect<T> {
         return self.expanded(Point {
             x: margin,
             y: margin,
@@ -205,7 +237,7 @@ impl Rect {
 
     // union returns the smallest rectangle containing the union of this rectangle and
     // the given rectangle.
-    pub fn union(self, r: Rect) -> Rect {
+    pub fn union(self, r: Rect<T>) -> Rect<T> {
         return Rect {
             x: self.x.union(r.x),
             y: self.y.union(r.y),
@@ -214,24 +246,316 @@ impl Rect {
 
     // Intersection returns the smallest rectangle containing the intersection of this
     // rectangle and the given rectangle.
-    pub fn intersection(self, r: Rect) -> Rect {
+    pub fn intersection(self, r: Rect<T>) -> Rect<T> {
         let xx = self.x.intersection(r.x);
         let yy = self.y.intersection(r.y);
 
         if xx.is_empty() || yy.is_empty() {
-            return empty_rect();
+            return Rect::empty();
         }
 
         return Rect { x: xx, y: yy };
     }
 
+    // cast converts the rectangle to another scalar type, see r1::Scalar::from_f64.
+    pub fn cast<U: Scalar>(self) -> Rect<U> {
+        return Rect {
+            x: self.x.cast(),
+            y: self.y.cast(),
+        };
+    }
+}
+
+impl Rect {
     // approx_equal returns true if the x- and y-intervals of the two rectangles are
-    // the same up to the given tolerance.
+    // the same up to the default tolerance.
     pub fn approx_equal(self, r: Rect) -> bool {
-        return self.x.approx_equal(r.x) && self.y.approx_equal(r.y);
+        return self.approx_equal_with(r, Tolerance::default());
+    }
+
+    // approx_equal_with returns true if the x- and y-intervals of the two rectangles
+    // are the same up to the given tolerance.
+    pub fn approx_equal_with(self, r: Rect, tolerance: Tolerance) -> bool {
+        return self.x.approx_equal_with(r.x, tolerance)
+            && self.y.approx_equal_with(r.y, tolerance);
+    }
+
+    // directed_hausdorff_distance returns the largest distance from a point of this
+    // rectangle to the closest point of the other one. The farthest point can be
+    // chosen independently along each axis, so this combines the directed
+    // Hausdorff distances of the x- and y-intervals.
+    pub fn directed_hausdorff_distance(self, r: Rect) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        if r.is_empty() {
+            return f64::INFINITY;
+        }
+
+        return self
+            .x
+            .directed_hausdorff_distance(r.x)
+            .hypot(self.y.directed_hausdorff_distance(r.y));
+    }
+
+    // hausdorff_distance returns the Hausdorff distance between the two rectangles,
+    // the largest of the two directed Hausdorff distances.
+    pub fn hausdorff_distance(self, r: Rect) -> f64 {
+        return self
+            .directed_hausdorff_distance(r)
+            .max(r.directed_hausdorff_distance(self));
+    }
+
+    // distance returns the smallest distance between a point of this rectangle and
+    // a point of the other one: 0 when they intersect, infinity when either is
+    // empty.
+    pub fn distance(self, r: Rect) -> f64 {
+        if self.is_empty() || r.is_empty() {
+            return f64::INFINITY;
+        }
+
+        return self.x.distance(r.x).hypot(self.y.distance(r.y));
+    }
+
+    // distance_to_point returns the distance from p to the closest point of the
+    // rectangle, or infinity if the rectangle is empty.
+    pub fn distance_to_point(self, p: Point) -> f64 {
+        if self.is_empty() {
+            return f64::INFINITY;
+        }
+
+        return p.sub(self.clamp_point(p)).norm();
+    }
+
+    // overlap_ratio returns the fraction of the area of this rectangle covered by
+    // the other one. A rectangle without area is either fully covered (1) or not
+    // (0), and an empty rectangle is never covered.
+    pub fn overlap_ratio(self, r: Rect) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        let area = self.area();
+        if area == 0.0 {
+            return if r.contains(self) { 1.0 } else { 0.0 };
+        }
+
+        return self.intersection(r).area() / area;
+    }
+
+    // intersection_over_union returns the area of the intersection of the two
+    // rectangles divided by the area of their union, from 0 for disjoint rectangles
+    // to 1 for equal ones. When the union has no area, the result is 1 if the
+    // rectangles are the same and 0 otherwise.
+    pub fn intersection_over_union(self, r: Rect) -> f64 {
+        if self.is_empty() || r.is_empty() {
+            return 0.0;
+        }
+
+        let inter = self.intersection(r).area();
+        let union = self.area() + r.area() - inter;
+        if union == 0.0 {
+            return if self.x == r.x && self.y == r.y {
+                1.0
+            } else {
+                0.0
+            };
+        }
+
+        return inter / union;
     }
 
     pub fn string(self) -> String {
         return format!("[lo{:?}, hi{:?}]", self.lo(), self.hi());
     }
 }
+
+// Display writes the rectangle as "[lo(x, y), hi(x, y)]", using the Display form
+// of its corners. Formatting options apply to every coordinate.
+impl<T: fmt::Display> fmt::Display for Rect<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lo = Point {
+            x: &self.x.lo,
+            y: &self.y.lo,
+        };
+        let hi = Point {
+            x: &self.x.hi,
+            y: &self.y.hi,
+        };
+
+        f.write_str("[lo")?;
+        fmt::Display::fmt(&lo, f)?;
+        f.write_str(", hi")?;
+        fmt::Display::fmt(&hi, f)?;
+        f.write_str("]")
+    }
+}
+
+// FromStr parses both the output of Display and the one of string(), where the
+// corners are written in their Debug form.
+impl<T: FromStr> FromStr for Rect<T> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut scanner = Scanner::new(s);
+
+        scanner.expect("[")?;
+        scanner.expect("lo")?;
+        let lo: Point<T> = parse_point(&mut scanner)?;
+        scanner.expect(",")?;
+        scanner.expect("hi")?;
+        let hi: Point<T> = parse_point(&mut scanner)?;
+        scanner.expect("]")?;
+        scanner.finish()?;
+
+        return Ok(Rect {
+            x: Interval { lo: lo.x, hi: hi.x },
+            y: Interval { lo: lo.y, hi: hi.y },
+        });
+    }
+}
+
+// Serialize writes the rectangle as an { x, y } struct of intervals. A rectangle
+// with either interval empty is empty, and is written as the canonical empty
+// rectangle so that every empty rectangle has a single encoding.
+#[cfg(feature = "serde")]
+impl<T: Scalar + serde::Serialize> serde::Serialize for Rect<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let r = if self.is_empty() {
+            Rect::empty()
+        } else {
+            *self
+        };
+
+        let mut state = serializer.serialize_struct("Rect", 2)?;
+        state.serialize_field("x", &r.x)?;
+        state.serialize_field("y", &r.y)?;
+        return state.end();
+    }
+}
+
+// Lossless conversions between rectangle precisions. Use Rect::cast for the
+// other ones.
+macro_rules! impl_rect_from {
+    ($($from:ty => $to:ty),*) => {$(
+        impl From<Rect<$from>> for Rect<$to> {
+            fn from(r: Rect<$from>) -> Self {
+                Rect {
+                    x: r.x.into(),
+                    y: r.y.into(),
+                }
+            }
+        }
+    )*};
+}
+
+impl_rect_from!(
+    f32 => f64,
+    i32 => f64,
+    u32 => f64,
+    i16 => f32,
+    u16 => f32,
+    i32 => i64,
+    u32 => u64
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let r = Rect {
+            x: Interval { lo: 0.1, hi: 2.0 },
+            y: Interval { lo: -1.5, hi: 3.0 },
+        };
+
+        assert_eq!("[lo(0.1, -1.5), hi(2, 3)]", r.to_string());
+        assert!(r.approx_equal(r.to_string().parse().unwrap()));
+        assert!(r.approx_equal(r.string().parse().unwrap()));
+        assert!(empty_rect().string().parse::<Rect>().unwrap().x.is_empty());
+
+        let err = "[lo(0, 0) hi(1, 1)]".parse::<Rect>().unwrap_err();
+        assert_eq!(10, err.position);
+        assert_eq!(ParseErrorKind::Expected(","), err.kind);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let r = rect_from_points(&[Point { x: 0.0, y: 1.0 }, Point { x: 2.0, y: 3.0 }]);
+        let back: Rect = serde_json::from_str(&serde_json::to_string(&r).unwrap()).unwrap();
+        assert!(r.approx_equal(back));
+
+        let empty = Rect {
+            x: Interval { lo: 4.0, hi: 2.0 },
+            y: Interval { lo: 7.0, hi: -7.0 },
+        };
+        let json = serde_json::to_string(&empty).unwrap();
+        assert_eq!(serde_json::to_string(&empty_rect()).unwrap(), json);
+
+        let back: Rect = serde_json::from_str(&json).unwrap();
+        assert!(back.x.equal(empty_interval()) && back.y.equal(empty_interval()));
+
+        // A single empty axis makes the whole rectangle empty.
+        let half_empty = Rect {
+            x: Interval { lo: 0.0, hi: 1.0 },
+            y: Interval { lo: 3.0, hi: 2.0 },
+        };
+        assert!(half_empty.is_empty());
+        assert_eq!(json, serde_json::to_string(&half_empty).unwrap());
+    }
+
+    #[test]
+    fn metrics() {
+        let unit = rect_from_points(&[Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 1.0 }]);
+        let shifted = rect_from_points(&[Point { x: 0.5, y: 0.0 }, Point { x: 1.5, y: 1.0 }]);
+        let far = rect_from_points(&[Point { x: 4.0, y: 5.0 }, Point { x: 5.0, y: 6.0 }]);
+        let inner = rect_from_center_size(Point { x: 0.5, y: 0.5 }, Point { x: 0.5, y: 0.5 });
+
+        assert_eq!(0.5, unit.hausdorff_distance(shifted));
+        assert_eq!(0.0, inner.directed_hausdorff_distance(unit));
+        assert_eq!(0.25f64.hypot(0.25), unit.directed_hausdorff_distance(inner));
+        assert_eq!(5.0, unit.distance(far));
+        assert_eq!(0.0, unit.distance(shifted));
+        assert_eq!(5.0, unit.distance_to_point(Point { x: 4.0, y: 5.0 }));
+        assert_eq!(0.0, unit.distance_to_point(Point { x: 0.2, y: 0.7 }));
+
+        assert_eq!(1.0, inner.overlap_ratio(unit));
+        assert_eq!(0.25, unit.overlap_ratio(inner));
+        assert_eq!(0.5, unit.overlap_ratio(shifted));
+        assert_eq!(1.0 / 3.0, unit.intersection_over_union(shifted));
+        assert_eq!(0.0, unit.intersection_over_union(far));
+        assert_eq!(1.0, unit.intersection_over_union(unit));
+
+        assert_eq!(0.0, empty_rect().area());
+        assert_eq!(f64::INFINITY, empty_rect().distance(unit));
+        assert_eq!(f64::INFINITY, unit.hausdorff_distance(empty_rect()));
+    }
+
+    #[test]
+    fn integer_expanded() {
+        let r: Rect<u8> = Rect {
+            x: Interval { lo: 0, hi: 250 },
+            y: Interval { lo: 3, hi: 4 },
+        };
+
+        let grown = r.expanded_by_margin(10);
+        assert_eq!(Interval { lo: 0, hi: 255 }, grown.x);
+        assert_eq!(Interval { lo: 0, hi: 14 }, grown.y);
+
+        let s: Rect<i16> = Rect {
+            x: Interval { lo: -32760, hi: 0 },
+            y: Interval { lo: 3, hi: 4 },
+        };
+        assert_eq!(Interval { lo: -32768, hi: 10 }, s.expanded_by_margin(10).x);
+        assert_eq!(
+            Interval { lo: -32755, hi: -5 },
+            s.expanded(Point { x: -5, y: 0 }).x
+        );
+        assert!(s.expanded(Point { x: 0, y: -1 }).is_empty());
+    }
+}
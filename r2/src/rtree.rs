@@ -0,0 +1,690 @@
+use crate::point::*;
+use crate::rect::*;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+// The largest and smallest number of children of a node, the root excepted.
+const MAX_ENTRIES: usize = 16;
+const MIN_ENTRIES: usize = 6;
+
+// RTree stores rectangles along with a payload and answers window, point and
+// nearest neighbour queries in O(log n + k) on typical data, k being the number
+// of reported entries.
+//
+// Every node holds the bounding rectangle of its subtree, so that whole subtrees
+// can be skipped during queries. The tree is either bulk loaded with the
+// Sort-Tile-Recursive algorithm by rtree_from_entries, or built incrementally by
+// insert, which splits overflowing nodes along the axis and position minimizing
+// the overlap of the two halves, as in the R*-tree.
+pub struct RTree<V> {
+    root: Node<V>,
+    len: usize,
+}
+
+struct Node<V> {
+    bound: Rect,
+    kind: NodeKind<V>,
+}
+
+enum NodeKind<V> {
+    Leaf(Vec<(Rect, V)>),
+    Internal(Vec<Node<V>>),
+}
+
+// rtree_from_entries builds a packed tree from the given entries in
+// O(n log n), which is faster than inserting them one by one and gives better
+// query performance.
+pub fn rtree_from_entries<V>(entries: Vec<(Rect, V)>) -> RTree<V> {
+    let len = entries.len();
+    if len == 0 {
+        return RTree::new();
+    }
+
+    let leaves: Vec<Node<V>> = sort_tile_recursive(entries, |e| e.0)
+        .into_iter()
+        .map(leaf)
+        .collect();
+
+    let mut level = leaves;
+    while level.len() > 1 {
+        level = sort_tile_recursive(level, |n| n.bound)
+            .into_iter()
+            .map(internal)
+            .collect();
+    }
+
+    return RTree {
+        root: level.pop().unwrap(),
+        len,
+    };
+}
+
+// sort_tile_recursive packs the items in groups of at most MAX_ENTRIES, by
+// sorting them along x, cutting them in vertical slices, and sorting every slice
+// along y.
+fn sort_tile_recursive<T>(mut items: Vec<T>, bound: impl Fn(&T) -> Rect) -> Vec<Vec<T>> {
+    let groups = items.len().div_ceil(MAX_ENTRIES);
+    let slices = (groups as f64).sqrt().ceil() as usize;
+    let slice_len = slices * MAX_ENTRIES;
+
+    items.sort_by(|a, b| bound(a).center().x.total_cmp(&bound(b).center().x));
+
+    let mut packed = Vec::with_capacity(groups);
+    while !items.is_empty() {
+        let rest = items.split_off(slice_len.min(items.len()));
+        let mut slice = std::mem::replace(&mut items, rest);
+        slice.sort_by(|a, b| bound(a).center().y.total_cmp(&bound(b).center().y));
+
+        while !slice.is_empty() {
+            let rest = slice.split_off(MAX_ENTRIES.min(slice.len()));
+            packed.push(std::mem::replace(&mut slice, rest));
+        }
+    }
+
+    return packed;
+}
+
+fn leaf<V>(entries: Vec<(Rect, V)>) -> Node<V> {
+    let bound = entries.iter().fold(empty_rect(), |b, e| b.union(e.0));
+    Node {
+        bound,
+        kind: NodeKind::Leaf(entries),
+    }
+}
+
+fn internal<V>(children: Vec<Node<V>>) -> Node<V> {
+    let bound = children.iter().fold(empty_rect(), |b, c| b.union(c.bound));
+    Node {
+        bound,
+        kind: NodeKind::Internal(children),
+    }
+}
+
+fn same_rect(a: Rect, b: Rect) -> bool {
+    return a.x == b.x && a.y == b.y;
+}
+
+// margin returns the half perimeter of the rectangle.
+fn margin(r: Rect) -> f64 {
+    if r.is_empty() {
+        return 0.0;
+    }
+
+    return r.x.length() + r.y.length();
+}
+
+impl<V> Default for RTree<V> {
+    fn default() -> Self {
+        RTree {
+            root: leaf(Vec::new()),
+            len: 0,
+        }
+    }
+}
+
+impl<V> RTree<V> {
+    pub fn new() -> RTree<V> {
+        RTree::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // bound returns the bounding rectangle of all the entries.
+    pub fn bound(&self) -> Rect {
+        return self.root.bound;
+    }
+
+    pub fn insert(&mut self, rect: Rect, value: V) {
+        self.len += 1;
+        if let Some(sibling) = self.root.insert(rect, value) {
+            let root = std::mem::replace(&mut self.root, leaf(Vec::new()));
+            self.root = internal(vec![root, sibling]);
+        }
+    }
+
+    // remove deletes one entry whose rectangle is exactly the given one and
+    // returns its payload, or None if there is no such entry.
+    pub fn remove(&mut self, rect: Rect) -> Option<V> {
+        return self.remove_where(rect, &|_| true);
+    }
+
+    // remove_entry deletes one entry holding exactly the given rectangle and
+    // value, and returns its payload. Use it rather than remove when several
+    // entries may share the same rectangle.
+    pub fn remove_entry(&mut self, rect: Rect, value: &V) -> Option<V>
+    where
+        V: PartialEq,
+    {
+        return self.remove_where(rect, &|v| v == value);
+    }
+
+    fn remove_where(&mut self, rect: Rect, matches: &dyn Fn(&V) -> bool) -> Option<V> {
+        let mut orphans = Vec::new();
+        let removed = self.root.remove(rect, matches, &mut orphans)?;
+        self.len -= 1;
+
+        // Shorten the tree while the root has a single child.
+        loop {
+            let child = match &mut self.root.kind {
+                NodeKind::Internal(children) if children.len() == 1 => children.pop().unwrap(),
+                _ => break,
+            };
+            self.root = child;
+        }
+
+        // The entries of the nodes dissolved for having too few children go back
+        // in the tree.
+        self.len -= orphans.len();
+        for (r, v) in orphans {
+            self.insert(r, v);
+        }
+
+        return Some(removed);
+    }
+
+    // intersecting returns the entries whose rectangle intersects the window, as
+    // reported by Rect::intersects.
+    pub fn intersecting(&self, window: Rect) -> Vec<(Rect, &V)> {
+        let mut result = Vec::new();
+        self.root.query(
+            &|b| b.intersects(window),
+            &|r| r.intersects(window),
+            &mut result,
+        );
+
+        return result;
+    }
+
+    // contained_in returns the entries whose rectangle lies inside the window, as
+    // reported by Rect::contains.
+    pub fn contained_in(&self, window: Rect) -> Vec<(Rect, &V)> {
+        let mut result = Vec::new();
+        self.root.query(
+            &|b| b.intersects(window),
+            &|r| window.contains(r),
+            &mut result,
+        );
+
+        return result;
+    }
+
+    // containing returns the entries whose rectangle contains the window, as
+    // reported by Rect::contains.
+    pub fn containing(&self, window: Rect) -> Vec<(Rect, &V)> {
+        let mut result = Vec::new();
+        self.root.query(
+            &|b| b.contains(window),
+            &|r| r.contains(window),
+            &mut result,
+        );
+
+        return result;
+    }
+
+    // containing_point returns the entries whose rectangle contains the point p,
+    // as reported by Rect::contains_point.
+    pub fn containing_point(&self, p: Point) -> Vec<(Rect, &V)> {
+        let mut result = Vec::new();
+        self.root.query(
+            &|b| b.contains_point(p),
+            &|r| r.contains_point(p),
+            &mut result,
+        );
+
+        return result;
+    }
+
+    // nearest returns the k entries closest to the query rectangle, by increasing
+    // Rect::distance. Entries at equal distance are returned in no particular
+    // order, and empty rectangles, being infinitely far, are never returned.
+    pub fn nearest(&self, query: Rect, k: usize) -> Vec<(Rect, &V)> {
+        let mut result = Vec::with_capacity(k);
+        if k == 0 {
+            return result;
+        }
+
+        // Best-first search: the nodes and entries met so far, closest first. A
+        // node is never closer than its bound, so an entry popped from the queue
+        // is closer than everything still in it.
+        let mut queue = BinaryHeap::new();
+        queue.push(Candidate {
+            distance: self.root.bound.distance(query),
+            item: Item::Node(&self.root),
+        });
+
+        while let Some(Candidate { distance, item }) = queue.pop() {
+            if distance == f64::INFINITY {
+                break;
+            }
+
+            match item {
+                Item::Entry(rect, value) => {
+                    result.push((rect, value));
+                    if result.len() == k {
+                        break;
+                    }
+                }
+                Item::Node(node) => match &node.kind {
+                    NodeKind::Leaf(entries) => {
+                        for (rect, value) in entries {
+                            queue.push(Candidate {
+                                distance: rect.distance(query),
+                                item: Item::Entry(*rect, value),
+                            });
+                        }
+                    }
+                    NodeKind::Internal(children) => {
+                        for child in children {
+                            queue.push(Candidate {
+                                distance: child.bound.distance(query),
+                                item: Item::Node(child),
+                            });
+                        }
+                    }
+                },
+            }
+        }
+
+        return result;
+    }
+
+    // nearest_to_point returns the k entries closest to the point p, by increasing
+    // Rect::distance_to_point.
+    pub fn nearest_to_point(&self, p: Point, k: usize) -> Vec<(Rect, &V)> {
+        return self.nearest(rect_from_points(&[p]), k);
+    }
+
+    // entries returns all entries, in no particular order.
+    pub fn entries(&self) -> Vec<(Rect, &V)> {
+        let mut result = Vec::with_capacity(self.len);
+        self.root.query(&|_| true, &|_| true, &mut result);
+
+        return result;
+    }
+}
+
+// Candidate is an element of the priority queue of RTree::nearest, ordered so
+// that the closest one comes first out of the max-heap.
+struct Candidate<'a, V> {
+    distance: f64,
+    item: Item<'a, V>,
+}
+
+enum Item<'a, V> {
+    Node(&'a Node<V>),
+    Entry(Rect, &'a V),
+}
+
+impl<V> PartialEq for Candidate<'_, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<V> Eq for Candidate<'_, V> {}
+
+impl<V> PartialOrd for Candidate<'_, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<V> Ord for Candidate<'_, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Entries come before nodes at the same distance, so that they are
+        // reported as soon as possible.
+        let rank = |c: &Self| match c.item {
+            Item::Entry(..) => 0,
+            Item::Node(_) => 1,
+        };
+
+        return other
+            .distance
+            .total_cmp(&self.distance)
+            .then(rank(other).cmp(&rank(self)));
+    }
+}
+
+impl<V> Node<V> {
+    fn update_bound(&mut self) {
+        self.bound = match &self.kind {
+            NodeKind::Leaf(entries) => entries.iter().fold(empty_rect(), |b, e| b.union(e.0)),
+            NodeKind::Internal(children) => {
+                children.iter().fold(empty_rect(), |b, c| b.union(c.bound))
+            }
+        };
+    }
+
+    // insert adds the entry to the subtree and returns the new sibling of the node
+    // if it had to be split.
+    fn insert(&mut self, rect: Rect, value: V) -> Option<Node<V>> {
+        self.bound = self.bound.union(rect);
+
+        match &mut self.kind {
+            NodeKind::Leaf(entries) => {
+                entries.push((rect, value));
+                if entries.len() <= MAX_ENTRIES {
+                    return None;
+                }
+
+                let (left, right) = split(std::mem::take(entries), |e| e.0);
+                *entries = left;
+                self.update_bound();
+                return Some(leaf(right));
+            }
+            NodeKind::Internal(children) => {
+                let best = choose_subtree(children, rect);
+                let sibling = children[best].insert(rect, value)?;
+                children.push(sibling);
+                if children.len() <= MAX_ENTRIES {
+                    return None;
+                }
+
+                let (left, right) = split(std::mem::take(children), |c| c.bound);
+                *children = left;
+                self.update_bound();
+                return Some(internal(right));
+            }
+        }
+    }
+
+    // remove detaches one entry holding the given rectangle and a value accepted
+    // by matches. The entries of the descendants left with too few children are
+    // moved to orphans.
+    fn remove(
+        &mut self,
+        rect: Rect,
+        matches: &dyn Fn(&V) -> bool,
+        orphans: &mut Vec<(Rect, V)>,
+    ) -> Option<V> {
+        let removed = match &mut self.kind {
+            NodeKind::Leaf(entries) => {
+                let position = entries
+                    .iter()
+                    .position(|(r, v)| same_rect(*r, rect) && matches(v))?;
+                entries.swap_remove(position).1
+            }
+            NodeKind::Internal(children) => {
+                let mut removed = None;
+                for i in 0..children.len() {
+                    if !children[i].bound.contains(rect) {
+                        continue;
+                    }
+
+                    if let Some(v) = children[i].remove(rect, matches, orphans) {
+                        if children[i].child_count() < MIN_ENTRIES {
+                            children.swap_remove(i).collect_entries(orphans);
+                        }
+                        removed = Some(v);
+                        break;
+                    }
+                }
+                removed?
+            }
+        };
+
+        self.update_bound();
+        return Some(removed);
+    }
+
+    fn child_count(&self) -> usize {
+        match &self.kind {
+            NodeKind::Leaf(entries) => entries.len(),
+            NodeKind::Internal(children) => children.len(),
+        }
+    }
+
+    fn collect_entries(self, out: &mut Vec<(Rect, V)>) {
+        match self.kind {
+            NodeKind::Leaf(entries) => out.extend(entries),
+            NodeKind::Internal(children) => {
+                for child in children {
+                    child.collect_entries(out);
+                }
+            }
+        }
+    }
+
+    // query collects the entries of the subtree accepted by pred, visiting only the
+    // subtrees whose bound is accepted by visit.
+    fn query<'a>(
+        &'a self,
+        visit: &dyn Fn(Rect) -> bool,
+        pred: &dyn Fn(Rect) -> bool,
+        result: &mut Vec<(Rect, &'a V)>,
+    ) {
+        if !visit(self.bound) {
+            return;
+        }
+
+        match &self.kind {
+            NodeKind::Leaf(entries) => {
+                for (rect, value) in entries {
+                    if pred(*rect) {
+                        result.push((*rect, value));
+                    }
+                }
+            }
+            NodeKind::Internal(children) => {
+                for child in children {
+                    child.query(visit, pred, result);
+                }
+            }
+        }
+    }
+}
+
+// choose_subtree returns the child whose bound needs the least enlargement to
+// include rect, the one with the smallest area in case of a tie.
+fn choose_subtree<V>(children: &[Node<V>], rect: Rect) -> usize {
+    let cost = |c: &Node<V>| {
+        let area = c.bound.area();
+        (c.bound.union(rect).area() - area, area)
+    };
+
+    let mut best = 0;
+    let mut best_cost = cost(&children[0]);
+    for (i, child) in children.iter().enumerate().skip(1) {
+        let c = cost(child);
+        if c.0 < best_cost.0 || (c.0 == best_cost.0 && c.1 < best_cost.1) {
+            best = i;
+            best_cost = c;
+        }
+    }
+
+    return best;
+}
+
+// split divides an overflowing node in two, as the R*-tree does: the items are
+// sorted along the axis where the halves have the smallest perimeters, and cut at
+// the position where the halves overlap the least.
+fn split<T>(mut items: Vec<T>, bound: impl Fn(&T) -> Rect) -> (Vec<T>, Vec<T>) {
+    let sort_x = |a: &T, b: &T| {
+        let (a, b) = (bound(a), bound(b));
+        a.x.lo.total_cmp(&b.x.lo).then(a.x.hi.total_cmp(&b.x.hi))
+    };
+    let sort_y = |a: &T, b: &T| {
+        let (a, b) = (bound(a), bound(b));
+        a.y.lo.total_cmp(&b.y.lo).then(a.y.hi.total_cmp(&b.y.hi))
+    };
+
+    // bounds returns the bounds of the prefixes and of the suffixes of the items.
+    let bounds = |items: &[T]| {
+        let mut prefix = Vec::with_capacity(items.len() + 1);
+        let mut suffix = vec![empty_rect(); items.len() + 1];
+        prefix.push(empty_rect());
+        for item in items {
+            let last = prefix[prefix.len() - 1];
+            prefix.push(Rect::union(last, bound(item)));
+        }
+        for i in (0..items.len()).rev() {
+            suffix[i] = suffix[i + 1].union(bound(&items[i]));
+        }
+        (prefix, suffix)
+    };
+
+    let n = items.len();
+    let cuts = MIN_ENTRIES..=n - MIN_ENTRIES;
+
+    items.sort_by(sort_x);
+    let (prefix, suffix) = bounds(&items);
+    let margin_x: f64 = cuts
+        .clone()
+        .map(|k| margin(prefix[k]) + margin(suffix[k]))
+        .sum();
+
+    items.sort_by(sort_y);
+    let (prefix, suffix) = bounds(&items);
+    let margin_y: f64 = cuts
+        .clone()
+        .map(|k| margin(prefix[k]) + margin(suffix[k]))
+        .sum();
+
+    if margin_x < margin_y {
+        items.sort_by(sort_x);
+    }
+    let (prefix, suffix) = if margin_x < margin_y {
+        bounds(&items)
+    } else {
+        (prefix, suffix)
+    };
+
+    let cost = |k: usize| {
+        let overlap = prefix[k].intersection(suffix[k]).area();
+        (overlap, prefix[k].area() + suffix[k].area())
+    };
+    let mut best = MIN_ENTRIES;
+    for k in cuts {
+        let (c, b) = (cost(k), cost(best));
+        if c.0 < b.0 || (c.0 == b.0 && c.1 < b.1) {
+            best = k;
+        }
+    }
+
+    let right = items.split_off(best);
+    return (items, right);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> Rect {
+        rect_from_points(&[Point { x: x0, y: y0 }, Point { x: x1, y: y1 }])
+    }
+
+    // rects returns a deterministic pseudo-random list of small rectangles.
+    fn rects(n: usize) -> Vec<Rect> {
+        let mut random = random_sequence(1);
+
+        return (0..n)
+            .map(|_| {
+                let (x, y) = (random() * 100.0, random() * 100.0);
+                rect(x, y, x + random() * 5.0, y + random() * 5.0)
+            })
+            .collect();
+    }
+
+    fn sorted(entries: Vec<(Rect, &usize)>) -> Vec<usize> {
+        let mut values: Vec<usize> = entries.into_iter().map(|(_, &v)| v).collect();
+        values.sort_unstable();
+        return values;
+    }
+
+    fn brute_force(list: &[Rect], pred: impl Fn(Rect) -> bool) -> Vec<usize> {
+        return (0..list.len()).filter(|&i| pred(list[i])).collect();
+    }
+
+    fn check_queries(tree: &RTree<usize>, list: &[Rect], alive: &[bool]) {
+        let window = rect(20.0, 30.0, 45.0, 50.0);
+        let p = Point { x: 50.0, y: 50.0 };
+        let alive_and = |pred: &dyn Fn(Rect) -> bool| {
+            brute_force(list, pred)
+                .into_iter()
+                .filter(|&i| alive[i])
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            alive_and(&|r| r.intersects(window)),
+            sorted(tree.intersecting(window))
+        );
+        assert_eq!(
+            alive_and(&|r| window.contains(r)),
+            sorted(tree.contained_in(window))
+        );
+        assert_eq!(
+            alive_and(&|r| r.contains_point(p)),
+            sorted(tree.containing_point(p))
+        );
+        assert_eq!(
+            alive_and(&|r| r.contains(rect(50.0, 50.0, 50.5, 50.5))),
+            sorted(tree.containing(rect(50.0, 50.0, 50.5, 50.5)))
+        );
+
+        let mut distances: Vec<f64> = alive_and(&|_| true)
+            .into_iter()
+            .map(|i| list[i].distance_to_point(p))
+            .collect();
+        distances.sort_by(f64::total_cmp);
+        let nearest: Vec<f64> = tree
+            .nearest_to_point(p, 10)
+            .into_iter()
+            .map(|(r, _)| r.distance_to_point(p))
+            .collect();
+        assert_eq!(&distances[..10], &nearest[..]);
+    }
+
+    #[test]
+    fn bulk_load() {
+        let list = rects(1000);
+        let mut tree = rtree_from_entries(list.iter().copied().zip(0..).collect());
+
+        assert_eq!(1000, tree.len());
+        check_queries(&tree, &list, &[true; 1000]);
+
+        let mut alive = vec![true; list.len()];
+        for i in (0..list.len()).step_by(2) {
+            assert_eq!(Some(i), tree.remove(list[i]));
+            alive[i] = false;
+        }
+        check_queries(&tree, &list, &alive);
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let list = rects(1000);
+        let mut tree = RTree::new();
+        for (i, &r) in list.iter().enumerate() {
+            tree.insert(r, i);
+        }
+
+        let mut alive = vec![true; list.len()];
+        check_queries(&tree, &list, &alive);
+
+        for i in (0..list.len()).step_by(3) {
+            assert_eq!(Some(i), tree.remove_entry(list[i], &i));
+            alive[i] = false;
+        }
+
+        assert_eq!(None, tree.remove_entry(list[0], &0));
+        assert_eq!(666, tree.len());
+        assert_eq!(666, tree.entries().len());
+        check_queries(&tree, &list, &alive);
+
+        for i in 0..list.len() {
+            if alive[i] {
+                assert_eq!(Some(i), tree.remove(list[i]));
+            }
+        }
+        assert!(tree.is_empty());
+        assert!(tree.bound().is_empty());
+    }
+}
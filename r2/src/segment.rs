@@ -0,0 +1,570 @@
+use crate::point::*;
+use crate::predicates::*;
+use crate::rect::*;
+use crate::sweep_status::*;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+
+// Segment is the closed line segment between two points. A segment whose
+// endpoints are equal is degenerate and stands for a single point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Segment {
+    pub a: Point,
+    pub b: Point,
+}
+
+// SegmentIntersection is the intersection of two segments: nothing, a single
+// point, or the segment along which two collinear segments overlap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentIntersection {
+    None,
+    Point(Point),
+    Segment(Segment),
+}
+
+impl Segment {
+    pub fn is_degenerate(self) -> bool {
+        return self.a == self.b;
+    }
+
+    pub fn length(self) -> f64 {
+        return self.b.sub(self.a).norm();
+    }
+
+    pub fn reversed(self) -> Segment {
+        Segment {
+            a: self.b,
+            b: self.a,
+        }
+    }
+
+    pub fn bound(self) -> Rect {
+        return rect_from_points(&[self.a, self.b]);
+    }
+
+    // contains_point reports whether p lies exactly on the segment.
+    pub fn contains_point(self, p: Point) -> bool {
+        return orient2d(self.a, self.b, p) == 0.0 && self.bound().contains_point(p);
+    }
+
+    // closest_point returns the point of the segment closest to p. The endpoints
+    // are returned exactly when they are the closest.
+    pub fn closest_point(self, p: Point) -> Point {
+        let d = self.b.sub(self.a);
+        let length2 = d.dot(d);
+        if length2 == 0.0 {
+            return self.a;
+        }
+
+        let t = p.sub(self.a).dot(d) / length2;
+        if t <= 0.0 {
+            return self.a;
+        }
+        if t >= 1.0 {
+            return self.b;
+        }
+
+        return self.bound().clamp_point(self.a.add(d.mul(t)));
+    }
+
+    pub fn distance_to_point(self, p: Point) -> f64 {
+        return p.sub(self.closest_point(p)).norm();
+    }
+
+    pub fn intersects(self, s: Segment) -> bool {
+        return self.intersection(s) != SegmentIntersection::None;
+    }
+
+    // intersection returns the intersection of the two segments. Whether they
+    // intersect is decided from the exact signs given by orient2d, so touching
+    // segments, for instance at a shared endpoint or at a T-junction, are
+    // detected exactly and the endpoint is returned as is. The crossing point of
+    // two segments that cross properly is rounded, but it is guaranteed to lie in
+    // the bounding rectangles of both segments.
+    pub fn intersection(self, s: Segment) -> SegmentIntersection {
+        let (a, b, c, d) = (self.a, self.b, s.a, s.b);
+
+        if self.is_degenerate() && s.is_degenerate() {
+            if a == c {
+                return SegmentIntersection::Point(a);
+            }
+            return SegmentIntersection::None;
+        }
+
+        let d1 = orient2d(a, b, c);
+        let d2 = orient2d(a, b, d);
+        let d3 = orient2d(c, d, a);
+        let d4 = orient2d(c, d, b);
+
+        if d1 == 0.0 && d2 == 0.0 && d3 == 0.0 && d4 == 0.0 {
+            return collinear_intersection(self, s);
+        }
+
+        if (d1 > 0.0 && d2 > 0.0) || (d1 < 0.0 && d2 < 0.0) {
+            return SegmentIntersection::None;
+        }
+        if (d3 > 0.0 && d4 > 0.0) || (d3 < 0.0 && d4 < 0.0) {
+            return SegmentIntersection::None;
+        }
+
+        // One segment touches the other with an endpoint.
+        if d1 == 0.0 && self.bound().contains_point(c) {
+            return SegmentIntersection::Point(c);
+        }
+        if d2 == 0.0 && self.bound().contains_point(d) {
+            return SegmentIntersection::Point(d);
+        }
+        if d3 == 0.0 && s.bound().contains_point(a) {
+            return SegmentIntersection::Point(a);
+        }
+        if d4 == 0.0 && s.bound().contains_point(b) {
+            return SegmentIntersection::Point(b);
+        }
+
+        let bound = self.bound().intersection(s.bound());
+        return SegmentIntersection::Point(bound.clamp_point(crossing_point(self, s)));
+    }
+}
+
+// crossing_point returns the intersection of the lines supporting two segments
+// known to cross. The segments are put in a canonical order and orientation
+// first, so that the rounded result does not depend on them.
+fn crossing_point(s: Segment, t: Segment) -> Point {
+    let canonical = |seg: Segment| {
+        if EventPoint::new(seg.b) < EventPoint::new(seg.a) {
+            return seg.reversed();
+        }
+        return seg;
+    };
+    let (mut s, mut t) = (canonical(s), canonical(t));
+    if (EventPoint::new(t.a), EventPoint::new(t.b)) < (EventPoint::new(s.a), EventPoint::new(s.b)) {
+        std::mem::swap(&mut s, &mut t);
+    }
+
+    let r = s.b.sub(s.a);
+    let q = t.b.sub(t.a);
+    let d3 = q.cross(s.a.sub(t.a));
+    let d4 = q.cross(s.b.sub(t.a));
+
+    return s.a.add(r.mul(d3 / (d3 - d4)));
+}
+
+// collinear_intersection returns the overlap of two segments lying on the same
+// line, at least one of them not degenerate. The endpoints of the result are
+// endpoints of the input segments.
+fn collinear_intersection(s: Segment, t: Segment) -> SegmentIntersection {
+    let (origin, direction) = if s.is_degenerate() {
+        (t.a, t.b.sub(t.a))
+    } else {
+        (s.a, s.b.sub(s.a))
+    };
+    let key = |p: Point| p.sub(origin).dot(direction);
+
+    let ordered = |seg: Segment| {
+        if key(seg.a) <= key(seg.b) {
+            return (seg.a, seg.b);
+        }
+        return (seg.b, seg.a);
+    };
+    let (s_lo, s_hi) = ordered(s);
+    let (t_lo, t_hi) = ordered(t);
+
+    let lo = if key(s_lo) >= key(t_lo) { s_lo } else { t_lo };
+    let hi = if key(s_hi) <= key(t_hi) { s_hi } else { t_hi };
+
+    match key(lo).partial_cmp(&key(hi)) {
+        Some(Ordering::Less) => SegmentIntersection::Segment(Segment { a: lo, b: hi }),
+        Some(Ordering::Equal) => SegmentIntersection::Point(lo),
+        _ => SegmentIntersection::None,
+    }
+}
+
+// EventPoint orders the points of the sweep lexicographically, x first.
+#[derive(Debug, Clone, Copy)]
+struct EventPoint(Point);
+
+impl EventPoint {
+    fn new(p: Point) -> EventPoint {
+        // Adding 0 turns -0 into +0, so that both are the same event.
+        EventPoint(Point {
+            x: p.x + 0.0,
+            y: p.y + 0.0,
+        })
+    }
+}
+
+impl PartialEq for EventPoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for EventPoint {}
+
+impl PartialOrd for EventPoint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for EventPoint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .x
+            .total_cmp(&other.0.x)
+            .then(self.0.y.total_cmp(&other.0.y))
+    }
+}
+
+// Event lists the segments starting, ending and crossing at an event point.
+#[derive(Default)]
+struct Event {
+    starts: Vec<usize>,
+    ends: Vec<usize>,
+    crossings: Vec<usize>,
+}
+
+// Sweep holds the state of the Bentley–Ottmann sweep of segment_intersections.
+struct Sweep {
+    // The segments, oriented from their smallest to their largest endpoint.
+    segments: Vec<Segment>,
+    events: BTreeMap<EventPoint, Event>,
+    status: SweepStatus,
+    found: HashSet<(usize, usize)>,
+    intersections: Vec<(usize, usize, SegmentIntersection)>,
+}
+
+impl Sweep {
+    // side returns a positive value if p is above the segment, i.e. on the left
+    // of its direction, a negative value if it is below and zero if p is on its
+    // supporting line.
+    fn side(&self, index: usize, p: Point) -> f64 {
+        let Segment { a, b } = self.segments[index];
+        return orient2d(a, b, p);
+    }
+
+    fn slope(&self, index: usize) -> f64 {
+        let Segment { a, b } = self.segments[index];
+        if a.x == b.x {
+            return f64::INFINITY;
+        }
+
+        return (b.y - a.y) / (b.x - a.x);
+    }
+
+    // passes_through reports whether the segment lies on p. Rounded crossing
+    // points are rarely exactly on the segments, so the segment is also accepted
+    // if it meets one of the segments known to pass through p at p, or overlaps
+    // one of them around p.
+    fn passes_through(&self, s: usize, p: Point, through: &[usize]) -> bool {
+        if self.segments[s].contains_point(p) {
+            return true;
+        }
+
+        return through
+            .iter()
+            .any(|&t| match self.segments[s].intersection(self.segments[t]) {
+                SegmentIntersection::None => false,
+                SegmentIntersection::Point(q) => EventPoint::new(q) == EventPoint::new(p),
+                SegmentIntersection::Segment(overlap) => overlap.bound().contains_point(p),
+            });
+    }
+
+    // report records the intersection of the two segments, if any, and returns
+    // it.
+    fn report(&mut self, s: usize, t: usize) -> SegmentIntersection {
+        let key = (s.min(t), s.max(t));
+        let intersection = self.segments[key.0].intersection(self.segments[key.1]);
+        if intersection != SegmentIntersection::None && self.found.insert(key) {
+            self.intersections.push((key.0, key.1, intersection));
+        }
+
+        return intersection;
+    }
+
+    // check reports the intersection of two segments that became neighbours in
+    // the status, and schedules their crossing if it is still ahead of the sweep.
+    fn check(&mut self, s: usize, t: usize, p: Point) {
+        if let SegmentIntersection::Point(q) = self.report(s, t) {
+            if EventPoint::new(q) > EventPoint::new(p) {
+                let event = self.events.entry(EventPoint::new(q)).or_default();
+                event.crossings.push(s);
+                event.crossings.push(t);
+            }
+        }
+    }
+
+    fn handle(&mut self, p: Point, event: Event) {
+        let mut through: Vec<usize> = Vec::new();
+        through.extend(&event.starts);
+        through.extend(&event.ends);
+        through.extend(&event.crossings);
+
+        // The segments of the status passing through p form a block, made of the
+        // ones known to pass through p and their neighbours lying on p. Locating
+        // the block from the known segments rather than by comparing ordinates
+        // keeps the status consistent at rounded crossing points.
+        let mut lo = self.status.len();
+        let mut hi = 0;
+        for &s in &through {
+            if self.status.contains(s) {
+                let position = self.status.position(s);
+                lo = lo.min(position);
+                hi = hi.max(position + 1);
+            }
+        }
+        if lo >= hi {
+            lo = self.status.partition_point(|s| self.side(s, p) > 0.0);
+            hi = lo;
+        }
+        through.extend((lo..hi).map(|position| self.status.get(position)));
+        while lo > 0 && self.passes_through(self.status.get(lo - 1), p, &through) {
+            lo -= 1;
+            through.push(self.status.get(lo));
+        }
+        while hi < self.status.len() && self.passes_through(self.status.get(hi), p, &through) {
+            through.push(self.status.get(hi));
+            hi += 1;
+        }
+        through.sort_unstable();
+        through.dedup();
+
+        for i in 0..through.len() {
+            for j in i + 1..through.len() {
+                self.report(through[i], through[j]);
+            }
+        }
+
+        // Replace the block with the segments going on past p, in their order
+        // right of p. They all pass through p, so this is the order of their
+        // slopes.
+        let mut inserted: Vec<usize> = through
+            .into_iter()
+            .filter(|&s| EventPoint::new(self.segments[s].b) > EventPoint::new(p))
+            .collect();
+        inserted.sort_by(|&s, &t| self.slope(s).total_cmp(&self.slope(t)));
+        let count = inserted.len();
+        self.status.replace(lo, hi, &inserted);
+
+        if count == 0 {
+            if lo > 0 && lo < self.status.len() {
+                self.check(self.status.get(lo - 1), self.status.get(lo), p);
+            }
+            return;
+        }
+
+        let last = lo + count - 1;
+        if lo > 0 {
+            self.check(self.status.get(lo - 1), self.status.get(lo), p);
+        }
+        if last + 1 < self.status.len() {
+            self.check(self.status.get(last), self.status.get(last + 1), p);
+        }
+    }
+}
+
+// segment_intersections returns every pair (i, j), with i < j, of intersecting
+// segments along with their intersection, sorted by i then j. Intersecting pairs
+// are found with a Bentley–Ottmann sweep over the n + k event points, for n
+// segments and k intersections, and their intersection is computed with
+// Segment::intersection. The segments crossing the sweep line are kept in a
+// balanced tree, see SweepStatus, so the sweep takes O((n + k) log n) time.
+pub fn segment_intersections(segments: &[Segment]) -> Vec<(usize, usize, SegmentIntersection)> {
+    let mut sweep = Sweep {
+        segments: Vec::with_capacity(segments.len()),
+        events: BTreeMap::new(),
+        status: SweepStatus::new(segments.len()),
+        found: HashSet::new(),
+        intersections: Vec::new(),
+    };
+
+    for (index, &s) in segments.iter().enumerate() {
+        let s = if EventPoint::new(s.a) <= EventPoint::new(s.b) {
+            s
+        } else {
+            s.reversed()
+        };
+        sweep.segments.push(s);
+
+        let start = sweep.events.entry(EventPoint::new(s.a)).or_default();
+        start.starts.push(index);
+        let end = sweep.events.entry(EventPoint::new(s.b)).or_default();
+        end.ends.push(index);
+    }
+
+    while let Some((p, event)) = sweep.events.pop_first() {
+        sweep.handle(p.0, event);
+    }
+
+    sweep.intersections.sort_by_key(|&(i, j, _)| (i, j));
+    return sweep.intersections;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    fn segment(ax: f64, ay: f64, bx: f64, by: f64) -> Segment {
+        Segment {
+            a: Point { x: ax, y: ay },
+            b: Point { x: bx, y: by },
+        }
+    }
+
+    fn point(x: f64, y: f64) -> SegmentIntersection {
+        SegmentIntersection::Point(Point { x, y })
+    }
+
+    #[test]
+    fn intersection() {
+        let tests_array: [(Segment, Segment, SegmentIntersection); 9] = [
+            // Proper crossing.
+            (
+                segment(0.0, 0.0, 2.0, 2.0),
+                segment(0.0, 2.0, 2.0, 0.0),
+                point(1.0, 1.0),
+            ),
+            // Shared endpoint.
+            (
+                segment(0.0, 0.0, 1.0, 1.0),
+                segment(1.0, 1.0, 2.0, 0.0),
+                point(1.0, 1.0),
+            ),
+            // T-junction.
+            (
+                segment(0.0, 0.0, 4.0, 0.0),
+                segment(1.0, 3.0, 1.0, 0.0),
+                point(1.0, 0.0),
+            ),
+            // Disjoint, on crossing lines.
+            (
+                segment(0.0, 0.0, 1.0, 0.0),
+                segment(2.0, -1.0, 2.0, 1.0),
+                SegmentIntersection::None,
+            ),
+            // Parallel.
+            (
+                segment(0.0, 0.0, 1.0, 1.0),
+                segment(0.0, 1.0, 1.0, 2.0),
+                SegmentIntersection::None,
+            ),
+            // Collinear overlap, in opposite directions.
+            (
+                segment(0.0, 0.0, 3.0, 3.0),
+                segment(4.0, 4.0, 1.0, 1.0),
+                SegmentIntersection::Segment(segment(1.0, 1.0, 3.0, 3.0)),
+            ),
+            // Collinear, touching at an endpoint.
+            (
+                segment(0.0, 0.0, 1.0, 0.0),
+                segment(1.0, 0.0, 2.0, 0.0),
+                point(1.0, 0.0),
+            ),
+            // Collinear, disjoint.
+            (
+                segment(0.0, 0.0, 1.0, 0.0),
+                segment(2.0, 0.0, 3.0, 0.0),
+                SegmentIntersection::None,
+            ),
+            // Degenerate segment on another one.
+            (
+                segment(0.0, 0.0, 2.0, 2.0),
+                segment(1.0, 1.0, 1.0, 1.0),
+                point(1.0, 1.0),
+            ),
+        ];
+
+        for (s, t, want) in tests_array {
+            assert_eq!(want, s.intersection(t));
+            if let SegmentIntersection::Point(_) = want {
+                assert_eq!(want, t.reversed().intersection(s));
+            }
+            assert_eq!(want != SegmentIntersection::None, t.intersects(s));
+        }
+    }
+
+    fn brute_force(segments: &[Segment]) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for i in 0..segments.len() {
+            for j in i + 1..segments.len() {
+                if segments[i].intersects(segments[j]) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+
+        return pairs;
+    }
+
+    fn pairs(segments: &[Segment]) -> Vec<(usize, usize)> {
+        let intersections = segment_intersections(segments);
+        for &(i, j, intersection) in &intersections {
+            assert_eq!(segments[i].intersection(segments[j]), intersection);
+        }
+
+        return intersections.iter().map(|&(i, j, _)| (i, j)).collect();
+    }
+
+    #[test]
+    fn sweep_random() {
+        let mut random = random_sequence(42);
+
+        let segments: Vec<Segment> = (0..200)
+            .map(|_| {
+                let (x, y) = (random() * 100.0, random() * 100.0);
+                segment(x, y, x + random() * 30.0 - 15.0, y + random() * 30.0 - 15.0)
+            })
+            .collect();
+
+        assert_eq!(brute_force(&segments), pairs(&segments));
+    }
+
+    #[test]
+    fn sweep_degeneracies() {
+        let mut segments = Vec::new();
+        // A grid of horizontal and vertical segments, some of them overlapping.
+        for k in 0..5 {
+            let k = k as f64;
+            segments.push(segment(0.0, k, 4.0, k));
+            segments.push(segment(k, 0.0, k, 4.0));
+        }
+        segments.push(segment(1.0, 2.0, 3.0, 2.0));
+        segments.push(segment(2.0, 1.0, 2.0, 3.0));
+        // A star of segments through (2, 2) and a few points.
+        segments.push(segment(0.0, 0.0, 4.0, 4.0));
+        segments.push(segment(0.0, 4.0, 4.0, 0.0));
+        segments.push(segment(0.0, 3.0, 4.0, 1.0));
+        segments.push(segment(2.0, 2.0, 2.0, 2.0));
+        segments.push(segment(0.5, 0.5, 0.5, 0.5));
+        segments.push(segment(5.0, 5.0, 6.0, 6.0));
+
+        assert_eq!(brute_force(&segments), pairs(&segments));
+    }
+
+    #[test]
+    fn self_crossing_outline() {
+        // A bow tie: its two diagonals cross, and consecutive edges share their
+        // endpoints.
+        let outline = [
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 2.0, y: 2.0 },
+            Point { x: 2.0, y: 0.0 },
+            Point { x: 0.0, y: 2.0 },
+        ];
+        let edges: Vec<Segment> = (0..4)
+            .map(|i| Segment {
+                a: outline[i],
+                b: outline[(i + 1) % 4],
+            })
+            .collect();
+
+        let crossings: Vec<(usize, usize, SegmentIntersection)> = segment_intersections(&edges)
+            .into_iter()
+            .filter(|&(i, j, _)| j != i + 1 && !(i == 0 && j == 3))
+            .collect();
+
+        assert_eq!(vec![(0, 2, point(1.0, 1.0))], crossings);
+    }
+}
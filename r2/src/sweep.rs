@@ -0,0 +1,282 @@
+use crate::rect::*;
+use r1::__private::ExpiryQueue;
+use r1::IntervalTree;
+
+// Batch algorithms over slices of rectangles, based on a sweep along the x-axis.
+// Rectangles are closed, so rectangles sharing only an edge or a corner do
+// intersect, and empty rectangles never intersect anything, as for
+// Rect::intersects.
+
+// rect_join returns the pairs (i, j) such that a[i] intersects b[j], in
+// O((n + k) log n) where n is the total number of rectangles and k the number of
+// reported pairs. The pairs are not returned in any particular order.
+pub fn rect_join(a: &[Rect], b: &[Rect]) -> Vec<(usize, usize)> {
+    let sides = [a, b];
+
+    let mut events: Vec<(f64, usize, usize)> = Vec::with_capacity(a.len() + b.len());
+    for (side, rects) in sides.iter().enumerate() {
+        for (index, r) in rects.iter().enumerate() {
+            if !r.is_empty() {
+                events.push((r.x.lo, side, index));
+            }
+        }
+    }
+    events.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    // The rectangles crossing the sweep line, indexed by their y-interval.
+    let mut active: [IntervalTree<usize>; 2] = [IntervalTree::new(), IntervalTree::new()];
+    let mut expiry = [ExpiryQueue::new(), ExpiryQueue::new()];
+    let mut pairs = Vec::new();
+
+    for (lo, side, index) in events {
+        for s in 0..2 {
+            while let Some(expired) = expiry[s].expire(lo) {
+                active[s].remove_entry(sides[s][expired].y, &expired);
+            }
+        }
+
+        let r = sides[side][index];
+        for (_, &j) in active[1 - side].intersecting(r.y) {
+            if side == 0 {
+                pairs.push((index, j));
+            } else {
+                pairs.push((j, index));
+            }
+        }
+
+        active[side].insert(r.y, index);
+        expiry[side].push(r.x.hi, index);
+    }
+
+    return pairs;
+}
+
+// CoverageTree is a segment tree over the elementary slabs between consecutive
+// y-coordinates, recording how many rectangles cover each node.
+struct CoverageTree {
+    ys: Vec<f64>,
+    // Number of rectangles covering the whole node, and largest depth found
+    // below it, for rect_overlap_depth.
+    count: Vec<i64>,
+    depth: Vec<i64>,
+    // Length covered by at least one rectangle below the node, for
+    // rect_coverage_area.
+    covered: Vec<f64>,
+}
+
+impl CoverageTree {
+    // new builds a tree over n leaves. Leaf k stands for the point ys[k] when
+    // computing depths and for the slab [ys[k], ys[k + 1]] when computing areas.
+    fn new(ys: Vec<f64>, n: usize) -> CoverageTree {
+        let size = 4 * n.max(1);
+        CoverageTree {
+            ys,
+            count: vec![0; size],
+            depth: vec![0; size],
+            covered: vec![0.0; size],
+        }
+    }
+
+    // add adds delta to the leaves in [lo, hi] of the node covering [start, end].
+    #[allow(clippy::too_many_arguments)]
+    fn add(&mut self, node: usize, start: usize, end: usize, lo: usize, hi: usize, delta: i64) {
+        if hi < start || end < lo {
+            return;
+        }
+
+        if lo <= start && end <= hi {
+            self.count[node] += delta;
+        } else {
+            let mid = (start + end) / 2;
+            self.add(2 * node, start, mid, lo, hi, delta);
+            self.add(2 * node + 1, mid + 1, end, lo, hi, delta);
+        }
+
+        self.update(node, start, end);
+    }
+
+    fn update(&mut self, node: usize, start: usize, end: usize) {
+        let leaf = start == end;
+
+        self.depth[node] = self.count[node];
+        if !leaf {
+            self.depth[node] += self.depth[2 * node].max(self.depth[2 * node + 1]);
+        }
+
+        if self.count[node] > 0 {
+            // Trees built for depths have no slab after their last point.
+            self.covered[node] = self.ys.get(end + 1).map_or(0.0, |hi| hi - self.ys[start]);
+        } else if leaf {
+            self.covered[node] = 0.0;
+        } else {
+            self.covered[node] = self.covered[2 * node] + self.covered[2 * node + 1];
+        }
+    }
+}
+
+// sorted_ys returns the distinct y-coordinates of the bounds of the rectangles.
+fn sorted_ys(rects: &[Rect]) -> Vec<f64> {
+    let mut ys: Vec<f64> = rects.iter().flat_map(|r| [r.y.lo, r.y.hi]).collect();
+    ys.sort_by(|a, b| a.total_cmp(b));
+    ys.dedup();
+
+    return ys;
+}
+
+fn y_index(ys: &[f64], y: f64) -> usize {
+    return ys.partition_point(|&v| v < y);
+}
+
+// rect_overlap_depth returns the largest number of rectangles sharing a common
+// point, in O(n log n).
+pub fn rect_overlap_depth(rects: &[Rect]) -> usize {
+    let rects: Vec<Rect> = rects.iter().copied().filter(|r| !r.is_empty()).collect();
+    if rects.is_empty() {
+        return 0;
+    }
+
+    // Starts (-1) are sorted before ends (+1) at the same abscissa since the
+    // rectangles are closed.
+    let mut events: Vec<(f64, i64, usize)> = Vec::with_capacity(2 * rects.len());
+    for (index, r) in rects.iter().enumerate() {
+        events.push((r.x.lo, -1, index));
+        events.push((r.x.hi, 1, index));
+    }
+    events.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    let ys = sorted_ys(&rects);
+    let n = ys.len();
+    let mut tree = CoverageTree::new(ys, n);
+
+    let mut max_depth = 0;
+    for (_, kind, index) in events {
+        let r = rects[index];
+        let lo = y_index(&tree.ys, r.y.lo);
+        let hi = y_index(&tree.ys, r.y.hi);
+
+        tree.add(1, 0, n - 1, lo, hi, -kind);
+        if kind < 0 {
+            max_depth = max_depth.max(tree.depth[1]);
+        }
+    }
+
+    return max_depth as usize;
+}
+
+// rect_coverage_area returns the area of the union of the rectangles, counting
+// overlapping parts once, in O(n log n).
+pub fn rect_coverage_area(rects: &[Rect]) -> f64 {
+    let rects: Vec<Rect> = rects.iter().copied().filter(|r| r.area() > 0.0).collect();
+    if rects.is_empty() {
+        return 0.0;
+    }
+
+    let mut events: Vec<(f64, i64, usize)> = Vec::with_capacity(2 * rects.len());
+    for (index, r) in rects.iter().enumerate() {
+        events.push((r.x.lo, 1, index));
+        events.push((r.x.hi, -1, index));
+    }
+    events.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let ys = sorted_ys(&rects);
+    let slabs = ys.len() - 1;
+    let mut tree = CoverageTree::new(ys, slabs);
+
+    let mut area = 0.0;
+    let mut last_x = events[0].0;
+    for (x, delta, index) in events {
+        area += tree.covered[1] * (x - last_x);
+        last_x = x;
+
+        let r = rects[index];
+        let lo = y_index(&tree.ys, r.y.lo);
+        let hi = y_index(&tree.ys, r.y.hi);
+        tree.add(1, 0, slabs - 1, lo, hi - 1, delta);
+    }
+
+    return area;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::Point;
+
+    fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> Rect {
+        rect_from_points(&[Point { x: x0, y: y0 }, Point { x: x1, y: y1 }])
+    }
+
+    #[test]
+    fn join() {
+        let a = [
+            rect(0.0, 0.0, 1.0, 1.0),
+            rect(2.0, 2.0, 3.0, 3.0),
+            rect(0.0, 5.0, 10.0, 6.0),
+            empty_rect(),
+        ];
+        let b = [
+            rect(1.0, 1.0, 2.0, 2.0),
+            rect(0.5, -1.0, 0.6, 0.0),
+            rect(4.0, 0.0, 5.0, 10.0),
+            rect(2.5, 2.5, 2.6, 2.6),
+        ];
+
+        let mut pairs = rect_join(&a, &b);
+        pairs.sort();
+
+        let mut want: Vec<(usize, usize)> = Vec::new();
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                if x.intersects(*y) {
+                    want.push((i, j));
+                }
+            }
+        }
+
+        assert_eq!(want, pairs);
+        assert_eq!(vec![(0, 0), (0, 1), (1, 0), (1, 3), (2, 2)], pairs);
+    }
+
+    #[test]
+    fn overlap_depth() {
+        assert_eq!(0, rect_overlap_depth(&[]));
+        assert_eq!(
+            2,
+            rect_overlap_depth(&[rect(0.0, 0.0, 1.0, 1.0), rect(1.0, 1.0, 2.0, 2.0)])
+        );
+        assert_eq!(
+            1,
+            rect_overlap_depth(&[rect(0.0, 0.0, 1.0, 1.0), rect(2.0, 0.0, 3.0, 1.0)])
+        );
+        assert_eq!(
+            3,
+            rect_overlap_depth(&[
+                rect(0.0, 0.0, 10.0, 10.0),
+                rect(1.0, 1.0, 3.0, 3.0),
+                rect(2.0, 2.0, 4.0, 4.0),
+                rect(3.5, 0.0, 5.0, 1.0),
+            ])
+        );
+    }
+
+    #[test]
+    fn coverage_area() {
+        assert_eq!(0.0, rect_coverage_area(&[]));
+        assert_eq!(
+            7.0,
+            rect_coverage_area(&[rect(0.0, 0.0, 2.0, 2.0), rect(1.0, 1.0, 3.0, 3.0)])
+        );
+        assert_eq!(
+            100.0,
+            rect_coverage_area(&[
+                rect(0.0, 0.0, 10.0, 10.0),
+                rect(1.0, 1.0, 3.0, 3.0),
+                rect(5.0, 5.0, 5.0, 8.0),
+            ])
+        );
+        assert_eq!(
+            2.0,
+            rect_coverage_area(&[rect(0.0, 0.0, 1.0, 1.0), rect(2.0, 0.0, 3.0, 1.0)])
+        );
+    }
+}
@@ -0,0 +1,261 @@
+// SweepStatus is the ordered list of the segments crossing the sweep line of
+// segment_intersections, from bottom to top.
+//
+// It is an implicit treap: a randomized balanced binary tree ordered by the
+// position of its items in the list rather than by a key, so that the order of
+// the segments is never recomputed from their ordinates. Nodes are stored by
+// item and linked to their parent, which gives the position of an item, the
+// item at a position and the replacement of a run of items in O(log n) expected
+// time.
+pub(crate) struct SweepStatus {
+    nodes: Vec<Node>,
+    root: usize,
+}
+
+// NIL stands for a missing node.
+const NIL: usize = usize::MAX;
+
+#[derive(Clone, Copy)]
+struct Node {
+    left: usize,
+    right: usize,
+    parent: usize,
+    // size is the number of nodes of the subtree.
+    size: usize,
+    // priority is larger than the ones of the children of the node.
+    priority: u64,
+    present: bool,
+}
+
+impl SweepStatus {
+    // new returns an empty list which may hold the items 0..capacity.
+    pub(crate) fn new(capacity: usize) -> SweepStatus {
+        let nodes = (0..capacity)
+            .map(|item| Node {
+                left: NIL,
+                right: NIL,
+                parent: NIL,
+                size: 1,
+                priority: priority(item),
+                present: false,
+            })
+            .collect();
+
+        return SweepStatus { nodes, root: NIL };
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        return self.size(self.root);
+    }
+
+    pub(crate) fn contains(&self, item: usize) -> bool {
+        return self.nodes[item].present;
+    }
+
+    // position returns the position of an item of the list.
+    pub(crate) fn position(&self, item: usize) -> usize {
+        let mut position = self.size(self.nodes[item].left);
+        let mut node = item;
+        while self.nodes[node].parent != NIL {
+            let parent = self.nodes[node].parent;
+            if self.nodes[parent].right == node {
+                position += self.size(self.nodes[parent].left) + 1;
+            }
+            node = parent;
+        }
+
+        return position;
+    }
+
+    // get returns the item at the given position, which must be in the list.
+    pub(crate) fn get(&self, position: usize) -> usize {
+        let mut node = self.root;
+        let mut position = position;
+        loop {
+            let left = self.size(self.nodes[node].left);
+            if position < left {
+                node = self.nodes[node].left;
+            } else if position == left {
+                return node;
+            } else {
+                position -= left + 1;
+                node = self.nodes[node].right;
+            }
+        }
+    }
+
+    // partition_point returns the number of leading items of the list for which
+    // pred holds. pred must hold for the items before some position and not for
+    // the ones after it, as for slice::partition_point.
+    pub(crate) fn partition_point(&self, pred: impl Fn(usize) -> bool) -> usize {
+        let mut count = 0;
+        let mut node = self.root;
+        while node != NIL {
+            if pred(node) {
+                count += self.size(self.nodes[node].left) + 1;
+                node = self.nodes[node].right;
+            } else {
+                node = self.nodes[node].left;
+            }
+        }
+
+        return count;
+    }
+
+    // replace removes the items at the positions lo..hi and inserts the given
+    // ones, which must not be in the list anymore, in their place and order. It
+    // returns the removed items, in order.
+    pub(crate) fn replace(&mut self, lo: usize, hi: usize, items: &[usize]) -> Vec<usize> {
+        let (left, rest) = self.split(self.root, lo);
+        let (middle, right) = self.split(rest, hi - lo);
+
+        let mut removed = Vec::with_capacity(hi - lo);
+        self.collect(middle, &mut removed);
+        for &item in &removed {
+            self.nodes[item].present = false;
+        }
+
+        let mut inserted = NIL;
+        for &item in items {
+            let node = &mut self.nodes[item];
+            node.left = NIL;
+            node.right = NIL;
+            node.size = 1;
+            node.present = true;
+            inserted = self.merge(inserted, item);
+        }
+
+        let root = self.merge(left, inserted);
+        self.root = self.merge(root, right);
+        if self.root != NIL {
+            self.nodes[self.root].parent = NIL;
+        }
+
+        return removed;
+    }
+
+    fn size(&self, node: usize) -> usize {
+        if node == NIL {
+            return 0;
+        }
+
+        return self.nodes[node].size;
+    }
+
+    fn set_children(&mut self, node: usize, left: usize, right: usize) {
+        self.nodes[node].left = left;
+        self.nodes[node].right = right;
+        self.nodes[node].size = self.size(left) + self.size(right) + 1;
+        for child in [left, right] {
+            if child != NIL {
+                self.nodes[child].parent = node;
+            }
+        }
+    }
+
+    // split splits the subtree of node into the subtrees of its first count
+    // items and of the other ones.
+    fn split(&mut self, node: usize, count: usize) -> (usize, usize) {
+        if node == NIL {
+            return (NIL, NIL);
+        }
+
+        let Node { left, right, .. } = self.nodes[node];
+        let left_size = self.size(left);
+        if count <= left_size {
+            let (first, second) = self.split(left, count);
+            self.set_children(node, second, right);
+            return (first, node);
+        }
+
+        let (first, second) = self.split(right, count - left_size - 1);
+        self.set_children(node, left, first);
+        return (node, second);
+    }
+
+    // merge returns the concatenation of the subtrees of a and b.
+    fn merge(&mut self, a: usize, b: usize) -> usize {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+
+        if self.nodes[a].priority > self.nodes[b].priority {
+            let Node { left, right, .. } = self.nodes[a];
+            let merged = self.merge(right, b);
+            self.set_children(a, left, merged);
+            return a;
+        }
+
+        let Node { left, right, .. } = self.nodes[b];
+        let merged = self.merge(a, left);
+        self.set_children(b, merged, right);
+        return b;
+    }
+
+    // collect appends the items of the subtree of node to items, in order.
+    fn collect(&self, node: usize, items: &mut Vec<usize>) {
+        if node == NIL {
+            return;
+        }
+
+        self.collect(self.nodes[node].left, items);
+        items.push(node);
+        self.collect(self.nodes[node].right, items);
+    }
+}
+
+// priority returns the pseudo-random priority of an item, from the finalizer of
+// SplitMix64. It depends on the item only, so that sweeps are reproducible.
+fn priority(item: usize) -> u64 {
+    let mut z = (item as u64).wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    return z ^ (z >> 31);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn replace_like_a_vec() {
+        let capacity = 300;
+        let mut random = random_bits(8);
+        let mut status = SweepStatus::new(capacity);
+        let mut list: Vec<usize> = Vec::new();
+
+        for _ in 0..2000 {
+            let lo = random() as usize % (list.len() + 1);
+            let hi = lo + random() as usize % (list.len() - lo + 1).min(4);
+            let removed: Vec<usize> = list.drain(lo..hi).collect();
+
+            let mut items: Vec<usize> = removed.clone();
+            for _ in 0..random() % 4 {
+                let item = random() as usize % capacity;
+                if !list.contains(&item) && !items.contains(&item) {
+                    items.push(item);
+                }
+            }
+            items.retain(|_| !random().is_multiple_of(4));
+            list.splice(lo..lo, items.iter().copied());
+
+            assert_eq!(removed, status.replace(lo, hi, &items));
+            assert_eq!(list.len(), status.len());
+            for (position, &item) in list.iter().enumerate() {
+                assert_eq!(item, status.get(position));
+                assert_eq!(position, status.position(item));
+            }
+            for item in 0..capacity {
+                assert_eq!(list.contains(&item), status.contains(item));
+            }
+
+            let count = random() as usize % (list.len() + 1);
+            let before = |item: usize| list[..count].contains(&item);
+            assert_eq!(count, status.partition_point(before));
+        }
+    }
+}
@@ -0,0 +1,21 @@
+// Deterministic pseudo-random numbers for the randomized tests, drawn from a
+// small linear congruential generator so that every run sees the same inputs.
+
+// random_bits returns a generator of 53-bit pseudo-random integers, started from
+// the given seed.
+pub fn random_bits(seed: u64) -> impl FnMut() -> u64 {
+    let mut state = seed;
+    return move || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        return state >> 11;
+    };
+}
+
+// random_sequence returns a generator of pseudo-random doubles uniformly
+// distributed in [0, 1), started from the given seed.
+pub fn random_sequence(seed: u64) -> impl FnMut() -> f64 {
+    let mut bits = random_bits(seed);
+    return move || bits() as f64 / (1u64 << 53) as f64;
+}
@@ -0,0 +1,331 @@
+use crate::point::*;
+use crate::rect::*;
+use r1::Tolerance;
+
+// Transform is an affine transformation of the plane, mapping (x, y) to
+//
+//   (xx * x + xy * y + tx, yx * x + yy * y + ty).
+//
+// Transforms are built from the identity with the translate, rotate, scale and
+// shear methods, each of which applies a further step after the current ones, so
+// that chains read in the order the steps are applied:
+//
+//   Transform::identity().scale(2.0).translate(Point { x: 1.0, y: 0.0 })
+//
+// scales by 2 around the origin, then moves right by 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transform {
+    pub xx: f64,
+    pub xy: f64,
+    pub yx: f64,
+    pub yy: f64,
+    pub tx: f64,
+    pub ty: f64,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+impl Transform {
+    // identity returns the transform leaving every point in place.
+    pub fn identity() -> Transform {
+        Transform {
+            xx: 1.0,
+            xy: 0.0,
+            yx: 0.0,
+            yy: 1.0,
+            tx: 0.0,
+            ty: 0.0,
+        }
+    }
+
+    // translation returns the transform moving every point by v.
+    pub fn translation(v: Point) -> Transform {
+        Transform {
+            tx: v.x,
+            ty: v.y,
+            ..Transform::identity()
+        }
+    }
+
+    // rotation returns the counter-clockwise rotation by the given angle in
+    // radians around the origin.
+    pub fn rotation(angle: f64) -> Transform {
+        let (sin, cos) = angle.sin_cos();
+        Transform {
+            xx: cos,
+            xy: -sin,
+            yx: sin,
+            yy: cos,
+            tx: 0.0,
+            ty: 0.0,
+        }
+    }
+
+    // scaling returns the transform scaling x by sx and y by sy around the origin.
+    // Negative factors mirror across the corresponding axis.
+    pub fn scaling(sx: f64, sy: f64) -> Transform {
+        Transform {
+            xx: sx,
+            yy: sy,
+            ..Transform::identity()
+        }
+    }
+
+    // shearing returns the transform mapping (x, y) to (x + kx * y, y + ky * x).
+    pub fn shearing(kx: f64, ky: f64) -> Transform {
+        Transform {
+            xy: kx,
+            yx: ky,
+            ..Transform::identity()
+        }
+    }
+
+    // compose returns the transform applying t first, then this transform.
+    pub fn compose(self, t: Transform) -> Transform {
+        return Transform {
+            xx: self.xx * t.xx + self.xy * t.yx,
+            xy: self.xx * t.xy + self.xy * t.yy,
+            yx: self.yx * t.xx + self.yy * t.yx,
+            yy: self.yx * t.xy + self.yy * t.yy,
+            tx: self.xx * t.tx + self.xy * t.ty + self.tx,
+            ty: self.yx * t.tx + self.yy * t.ty + self.ty,
+        };
+    }
+
+    // then returns the transform applying this transform first, then t.
+    pub fn then(self, t: Transform) -> Transform {
+        return t.compose(self);
+    }
+
+    // translate returns this transform followed by a translation by v.
+    pub fn translate(self, v: Point) -> Transform {
+        return self.then(Transform::translation(v));
+    }
+
+    // rotate returns this transform followed by a counter-clockwise rotation by
+    // the given angle in radians around the origin.
+    pub fn rotate(self, angle: f64) -> Transform {
+        return self.then(Transform::rotation(angle));
+    }
+
+    // rotate_around returns this transform followed by a counter-clockwise
+    // rotation by the given angle in radians around the given center.
+    pub fn rotate_around(self, angle: f64, center: Point) -> Transform {
+        return self
+            .translate(center.mul(-1.0))
+            .rotate(angle)
+            .translate(center);
+    }
+
+    // scale returns this transform followed by a uniform scaling by s around the
+    // origin.
+    pub fn scale(self, s: f64) -> Transform {
+        return self.then(Transform::scaling(s, s));
+    }
+
+    // scale_xy returns this transform followed by a scaling of x by sx and of y by
+    // sy around the origin.
+    pub fn scale_xy(self, sx: f64, sy: f64) -> Transform {
+        return self.then(Transform::scaling(sx, sy));
+    }
+
+    // shear returns this transform followed by the shearing of kx and ky, see
+    // Transform::shearing.
+    pub fn shear(self, kx: f64, ky: f64) -> Transform {
+        return self.then(Transform::shearing(kx, ky));
+    }
+
+    // determinant returns the determinant of the linear part of the transform:
+    // the factor by which it multiplies areas, negative when it mirrors the plane.
+    pub fn determinant(self) -> f64 {
+        return self.xx * self.yy - self.xy * self.yx;
+    }
+
+    // is_invertible reports whether the transform is a bijection, that is whether
+    // its determinant is a finite non-zero number.
+    pub fn is_invertible(self) -> bool {
+        let det = self.determinant();
+        return det != 0.0 && det.is_finite();
+    }
+
+    // invert returns the transform undoing this one, or None if it collapses the
+    // plane onto a line or a point.
+    pub fn invert(self) -> Option<Transform> {
+        if !self.is_invertible() {
+            return None;
+        }
+
+        let det = self.determinant();
+        let xx = self.yy / det;
+        let xy = -self.xy / det;
+        let yx = -self.yx / det;
+        let yy = self.xx / det;
+        return Some(Transform {
+            xx,
+            xy,
+            yx,
+            yy,
+            tx: -(xx * self.tx + xy * self.ty),
+            ty: -(yx * self.tx + yy * self.ty),
+        });
+    }
+
+    // transform_point returns the image of the point.
+    pub fn transform_point(self, p: Point) -> Point {
+        return Point {
+            x: self.xx * p.x + self.xy * p.y + self.tx,
+            y: self.yx * p.x + self.yy * p.y + self.ty,
+        };
+    }
+
+    // transform_vector returns the image of the displacement v, which is not
+    // affected by the translation part of the transform.
+    pub fn transform_vector(self, v: Point) -> Point {
+        return Point {
+            x: self.xx * v.x + self.xy * v.y,
+            y: self.yx * v.x + self.yy * v.y,
+        };
+    }
+
+    // transform_points returns the images of the points, in order.
+    pub fn transform_points(self, points: &[Point]) -> Vec<Point> {
+        return points.iter().map(|&p| self.transform_point(p)).collect();
+    }
+
+    // transform_rect returns the bound of the image of the rectangle, that is of
+    // its transformed vertices. The image itself is a parallelogram, equal to the
+    // result only for transforms without rotation or shear. The image of an empty
+    // rectangle is empty.
+    pub fn transform_rect(self, r: Rect) -> Rect {
+        if r.is_empty() {
+            return empty_rect();
+        }
+
+        return rect_from_points(&r.vertices().map(|p| self.transform_point(p)));
+    }
+
+    // approx_equal reports whether the coefficients of the two transforms are
+    // equal up to the default tolerance.
+    pub fn approx_equal(self, t: Transform) -> bool {
+        return self.approx_equal_with(t, Tolerance::default());
+    }
+
+    // approx_equal_with reports whether the coefficients of the two transforms are
+    // equal up to the given tolerance.
+    pub fn approx_equal_with(self, t: Transform, tolerance: Tolerance) -> bool {
+        return tolerance.approx_eq(self.xx, t.xx)
+            && tolerance.approx_eq(self.xy, t.xy)
+            && tolerance.approx_eq(self.yx, t.yx)
+            && tolerance.approx_eq(self.yy, t.yy)
+            && tolerance.approx_eq(self.tx, t.tx)
+            && tolerance.approx_eq(self.ty, t.ty);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn point(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    #[test]
+    fn transform_point() {
+        let tests_array: [(Transform, Point, Point); 7] = [
+            (Transform::identity(), point(3.0, 4.0), point(3.0, 4.0)),
+            (
+                Transform::translation(point(1.0, -2.0)),
+                point(3.0, 4.0),
+                point(4.0, 2.0),
+            ),
+            (
+                Transform::rotation(FRAC_PI_2),
+                point(1.0, 0.0),
+                point(0.0, 1.0),
+            ),
+            (
+                Transform::scaling(2.0, -1.0),
+                point(3.0, 4.0),
+                point(6.0, -4.0),
+            ),
+            (
+                Transform::shearing(1.0, 0.0),
+                point(3.0, 4.0),
+                point(7.0, 4.0),
+            ),
+            // Steps apply in the order they are chained.
+            (
+                Transform::identity().scale(2.0).translate(point(1.0, 0.0)),
+                point(1.0, 1.0),
+                point(3.0, 2.0),
+            ),
+            (
+                Transform::identity().rotate_around(FRAC_PI_2, point(1.0, 1.0)),
+                point(2.0, 1.0),
+                point(1.0, 2.0),
+            ),
+        ];
+
+        for (t, p, want) in tests_array {
+            assert!(t.transform_point(p).approx_equal(want));
+        }
+
+        let t = Transform::translation(point(1.0, 1.0)).scale(3.0);
+        assert_eq!(point(3.0, 0.0), t.transform_vector(point(1.0, 0.0)));
+        assert_eq!(
+            vec![point(6.0, 3.0), point(3.0, 6.0)],
+            t.transform_points(&[point(1.0, 0.0), point(0.0, 1.0)])
+        );
+    }
+
+    #[test]
+    fn compose_and_invert() {
+        let t = Transform::identity()
+            .scale_xy(2.0, 0.5)
+            .shear(0.25, -1.0)
+            .rotate(0.7)
+            .translate(point(-3.0, 5.0));
+        assert!((t.determinant() - 1.25).abs() < 1e-12);
+
+        let inverse = t.invert().unwrap();
+        assert!(t
+            .compose(inverse)
+            .approx_equal_with(Transform::identity(), Tolerance::Absolute(1e-12)));
+        assert!(inverse
+            .compose(t)
+            .approx_equal_with(Transform::identity(), Tolerance::Absolute(1e-12)));
+
+        let p = point(1.5, -2.5);
+        assert!(inverse
+            .transform_point(t.transform_point(p))
+            .approx_equal_with(p, Tolerance::Absolute(1e-12)));
+
+        assert_eq!(None, Transform::scaling(1.0, 0.0).invert());
+        assert_eq!(None, Transform::shearing(1.0, 1.0).invert());
+        assert_eq!(-1.0, Transform::scaling(-1.0, 1.0).determinant());
+    }
+
+    #[test]
+    fn transform_rect() {
+        let r = rect_from_points(&[point(0.0, 0.0), point(2.0, 1.0)]);
+
+        let t = Transform::identity()
+            .scale_xy(-1.0, 2.0)
+            .translate(point(1.0, 1.0));
+        let want = rect_from_points(&[point(-1.0, 1.0), point(1.0, 3.0)]);
+        assert!(t.transform_rect(r).approx_equal(want));
+
+        let t = Transform::rotation(FRAC_PI_2);
+        let want = rect_from_points(&[point(-1.0, 0.0), point(0.0, 2.0)]);
+        assert!(t.transform_rect(r).approx_equal(want));
+
+        assert!(t.transform_rect(empty_rect()).is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+use crate::clip::*;
+use crate::delaunay::*;
+use crate::point::*;
+use crate::polygon::*;
+use crate::rect::*;
+
+// voronoi_cells returns the cells of the Voronoi diagram of the points, clipped
+// to the given rectangle: the cell of a point is the convex region of the plane
+// closer to it than to any other point. Cells are returned in the order of the
+// points, as counter-clockwise polygons without holes, and are empty when they
+// do not meet the rectangle. The cell of a duplicate point belongs to its first
+// occurrence, the others getting an empty cell.
+//
+// Each cell is computed as the rectangle cut by the bisectors between the point
+// and its neighbors in the Delaunay triangulation, the dual of the diagram. Its
+// vertices are rounded, so neighboring cells may overlap or leave gaps of the
+// order of the rounding error.
+pub fn voronoi_cells(points: &[Point], bound: Rect) -> Vec<Polygon> {
+    let mut cells = vec![Polygon::default(); points.len()];
+    if bound.is_empty() {
+        return cells;
+    }
+
+    let (sites, neighbors) = voronoi_neighbors(points);
+    for site in sites {
+        let p = points[site];
+        let mut ring = bound.vertices().to_vec();
+        for &other in &neighbors[site] {
+            let q = points[other];
+            let middle = p.add(q).mul(0.5);
+            let direction = q.sub(p);
+            ring = clip_ring_to_line(
+                &ring,
+                |x| x.sub(middle).dot(direction) <= 0.0,
+                |a, b| {
+                    let t = middle.sub(a).dot(direction) / b.sub(a).dot(direction);
+                    return a.add(b.sub(a).mul(t.clamp(0.0, 1.0)));
+                },
+            );
+        }
+
+        let ring = clean_clipped_ring(ring);
+        if !ring.is_empty() {
+            cells[site] = polygon_from_points(&ring);
+        }
+    }
+
+    return cells;
+}
+
+// voronoi_neighbors returns the points which are not duplicates of an earlier
+// one, and for each of them its neighbors in the Delaunay triangulation. Points
+// that are all collinear have no triangulation, and their neighbors are the
+// points next to them along the line.
+fn voronoi_neighbors(points: &[Point]) -> (Vec<usize>, Vec<Vec<usize>>) {
+    let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); points.len()];
+    let triangulation = delaunay_triangulation(points);
+
+    if triangulation.triangles.is_empty() {
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        order.sort_by(|&i, &j| {
+            let (p, q) = (points[i], points[j]);
+            return p.x.total_cmp(&q.x).then(p.y.total_cmp(&q.y));
+        });
+
+        // The sort is stable, so the first occurrence of a point comes first.
+        let mut sites: Vec<usize> = Vec::new();
+        for i in order {
+            if sites.last().is_none_or(|&j| points[j] != points[i]) {
+                sites.push(i);
+            }
+        }
+        for pair in sites.windows(2) {
+            neighbors[pair[0]].push(pair[1]);
+            neighbors[pair[1]].push(pair[0]);
+        }
+
+        return (sites, neighbors);
+    }
+
+    for (i, j) in triangulation.edges() {
+        neighbors[i].push(j);
+        neighbors[j].push(i);
+    }
+    let sites = (0..points.len())
+        .filter(|&i| !neighbors[i].is_empty())
+        .collect();
+
+    return (sites, neighbors);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    fn points(coords: &[(f64, f64)]) -> Vec<Point> {
+        coords.iter().map(|&(x, y)| Point { x, y }).collect()
+    }
+
+    #[test]
+    fn nearest_site() {
+        let mut random = random_sequence(17);
+
+        // Sites in the unit square, cells clipped to a larger rectangle.
+        let mut sites: Vec<Point> = (0..200)
+            .map(|_| Point {
+                x: random(),
+                y: random(),
+            })
+            .collect();
+        sites.push(sites[10]);
+        let bound = rect_from_points(&points(&[(-0.5, -0.5), (1.5, 1.5)]));
+
+        let cells = voronoi_cells(&sites, bound);
+        assert!(cells[200].is_empty());
+        let area: f64 = cells.iter().map(|c| c.area()).sum();
+        assert!((area - bound.area()).abs() < 1e-9);
+
+        for _ in 0..1000 {
+            let q = Point {
+                x: 2.0 * random() - 0.5,
+                y: 2.0 * random() - 0.5,
+            };
+            let mut distances: Vec<(f64, usize)> = sites
+                .iter()
+                .enumerate()
+                .map(|(i, p)| (p.sub(q).norm(), i))
+                .collect();
+            distances.sort_by(|a, b| a.0.total_cmp(&b.0));
+            if distances[1].0 - distances[0].0 < 1e-9 {
+                continue;
+            }
+            let nearest = distances[0].1;
+            assert!(cells[nearest].contains_point(q));
+            assert!(!cells[distances[1].1].interior_contains_point(q));
+        }
+    }
+
+    #[test]
+    fn degenerate() {
+        let bound = rect_from_points(&points(&[(0.0, 0.0), (4.0, 2.0)]));
+
+        // A single site owns the whole rectangle.
+        let cells = voronoi_cells(&points(&[(1.0, 1.0)]), bound);
+        assert_eq!(8.0, cells[0].area());
+
+        // Collinear sites split it into strips, the last one outside of it.
+        let sites = points(&[(3.0, 1.0), (1.0, 1.0), (1.0, 1.0), (9.0, 1.0)]);
+        let cells = voronoi_cells(&sites, bound);
+        let areas: Vec<f64> = cells.iter().map(|c| c.area()).collect();
+        assert_eq!(vec![4.0, 4.0, 0.0, 0.0], areas);
+        assert!(cells[1]
+            .bound()
+            .approx_equal(rect_from_points(&points(&[(0.0, 0.0), (2.0, 2.0)]))));
+
+        // The four cells of the corners of a square meet at its center.
+        let sites = points(&[(1.0, 0.0), (3.0, 0.0), (3.0, 2.0), (1.0, 2.0)]);
+        for cell in voronoi_cells(&sites, bound) {
+            assert_eq!(2.0, cell.area());
+            assert!(cell.exterior.contains(&Point { x: 2.0, y: 1.0 }));
+        }
+
+        assert!(voronoi_cells(&sites, empty_rect())
+            .iter()
+            .all(|c| c.is_empty()));
+    }
+}
@@ -1,2 +1,5 @@
+#![allow(clippy::needless_return, clippy::should_implement_trait)]
+
 mod vector;
 
+pub use vector::*;
@@ -1,62 +1,43 @@
-#[derive(Debug, PartialEq, Clone, Copy)]
-pub struct Vector {
-    pub x: f64,
-    pub y: f64,
-    pub z: f64,
+use r1::__private::Scanner;
+use r1::{Float, ParseError, Scalar, Tolerance};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::Sum;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign,
+};
+use std::str::FromStr;
+
+// Vector implements Eq, Ord and Hash for every scalar type so that vectors can be
+// used as map keys. The order is the lexicographic one of cmp. To make it total,
+// NaN coordinates compare equal to each other and greater than any number, and
+// -0 and +0 are equal.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vector<T = f64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
 }
 
 // The three axes of ℝ³.
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(i64)]
-enum Axes {
+pub enum Axes {
     XAxis = 0,
     YAxis,
     ZAxis,
 }
 
-impl Vector {
-    pub fn approx_equal(self, v: Vector) -> bool {
-        let epsilon = 1e-16;
-        return (self.x - v.x).abs() < epsilon
-            && (self.y - v.y) < epsilon
-            && (self.z - v.z) < epsilon;
-    }
-
-    pub fn string(self) -> String {
-        return format!("({:.24}, {:.24}, {:.24})", self.x, self.y, self.z);
-    }
-
-    pub fn norm(self) -> f64 {
-        return self.dot(self).sqrt();
-    }
-
+impl<T: Scalar> Vector<T> {
     // norm2 returns the square of the norm.
-    pub fn norm2(self) -> f64 {
+    pub fn norm2(self) -> T {
         return self.dot(self);
     }
 
-    // normalize returns a unit vector in the same direction as v.
-    pub fn normalize(self) -> Vector {
-        let n2 = self.norm2();
-
-        if n2 == 0.0 {
-            return Vector {
-                x: 0.0,
-                y: 0.0,
-                z: 0.0,
-            };
-        }
-
-        return self.mul(1.0 / n2.sqrt());
-    }
-
-    // is_unit returns whether this vector is of approximately unit length.
-    pub fn is_unit(self) -> bool {
-        let epsilon = 5e-14;
-
-        return (self.norm2() - 1.0).abs() <= epsilon;
-    }
-
-    pub fn mul(self, m: f64) -> Vector {
+    pub fn mul(self, m: T) -> Vector<T> {
         return Vector {
             x: m * self.x,
             y: m * self.y,
@@ -65,7 +46,7 @@ impl Vector {
     }
 
     // abs returns the vector with nonnegative components.
-    pub fn abs(self) -> Vector {
+    pub fn abs(self) -> Vector<T> {
         return Vector {
             x: self.x.abs(),
             y: self.y.abs(),
@@ -74,7 +55,7 @@ impl Vector {
     }
 
     // add returns the standard vector sum of v and ov.
-    pub fn add(self, v: Vector) -> Vector {
+    pub fn add(self, v: Vector<T>) -> Vector<T> {
         return Vector {
             x: self.x + v.x,
             y: self.y + v.y,
@@ -83,7 +64,7 @@ impl Vector {
     }
 
     // sub returns the standard vector difference of v and ov.
-    pub fn sub(self, v: Vector) -> Vector {
+    pub fn sub(self, v: Vector<T>) -> Vector<T> {
         return Vector {
             x: self.x - v.x,
             y: self.y - v.y,
@@ -92,12 +73,12 @@ impl Vector {
     }
 
     // dot returns the standard dot product of v and ov.
-    pub fn dot(self, v: Vector) -> f64 {
+    pub fn dot(self, v: Vector<T>) -> T {
         return self.x * v.x + self.y * v.y + self.z * v.z;
     }
 
     // cross returns the standard cross product of v and ov.
-    pub fn cross(self, v: Vector) -> Vector {
+    pub fn cross(self, v: Vector<T>) -> Vector<T> {
         return Vector {
             x: self.y * v.z - self.z * v.y,
             y: self.z * v.x - self.x * v.z,
@@ -105,36 +86,6 @@ impl Vector {
         };
     }
 
-    // distance returns the Euclidean distance between v and ov.
-    pub fn distance(self, v: Vector) -> f64 {
-        return self.sub(v).norm();
-    }
-
-    /* TODO
-    // angle returns the angle between v and ov.
-    func (v Vector) Angle(ov Vector) s1.Angle {
-        return s1.Angle(math.Atan2(v.Cross(ov).Norm(), v.Dot(ov))) * s1.Radian
-    }
-    */
-
-    // ortho returns a unit vector that is orthogonal to v.
-    // ortho(-v) = -ortho(v) for all v.
-    pub fn orhto(self) -> Vector {
-        let mut v = Vector {
-            x: 0.0,
-            y: 0.0,
-            z: 0.0,
-        };
-
-        match self.largest_component() {
-            Axes::XAxis => v.z = 1.0,
-            Axes::YAxis => v.x = 1.0,
-            _ => v.y = 1.0,
-        }
-
-        return self.cross(v).normalize();
-    }
-
     // largest_component returns the axis that represents the largest component in this vector.
     pub fn largest_component(self) -> Axes {
         let v = self.abs();
@@ -156,7 +107,7 @@ impl Vector {
 
     // smallest_component returns the axis that represents the smallest component in this vector.
     pub fn smallest_component(self) -> Axes {
-        let v: Vector = self.abs();
+        let v: Vector<T> = self.abs();
 
         if v.x < v.y {
             if v.x < v.z {
@@ -183,32 +134,445 @@ impl Vector {
     // are compared element by element with the given operator. The first mismatch
     // defines which is less (or greater) than the other. If both have equivalent
     // values they are lexicographically equal.
-    pub fn cmp(self, v: Vector) -> i64 {
-        if self.x < v.x {
-            return -1;
+    pub fn cmp(self, v: Vector<T>) -> i64 {
+        match Ord::cmp(&self, &v) {
+            Ordering::Less => return -1,
+            Ordering::Equal => return 0,
+            Ordering::Greater => return 1,
         }
+    }
+
+    // cast converts the vector to another scalar type, see r1::Scalar::from_f64.
+    pub fn cast<U: Scalar>(self) -> Vector<U> {
+        return Vector {
+            x: U::from_f64(self.x.to_f64()),
+            y: U::from_f64(self.y.to_f64()),
+            z: U::from_f64(self.z.to_f64()),
+        };
+    }
+}
+
+impl<T: Float> Vector<T> {
+    pub fn norm(self) -> T {
+        return self.dot(self).sqrt();
+    }
+
+    // normalize returns a unit vector in the same direction as v.
+    pub fn normalize(self) -> Vector<T> {
+        let n2 = self.norm2();
 
-        if self.x > v.x {
-            return 1;
+        if n2 == T::zero() {
+            return Vector {
+                x: T::zero(),
+                y: T::zero(),
+                z: T::zero(),
+            };
         }
 
-        // First elements were the same, try the next.
-        if self.y < v.y {
-            return -1;
+        return self.mul(T::one() / n2.sqrt());
+    }
+
+    // distance returns the Euclidean distance between v and ov.
+    pub fn distance(self, v: Vector<T>) -> T {
+        return self.sub(v).norm();
+    }
+
+    /* TODO
+    // angle returns the angle between v and ov.
+    func (v Vector<T>) Angle(ov Vector<T>) s1.Angle {
+        return s1.Angle(math.Atan2(v.Cross(ov).Norm(), v.Dot(ov))) * s1.Radian
+    }
+    */
+
+    // ortho returns a unit vector that is orthogonal to v.
+    // ortho(-v) = -ortho(v) for all v.
+    pub fn orhto(self) -> Vector<T> {
+        let mut v = Vector {
+            x: T::zero(),
+            y: T::zero(),
+            z: T::zero(),
+        };
+
+        match self.largest_component() {
+            Axes::XAxis => v.z = T::one(),
+            Axes::YAxis => v.x = T::one(),
+            _ => v.y = T::one(),
         }
-        if self.y > v.y {
-            return 1;
+
+        return self.cross(v).normalize();
+    }
+}
+
+impl Vector {
+    // approx_equal reports whether the coordinates of the two vectors are equal up
+    // to the default tolerance.
+    pub fn approx_equal(self, v: Vector) -> bool {
+        return self.approx_equal_with(v, Tolerance::default());
+    }
+
+    // approx_equal_with reports whether the coordinates of the two vectors are
+    // equal up to the given tolerance.
+    pub fn approx_equal_with(self, v: Vector, tolerance: Tolerance) -> bool {
+        return tolerance.approx_eq(self.x, v.x)
+            && tolerance.approx_eq(self.y, v.y)
+            && tolerance.approx_eq(self.z, v.z);
+    }
+
+    pub fn string(self) -> String {
+        return format!("({:.24}, {:.24}, {:.24})", self.x, self.y, self.z);
+    }
+
+    // is_unit returns whether this vector is of approximately unit length. The
+    // squared norm accumulates the rounding errors of several operations, hence a
+    // looser tolerance than the approx_equal default.
+    pub fn is_unit(self) -> bool {
+        return self.is_unit_with(Tolerance::Absolute(5e-14));
+    }
+
+    // is_unit_with returns whether the squared norm of this vector is equal to 1 up
+    // to the given tolerance.
+    pub fn is_unit_with(self, tolerance: Tolerance) -> bool {
+        return tolerance.approx_eq(self.norm2(), 1.0);
+    }
+}
+
+// Display writes the vector in the "(x, y, z)" form used by string(). Formatting
+// options apply to every coordinate, so format!("{:.24}", v) is v.string(),
+// while the default shortest representation parses back to the very same vector.
+impl<T: fmt::Display> fmt::Display for Vector<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(")?;
+        fmt::Display::fmt(&self.x, f)?;
+        f.write_str(", ")?;
+        fmt::Display::fmt(&self.y, f)?;
+        f.write_str(", ")?;
+        fmt::Display::fmt(&self.z, f)?;
+        f.write_str(")")
+    }
+}
+
+// FromStr parses the "(x, y, z)" form written by string() and Display.
+impl<T: FromStr> FromStr for Vector<T> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut scanner = Scanner::new(s);
+
+        scanner.expect("(")?;
+        let x = scanner.number()?;
+        scanner.expect(",")?;
+        let y = scanner.number()?;
+        scanner.expect(",")?;
+        let z = scanner.number()?;
+        scanner.expect(")")?;
+        scanner.finish()?;
+
+        return Ok(Vector { x, y, z });
+    }
+}
+
+// compare_component compares two coordinates, ordering NaN after every number.
+fn compare_component<T: Scalar>(a: T, b: T) -> Ordering {
+    #[allow(clippy::eq_op)]
+    let (a_nan, b_nan) = (a != a, b != b);
+
+    match a.partial_cmp(&b) {
+        Some(ordering) => ordering,
+        None => a_nan.cmp(&b_nan),
+    }
+}
+
+impl<T: Scalar> PartialEq for Vector<T> {
+    fn eq(&self, v: &Vector<T>) -> bool {
+        return Ord::cmp(self, v) == Ordering::Equal;
+    }
+}
+
+impl<T: Scalar> Eq for Vector<T> {}
+
+impl<T: Scalar> PartialOrd for Vector<T> {
+    fn partial_cmp(&self, v: &Vector<T>) -> Option<Ordering> {
+        return Some(Ord::cmp(self, v));
+    }
+}
+
+impl<T: Scalar> Ord for Vector<T> {
+    fn cmp(&self, v: &Vector<T>) -> Ordering {
+        return compare_component(self.x, v.x)
+            .then_with(|| compare_component(self.y, v.y))
+            .then_with(|| compare_component(self.z, v.z));
+    }
+}
+
+// hash_component feeds a coordinate to the hasher such that coordinates equal
+// for compare_component hash the same.
+fn hash_component<T: Scalar, H: Hasher>(c: T, state: &mut H) {
+    let c = c.to_f64();
+    if c.is_nan() {
+        f64::NAN.to_bits().hash(state);
+    } else {
+        // Adding 0 turns -0 into +0.
+        (c + 0.0).to_bits().hash(state);
+    }
+}
+
+impl<T: Scalar> Hash for Vector<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_component(self.x, state);
+        hash_component(self.y, state);
+        hash_component(self.z, state);
+    }
+}
+
+// Arithmetic operators, matching the add, sub and mul methods.
+impl<T: Scalar> Add for Vector<T> {
+    type Output = Vector<T>;
+
+    fn add(self, v: Vector<T>) -> Vector<T> {
+        return Vector::add(self, v);
+    }
+}
+
+impl<T: Scalar> Sub for Vector<T> {
+    type Output = Vector<T>;
+
+    fn sub(self, v: Vector<T>) -> Vector<T> {
+        return Vector::sub(self, v);
+    }
+}
+
+impl<T: Scalar + Neg<Output = T>> Neg for Vector<T> {
+    type Output = Vector<T>;
+
+    fn neg(self) -> Vector<T> {
+        return Vector {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        };
+    }
+}
+
+impl<T: Scalar> Mul<T> for Vector<T> {
+    type Output = Vector<T>;
+
+    fn mul(self, m: T) -> Vector<T> {
+        return Vector::mul(self, m);
+    }
+}
+
+impl<T: Scalar> Div<T> for Vector<T> {
+    type Output = Vector<T>;
+
+    fn div(self, d: T) -> Vector<T> {
+        return Vector {
+            x: self.x / d,
+            y: self.y / d,
+            z: self.z / d,
+        };
+    }
+}
+
+impl<T: Scalar> AddAssign for Vector<T> {
+    fn add_assign(&mut self, v: Vector<T>) {
+        *self = *self + v;
+    }
+}
+
+impl<T: Scalar> SubAssign for Vector<T> {
+    fn sub_assign(&mut self, v: Vector<T>) {
+        *self = *self - v;
+    }
+}
+
+impl<T: Scalar> MulAssign<T> for Vector<T> {
+    fn mul_assign(&mut self, m: T) {
+        *self = *self * m;
+    }
+}
+
+impl<T: Scalar> DivAssign<T> for Vector<T> {
+    fn div_assign(&mut self, d: T) {
+        *self = *self / d;
+    }
+}
+
+impl<T: Scalar> Sum for Vector<T> {
+    fn sum<I: Iterator<Item = Vector<T>>>(iter: I) -> Vector<T> {
+        let zero = Vector {
+            x: T::zero(),
+            y: T::zero(),
+            z: T::zero(),
+        };
+
+        return iter.fold(zero, |acc, v| acc + v);
+    }
+}
+
+impl<'a, T: Scalar> Sum<&'a Vector<T>> for Vector<T> {
+    fn sum<I: Iterator<Item = &'a Vector<T>>>(iter: I) -> Vector<T> {
+        return iter.copied().sum();
+    }
+}
+
+impl<T> Index<Axes> for Vector<T> {
+    type Output = T;
+
+    fn index(&self, axis: Axes) -> &T {
+        match axis {
+            Axes::XAxis => &self.x,
+            Axes::YAxis => &self.y,
+            Axes::ZAxis => &self.z,
         }
+    }
+}
 
-        // Second elements were the same return the final compare.
-        if self.z < v.z {
-            return -1;
+impl<T> IndexMut<Axes> for Vector<T> {
+    fn index_mut(&mut self, axis: Axes) -> &mut T {
+        match axis {
+            Axes::XAxis => &mut self.x,
+            Axes::YAxis => &mut self.y,
+            Axes::ZAxis => &mut self.z,
         }
-        if self.z > v.z {
-            return 1;
+    }
+}
+
+impl<T> From<[T; 3]> for Vector<T> {
+    fn from([x, y, z]: [T; 3]) -> Self {
+        Vector { x, y, z }
+    }
+}
+
+impl<T> From<(T, T, T)> for Vector<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Vector { x, y, z }
+    }
+}
+
+impl<T> From<Vector<T>> for [T; 3] {
+    fn from(v: Vector<T>) -> Self {
+        [v.x, v.y, v.z]
+    }
+}
+
+impl<T> From<Vector<T>> for (T, T, T) {
+    fn from(v: Vector<T>) -> Self {
+        (v.x, v.y, v.z)
+    }
+}
+
+// Lossless conversions between vector precisions. Use Vector::cast for the other
+// ones.
+macro_rules! impl_vector_from {
+    ($($from:ty => $to:ty),*) => {$(
+        impl From<Vector<$from>> for Vector<$to> {
+            fn from(v: Vector<$from>) -> Self {
+                Vector {
+                    x: v.x.into(),
+                    y: v.y.into(),
+                    z: v.z.into(),
+                }
+            }
         }
+    )*};
+}
+
+impl_vector_from!(
+    f32 => f64,
+    i32 => f64,
+    u32 => f64,
+    i16 => f32,
+    u16 => f32,
+    i32 => i64,
+    u32 => u64
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn approx_equal() {
+        let v = Vector {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        };
+        let far = Vector {
+            x: 1.0,
+            y: 1.0,
+            z: 2.0,
+        };
+
+        assert!(v.approx_equal(v));
+        assert!(!v.approx_equal(far));
+        assert!(!far.approx_equal(v));
+        assert!(v.approx_equal_with(far, Tolerance::Absolute(1.0)));
+        assert!(v.normalize().is_unit());
+        assert!(!v.is_unit());
+    }
 
-        // Both are equal
-        return 0;
+    #[test]
+    fn operators() {
+        let mut v = Vector::from([1.0, 2.0, 3.0]);
+        let w = Vector::from((1.0, 0.0, -1.0));
+
+        assert_eq!(Vector::from([2.0, 2.0, 2.0]), v + w);
+        assert_eq!(Vector::from([0.0, 2.0, 4.0]), v - w);
+        assert_eq!(Vector::from([-1.0, -2.0, -3.0]), -v);
+        assert_eq!(Vector::from([2.0, 4.0, 6.0]), v * 2.0);
+        assert_eq!(Vector::from([0.5, 1.0, 1.5]), v / 2.0);
+        assert_eq!(Vector::from([3.0, 2.0, 1.0]), [v, w, w].iter().sum());
+
+        v += w;
+        v -= w * 2.0;
+        v *= 3.0;
+        v /= 3.0;
+        assert_eq!(Vector::from([0.0, 2.0, 4.0]), v);
+
+        let axis = v.largest_component();
+        v[axis] = -1.0;
+        assert_eq!(-1.0, v[Axes::ZAxis]);
+        assert_eq!([0.0, 2.0, -1.0], <[f64; 3]>::from(v));
+    }
+
+    #[test]
+    fn ordering() {
+        let v = |x: f64, y: f64, z: f64| Vector { x, y, z };
+
+        assert_eq!(-1, v(1.0, 2.0, 3.0).cmp(v(1.0, 2.0, 4.0)));
+        assert_eq!(1, v(2.0, 0.0, 0.0).cmp(v(1.0, 5.0, 5.0)));
+        assert_eq!(0, v(-0.0, 0.0, 1.0).cmp(v(0.0, -0.0, 1.0)));
+        assert_eq!(1, v(f64::NAN, 0.0, 0.0).cmp(v(f64::INFINITY, 0.0, 0.0)));
+        assert_eq!(v(f64::NAN, 0.0, 0.0), v(f64::NAN, 0.0, 0.0));
+
+        let mut set = std::collections::HashSet::new();
+        assert!(set.insert(v(0.0, 1.0, 2.0)));
+        assert!(!set.insert(v(-0.0, 1.0, 2.0)));
+        assert!(set.insert(v(f64::NAN, 1.0, 2.0)));
+        assert!(!set.insert(v(f64::NAN, 1.0, 2.0)));
+
+        let mut map = std::collections::BTreeMap::new();
+        map.insert(v(1.0, 0.0, 0.0), "b");
+        map.insert(v(0.0, 9.0, 9.0), "a");
+        assert_eq!(vec!["a", "b"], map.into_values().collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let v = Vector {
+            x: 1.0,
+            y: -2.5,
+            z: 0.0,
+        };
+        let json = serde_json::to_string(&v).unwrap();
+        assert_eq!(r#"{"x":1.0,"y":-2.5,"z":0.0}"#, json);
+        assert_eq!(v, serde_json::from_str(&json).unwrap());
+
+        let cells = Vector { x: 3, y: -4, z: 5 };
+        let json = serde_json::to_string(&cells).unwrap();
+        assert_eq!(r#"{"x":3,"y":-4,"z":5}"#, json);
+        assert_eq!(cells, serde_json::from_str::<Vector<i32>>(&json).unwrap());
     }
 }